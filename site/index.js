@@ -36,13 +36,19 @@ import('../pkg/pga_axioms.js').then((wasm) => {
     const paperBounds = paper.bbox();
 
     // The WASM code needs to know about the dimensions of our paper, which is what
-    // this object represents
-    const paperStruct = new wasm.Paper(
+    // this object represents (note that WASM objects are consumed when passed by value,
+    // so the session gets its own copy)
+    const makePaper = () => new wasm.Paper(
         new wasm.Point(scx - paperSize * 0.5, scy - paperSize * 0.5), // Upper-left
         new wasm.Point(scx + paperSize * 0.5, scy - paperSize * 0.5), // Upper-right
         new wasm.Point(scx + paperSize * 0.5, scy + paperSize * 0.5), // Lower-right
         new wasm.Point(scx - paperSize * 0.5, scy + paperSize * 0.5)  // Lower-left
     );
+    const paperStruct = makePaper();
+
+    // The session owns the inputs to the current axiom, the resulting crease, and the history of
+    // applied folds: the page only forwards user input and renders the results
+    let session = new wasm.FoldSession(makePaper());
 
     const axiomSpecifications = [
         {
@@ -54,8 +60,7 @@ import('../pkg/pga_axioms.js').then((wasm) => {
                 ],
                 'lines': []
            
-            },
-            'function': wasm.axiom_1
+            }
         },
         {
             'description': 'Given two points <b>p0</b> and <b>p1</b>, there is a unique fold that places <b>p0</b> onto <b>p1</b>.',
@@ -66,8 +71,7 @@ import('../pkg/pga_axioms.js').then((wasm) => {
                 ],
                 'lines': []
            
-            },
-            'function': wasm.axiom_2
+            }
         },
         {
             'description': 'Given two lines <b>l0</b> and <b>l1</b>, there is a fold that places <b>l0</b> onto <b>l1</b>.',
@@ -78,8 +82,7 @@ import('../pkg/pga_axioms.js').then((wasm) => {
                     [scx - paperSize * 0.25, scy + paperSize * 0.25, scx + paperSize * 0.25, scy + paperSize * 0.25]
                 ]
            
-            },
-            'function': wasm.axiom_3
+            }
         },
         {
             'description': 'Given a point <b>p</b> and a line <b>l</b>, there is a unique fold perpendicular to <b>l</b> that passes through point <b>p</b>.',
@@ -91,8 +94,7 @@ import('../pkg/pga_axioms.js').then((wasm) => {
                     [scx - paperSize * 0.25, scy, scx + paperSize * 0.25, scy]
                 ]
            
-            },
-            'function': wasm.axiom_4
+            }
         },
         {
             'description': 'Given two points <b>p0</b> and <b>p1</b> and a line <b>l</b>, there is a fold that places <b>p0</b> onto <b>l</b> and passes through <b>p1</b>.',
@@ -106,7 +108,24 @@ import('../pkg/pga_axioms.js').then((wasm) => {
                 ]
            
             },
-            'function': wasm.axiom_5
+            // The creases are tangent to the parabola with focus p0 and directrix l0 (pairs
+            // of point and line indices)
+            'envelopes': [[0, 0]]
+        },
+        {
+            'description': 'Given two points <b>p0</b> and <b>p1</b> and two lines <b>l0</b> and <b>l1</b>, there is a fold that places <b>p0</b> onto <b>l0</b> and <b>p1</b> onto <b>l1</b>.',
+            'inputs': {
+                'points': [
+                    [scx - paperSize * 0.125, scy - paperSize * 0.25],
+                    [scx + paperSize * 0.125, scy + paperSize * 0.25]
+                ],
+                'lines': [
+                    [scx - paperSize * 0.25, scy, scx + paperSize * 0.25, scy],
+                    [scx - paperSize * 0.25, scy - paperSize * 0.25, scx - paperSize * 0.25, scy + paperSize * 0.25]
+                ]
+           
+            },
+            'envelopes': [[0, 0], [1, 1]]
         },
         {
             'description': 'Given one point <b>p</b> and two lines <b>l0</b> and <b>l1</b>, there is a fold that places <b>p</b> onto <b>l0</b> and is perpendicular to <b>l1</b>.',
@@ -119,8 +138,7 @@ import('../pkg/pga_axioms.js').then((wasm) => {
                     [scx, scy + paperSize * 0.125, scx, scy - paperSize * 0.125]
                 ]
            
-            },
-            'function': wasm.axiom_7
+            }
         }
 
     ];
@@ -155,6 +173,9 @@ import('../pkg/pga_axioms.js').then((wasm) => {
     negative.insertAfter(positive);
     crease.insertAfter(negative)
 
+    // The parabolas that the crease is tangent to (for axioms 5 and 6), drawn behind everything else
+    let envelopes = [];
+
 
 
 
@@ -192,6 +213,11 @@ import('../pkg/pga_axioms.js').then((wasm) => {
 
 
 
+    // Displays the reason why the current fold is invalid (or clears it, if `reason` is null).
+    function setStatus(reason) {
+        document.getElementById('status').innerHTML = reason != null ? reason : '';
+    }
+
     // Keeps the element within the bounds of the paper.
     function checkPaperBoundaries(e) {
         const { handler, box } = e.detail
@@ -215,48 +241,92 @@ import('../pkg/pga_axioms.js').then((wasm) => {
         handler.move(x, y);
     }
 
-    // A function that calls into WASM code to calculate a new crease based
-    // on the current state of the paper.
-    function callCurrentAxiom() {
-        // Retrieve all of the relevant points and convert them into WASM objects
-        const pointCoords = draw.children()
-            .filter(elem => elem.hasClass('point'))
-            .map(elem => new wasm.Point(elem.cx(), elem.cy()));
+    // The draggable points that are the inputs to the current axiom, in the order that the
+    // session expects: the free points, followed by the two endpoints of each line
+    let handles = [];
 
-        const segmentEndpointCoords = draw.children()
-            .filter(elem => elem.hasClass('segment'))
-            .map(element => [ 
-                new wasm.Point(...element.array().slice(0, 2)),
-                new wasm.Point(...element.array().slice(2, 4))
-            ]);
-
-        const coords = pointCoords.concat(segmentEndpointCoords);
-        
-        // Run current axiom - points then lines (in that order)
-        const results = currentAxiom.function(
-            paperStruct,
-            ...coords
+    const toWasmPoint = handle => new wasm.Point(handle.cx(), handle.cy());
+
+    // The creases of the folds that have been applied so far
+    let appliedCreases = [];
+
+    // Redraws the creases of the applied folds.
+    function renderAppliedCreases() {
+        appliedCreases.forEach(line => line.remove());
+        appliedCreases = session.creases.map(line =>
+            drawLineFromCoeffs(line.a, line.b, line.c)
+                .attr({
+                    'stroke': '#7f7f7f',
+                    'stroke-dasharray': 'none',
+                    'stroke-width': lineWidth * 0.5
+                })
+                .insertAfter(paper)
         );
+    }
 
-        // WASM code will return `null` if no valid creases are found
-        if (results != null && results.line.a != null && results.line.b != null && results.line.c != null) {
-            // Rebuild the crease and update the cut polygons
-            crease.remove();
-            crease = drawLineFromCoeffs(results.line.a, results.line.b, results.line.c);
-            crease.insertAfter(negative);
-            positive.plot(results.positive.map(pt => [pt.x, pt.y]));
-            negative.plot(results.negative.map(pt => [pt.x, pt.y]));
-        } else {
-            // Otherwise, a valid crease wasn't found, so hide the crease and cut polygons
+    // Redraws the crease, cut polygons, and envelopes, given a function that calls into the
+    // session (which will throw an error if no valid creases are found).
+    function render(computeResults) {
+        // Redraw the envelope(s) - line endpoints are stored after the free points
+        envelopes.forEach(envelope => envelope.remove());
+        envelopes = (currentAxiom.envelopes || []).map(([pointIndex, lineIndex]) => {
+            const lineStart = currentAxiom.inputs.points.length + lineIndex * 2;
+            const envelope = wasm.parabola_envelope(
+                paperStruct,
+                toWasmPoint(handles[pointIndex]),
+                toWasmPoint(handles[lineStart]),
+                toWasmPoint(handles[lineStart + 1]),
+                64
+            );
+
+            return draw.polyline(envelope.map(pt => [pt.x, pt.y]))
+                .attr({
+                    'fill': 'none',
+                    'stroke': '#8f8fbf',
+                    'stroke-width': lineWidth * 0.5
+                })
+                .insertAfter(paper);
+        });
+
+        let results;
+        try {
+            results = computeResults();
+        } catch (error) {
+            // A valid crease wasn't found, so hide the crease and cut polygons
             crease.remove();
             positive.plot([]);
             negative.plot([]);
+            setStatus(error.message);
+            return;
         }
+
+        // Rebuild the crease and update the cut polygons
+        const line = results.crease;
+        crease.remove();
+        crease = drawLineFromCoeffs(line.a, line.b, line.c);
+        crease.insertAfter(negative);
+        positive.plot(results.positive.map(pt => [pt.x, pt.y]));
+        negative.plot(results.negative.map(pt => [pt.x, pt.y]));
+
+        // The crease may be mathematically correct but impossible to fold on the actual paper
+        const reason = results.reason;
+        if (reason != null) {
+            crease.attr('stroke', '#999999');
+        }
+        setStatus(reason);
     }
 
-    const pointDragCallback = function(e) {
+    // Returns a callback that forwards the new position of the handle at `index` to the session.
+    const pointDragCallback = index => function(e) {
         checkPaperBoundaries(e);
-        callCurrentAxiom();
+
+        // Snap to nearby corners, edges, creases, crease intersections, and midpoints
+        const snap = session.snap(toWasmPoint(handles[index]), pointRadius);
+        if (snap !== undefined) {
+            handles[index].center(snap.point.x, snap.point.y);
+        }
+        updateSegments();
+        render(() => session.move_point(index, toWasmPoint(handles[index])));
     };
 
     // Iterates through all of the SVG elements that represent interactive
@@ -287,38 +357,38 @@ import('../pkg/pga_axioms.js').then((wasm) => {
             });
     }
 
-    const segmentCallback = function(e) {
-        updateSegments();
-        pointDragCallback(e);
-    }
-
     function clear() {
         // Delete all existing points and lines
         draw.children()
             .filter(elem => elem.hasClass('segment') || elem.hasClass('point'))
             .forEach(elem => elem.remove());
 
-        // Remove the existing crease and cut polygons
+        handles = [];
+
+        // Remove the existing crease, envelopes, and cut polygons
+        envelopes.forEach(envelope => envelope.remove());
+        envelopes = [];
         crease.remove();
         positive.plot([]);
         negative.plot([]);
     }
 
-    function initCurrentAxiom() {
+    function initCurrentAxiom(inputs = currentAxiom.inputs) {
         clear();
+        const lineEndpoints = [];
 
         // Initialize interactive points
-        currentAxiom.inputs.points.forEach(coords => {
+        inputs.points.forEach(coords => {
             let circle = draw.circle(pointRadius)
                 .center(coords[0], coords[1])
                 .addClass('point')
                 .draggable();
 
-            circle.on('dragmove.namespace', pointDragCallback);
+            handles.push(circle);
         });
 
         // Initialize interactive lines
-        currentAxiom.inputs.lines.forEach(coords => {
+        inputs.lines.forEach(coords => {
             // Create the interactive point that represents the source endpoint of this line segment
             let src = draw.circle(pointRadius)
                 .center(...coords.slice(0, 2))
@@ -347,22 +417,50 @@ import('../pkg/pga_axioms.js').then((wasm) => {
 
             src.insertAfter(line);
             dst.insertAfter(line);
-            src.on('dragmove.namespace', segmentCallback);
-            dst.on('dragmove.namespace', segmentCallback);
+            lineEndpoints.push(src, dst);
         });
+
+        // Free points come first, followed by the endpoints of each line
+        handles = handles.concat(lineEndpoints);
+        handles.forEach((handle, index) => handle.on('dragmove.namespace', pointDragCallback(index)));
     }
 
-    function switchAxiom(index) {
+    function showAxiom(index, inputs) {
         // Initialize interactive objects
         currentAxiom = axiomSpecifications[index];
-        initCurrentAxiom();
+        initCurrentAxiom(inputs);
 
         // Set some descriptive info text
         const descriptionP = document.getElementById('description');
         descriptionP.innerHTML = currentAxiom.description;
+    }
 
-        // Update crease and cut polygons 
-        callCurrentAxiom();
+    function switchAxiom(index) {
+        showAxiom(index);
+
+        // Hand the new inputs to the session, and update the crease and cut polygons
+        render(() => session.set_axiom(index + 1, handles.map(toWasmPoint)));
+    }
+
+    // Undoing a fold hands its axiom and inputs back to the session, so move the handles to match
+    function syncAxiom() {
+        const points = session.inputs.map(point => [point.x, point.y]);
+        const pointCount = axiomSpecifications[session.axiom - 1].inputs.points.length;
+        const lines = [];
+        for (let i = pointCount; i + 1 < points.length; i += 2) {
+            lines.push(points[i].concat(points[i + 1]));
+        }
+        showAxiom(session.axiom - 1, { 'points': points.slice(0, pointCount), 'lines': lines });
+        render(() => session.current());
+    }
+
+    // Cycles through the creases of axioms that produce more than one (e.g. both bisectors of
+    // axiom 3)
+    function nextSolution() {
+        render(() => {
+            const results = session.current();
+            return session.set_solution((results.solution + 1) % results.solution_count);
+        });
     }
 
     // Add a key callback for changing between the different axioms.
@@ -375,6 +473,39 @@ import('../pkg/pga_axioms.js').then((wasm) => {
         if (isValidAxiom) {
             switchAxiom(key - 1);
         }
+
+        // Apply the current fold (Enter), step backwards (z) and forwards (y) through the applied
+        // folds, pick the next solution (n), or save (s) and load (l) the tree of applied folds
+        try {
+            if (key === 'Enter') {
+                session.apply_fold();
+            } else if (key === 'z') {
+                if (session.undo()) {
+                    syncAxiom();
+                }
+            } else if (key === 'y') {
+                session.redo();
+            } else if (key === 'n') {
+                nextSolution();
+            } else if (key === 's') {
+                localStorage.setItem('session', session.save());
+                setStatus('Session saved');
+            } else if (key === 'l') {
+                const saved = localStorage.getItem('session');
+                if (saved === null) {
+                    return;
+                }
+                const restored = wasm.FoldSession.restore(makePaper(), saved);
+                session.free();
+                session = restored;
+                switchAxiom(0);
+            } else {
+                return;
+            }
+            renderAppliedCreases();
+        } catch (error) {
+            setStatus(error.message);
+        }
     });
 
     // Kick off the application
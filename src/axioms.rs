@@ -1,4 +1,5 @@
 use crate::geometry;
+use crate::interval::{self, Existence};
 use crate::multivector::*;
 use web_sys::console::dir;
 
@@ -36,82 +37,42 @@ pub fn axiom_4(p: &Multivector, l: &Multivector) -> Multivector {
 
 /// Given two points `p0` and `p1` and a line `l`, there is a fold that places `p0` onto `l` and
 /// passes through `p1`.
-pub fn axiom_5(p0: &Multivector, p1: &Multivector, l: &Multivector) -> Option<Multivector> {
-    // Calculate. the radius of the circle centered on `p1` that is tangent to `p0`
-    let r = geometry::dist_point_to_point(p0, p1);
-
-    // Then, calculate the (shortest) distance from the line to the center of the circle
-    let dist_from_line_to_center = geometry::dist_point_to_line(p1, &l);
-
-    // Exit early if no intersection is possible
-    if dist_from_line_to_center > r {
-        return None;
-    }
-
-    // Constructs a line perpendicular to `l` that passes through `p1`
-    let orthogonal = geometry::orthogonal(p1, &l);
-
-    // Then, "meet" this line with the original line to calculate the point of intersection
-    let mut perpendicular = geometry::intersect_lines(&orthogonal, &l).normalized();
-
-    // "Flip" x/y if e12 is negative
-    perpendicular = perpendicular * perpendicular.e12();
-
-    // Pythagoras' theorem: find the length of the third side of the triangle
-    // whose hypotenuse is `r` and other side is `dist_from_line_to_center`
-    //
-    // We don't need to take the absolute value of the value inside of the sqrt operation
-    // (as in enki's ray tracing code) since we check above that `dist_from_line_to_center`
-    // is less than (or equal to) the radius `r`
-    let d = (r * r - dist_from_line_to_center * dist_from_line_to_center).sqrt();
-
-    // Multiplying a line by e012 has the effect of "pulling out" its direction vector,
-    // represented by an ideal point (i.e. a point at infinity) - this is also known as
-    // metric polarity
-    let mut direction = (*l) * e012;
-    direction /= direction.ideal_norm();
-
-    // If l isn't normalized, we have to do the above or just:
-    // direction = l.normalize() * e012;
-
-    // If there are 2 intersections (i.e., the line "pierces through" the circle), then you
-    // can choose either point of intersection (both are valid) - simply change the `+` to a
-    // `-` (or vice-versa)
-    //
-    // The point of intersection can be found by translating the point `perp` along the line
-    // `l` by an amount `d` (in either direction, in the case of 2 intersections)
-    direction *= d;
-    let intersection = geometry::translate(&perpendicular, direction.e20(), direction.e01());
-
-    // A new line joining the point of intersection and p0
-    let m = intersection.join(p0);
-
-    // A line perpendicular to m that passes through p1: note that this line should always
-    // pass through the midpoint of the line segment `intersection - p0`
-    let crease = geometry::orthogonal(p1, &m);
-
-    Some(crease.normalized())
+///
+/// Every such fold is tangent to the parabola with focus `p0` and directrix `l`, so the solutions
+/// are the tangents to this parabola that pass through `p1`. In general, there are 0, 1, or 2 such
+/// folds, so all of the solutions are returned (see `Parabola::tangents_from` for details). The
+/// result will be empty if no such fold exists.
+pub fn axiom_5(p0: &Multivector, p1: &Multivector, l: &Multivector) -> Vec<Multivector> {
+    let parabola = geometry::Parabola::new(p0, l);
+    parabola.tangents_from(p1)
 }
 
 /// Given two points `p0` and `p1` and two lines `l0` and `l1`, there is a fold that places `p0` onto
 /// `l0` and `p1` onto `l1`.
+///
+/// Every fold that places `p0` onto `l0` is tangent to the parabola with focus `p0` and directrix
+/// `l0`, so this axiom amounts to finding the common tangents of two parabolas. In general, there
+/// are up to 3 such folds (this is the only axiom that requires solving a cubic equation), so all
+/// of the solutions are returned. The result will be empty if no such fold exists.
 pub fn axiom_6(
     p0: &Multivector,
     p1: &Multivector,
     l0: &Multivector,
     l1: &Multivector,
-) -> Multivector {
-    unimplemented!();
+) -> Vec<Multivector> {
+    let parabola_0 = geometry::Parabola::new(p0, l0);
+    let parabola_1 = geometry::Parabola::new(p1, l1);
+
+    parabola_0.common_tangents(&parabola_1)
 }
 
 /// Given one point `p` and two lines `l0` and `l1`, there is a fold that places `p` onto `l0`
 /// and is perpendicular to `l1`.
 pub fn axiom_7(p: &Multivector, l0: &Multivector, l1: &Multivector) -> Option<Multivector> {
-    let angle_between = geometry::angle(l0, l1);
-
     // Lines are parallel - no solution (at least, a solution that does not involve
-    // infinite elements)
-    if (angle_between.abs() - std::f32::consts::PI).abs() <= 0.001 {
+    // infinite elements). Lines that are too close to parallel to tell apart still produce a
+    // crease: `interval::certify_axiom_7` tells whether it certainly exists
+    if interval::certify_axiom_7(p, l0, l1).existence == Existence::Impossible {
         return None;
     }
 
@@ -122,6 +83,9 @@ pub fn axiom_7(p: &Multivector, l0: &Multivector, l1: &Multivector) -> Option<Mu
     // the input lines will, in general, not be normalized or oriented in the same
     // direction
     let mut intersect = shifted.meet(&l0);
+    if intersect.e12() == 0.0 {
+        return None;
+    }
     intersect = intersect.normalized();
     intersect *= intersect.e12();
 
@@ -132,3 +96,53 @@ pub fn axiom_7(p: &Multivector, l0: &Multivector, l1: &Multivector) -> Option<Mu
 
     Some(crease.normalized())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axiom_5() {
+        // Fold <0, 1> onto the x-axis with a crease through the origin: the circle centered at
+        // the origin that passes through <0, 1> meets the x-axis at <-1, 0> and <1, 0>, so
+        // should be: the diagonals `y = x` and `y = -x`
+        let p0 = Multivector::point(0.0, 1.0);
+        let p1 = Multivector::point(0.0, 0.0);
+        let l = Multivector::line(0.0, 1.0, 0.0);
+        let creases = axiom_5(&p0, &p1, &l);
+        assert_eq!(creases.len(), 2);
+        for crease in creases.iter() {
+            println!("Axiom 5: {}", crease);
+            assert!(geometry::dist_point_to_line(&p1, crease).abs() < 0.001);
+            assert!((crease.e1().abs() - crease.e2().abs()).abs() < 0.001);
+            let image = geometry::reflect(&p0, crease);
+            assert!(geometry::dist_point_to_line(&image, &l).abs() < 0.001);
+        }
+
+        // Should be: no solution (the circle centered at <0, 3> doesn't reach the x-axis)
+        assert!(axiom_5(&p0, &Multivector::point(0.0, 3.0), &l).is_empty());
+    }
+
+    #[test]
+    fn test_axiom_6() {
+        // The parabolas `y = x² / 4` (focus <0, 1>, directrix `y = -1`) and `x = y² / 4` (focus
+        // <1, 0>, directrix `x = -1`) have a single common tangent. Should be: `x + y + 1 = 0`
+        let p0 = Multivector::point(0.0, 1.0);
+        let p1 = Multivector::point(1.0, 0.0);
+        let l0 = Multivector::line(0.0, 1.0, 1.0);
+        let l1 = Multivector::line(1.0, 0.0, 1.0);
+        let creases = axiom_6(&p0, &p1, &l0, &l1);
+        assert_eq!(creases.len(), 1);
+        let crease = creases[0];
+        println!("Axiom 6: {}", crease);
+        for p in [Multivector::point(-1.0, 0.0), Multivector::point(0.0, -1.0)].iter() {
+            assert!(geometry::dist_point_to_line(p, &crease).abs() < 0.001);
+        }
+
+        // Each solution places each point onto its line
+        for (p, l) in [(p0, l0), (p1, l1)].iter() {
+            let image = geometry::reflect(p, &crease);
+            assert!(geometry::dist_point_to_line(&image, l).abs() < 0.001);
+        }
+    }
+}
@@ -0,0 +1,643 @@
+use crate::multivector::{Multivector, BASIS_COUNT};
+use crate::scalar::Scalar;
+
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Neg, Not, Sub};
+
+/// The basis blades of 2D PGA (in the same order as `multivector::BASIS_ELEMENTS`), encoded as
+/// bitmasks over the generators `e0`, `e1`, and `e2`, along with the sign that relates each
+/// blade to the "canonical" blade with ascending indices (for example, `e20 = -e02`).
+const BLADES: [(usize, f32); BASIS_COUNT] = [
+    (0b000, 1.0),  // 1
+    (0b001, 1.0),  // e0
+    (0b010, 1.0),  // e1
+    (0b100, 1.0),  // e2
+    (0b011, 1.0),  // e01
+    (0b101, -1.0), // e20
+    (0b110, 1.0),  // e12
+    (0b111, 1.0),  // e012
+];
+
+/// Returns the index of the blade with the specified bitmask in the table `blades`.
+fn blade_index(blades: &[(usize, f32)], mask: usize) -> usize {
+    blades.iter().position(|&(blade, _)| blade == mask).unwrap()
+}
+
+/// Returns the grade of the blade at `index`.
+fn blade_grade(index: usize) -> u32 {
+    BLADES[index].0.count_ones()
+}
+
+/// Computes the geometric product of the basis blades at indices `i` and `j` of the table
+/// `blades` (see `BLADES`), returning the sign and index of the resulting blade (or `None` if
+/// the product is zero, which happens whenever both blades contain the degenerate generator
+/// `e0`). The table can describe any PGA whose first generator is `e0`: this is also used by
+/// `pga3d`.
+pub fn blade_product(blades: &[(usize, f32)], i: usize, j: usize) -> Option<(f32, usize)> {
+    let (a, a_sign) = blades[i];
+    let (b, b_sign) = blades[j];
+
+    // `e0` squares to zero
+    if a & b & 0b001 != 0 {
+        return None;
+    }
+
+    // Count the number of swaps needed to move each generator of `b` past the generators of
+    // `a` that come after it (the other generators square to +1, so they simply cancel
+    // afterwards)
+    let mut swaps = 0;
+    let mut shifted = a >> 1;
+    while shifted != 0 {
+        swaps += (shifted & b).count_ones();
+        shifted >>= 1;
+    }
+
+    let k = blade_index(blades, a ^ b);
+    let sign = if swaps % 2 == 0 { 1.0 } else { -1.0 };
+    Some((sign * a_sign * b_sign * blades[k].1, k))
+}
+
+/// A multivector in 2D PGA whose coefficients are any `Scalar` type (rather than `f32`).
+///
+/// This mirrors the "core" of `Multivector` - the products, join, meet, and the constructors
+/// for points and lines - which is all that the axioms below require. Over exact rationals (see
+/// `rational::ExactMultivector`), axioms 1-4 produce exact results, and over intervals (see
+/// `interval::IntervalMultivector`), every axiom below produces a certified enclosure of its
+/// result. Use `to_multivector` to convert back to floating-point for display.
+///
+/// Rather than hard-coding each product, the products are computed blade-by-blade from the
+/// multiplication table of the algebra (see `blade_product`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenericMultivector<T: Scalar> {
+    /// The coefficients of this multivector.
+    coeff: [T; BASIS_COUNT],
+}
+
+impl<T: Scalar> GenericMultivector<T> {
+    /// Constructs a new multivector with the specified coefficients.
+    pub fn with_coefficients(coeff: [T; BASIS_COUNT]) -> Self {
+        Self { coeff }
+    }
+
+    /// Constructs the zero multivector (i.e. a multivector with all coefficients set to zero).
+    pub fn zeros() -> Self {
+        Self {
+            coeff: [
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+            ],
+        }
+    }
+
+    /// Constructs a multivector that represents a Euclidean point (grade-2 element) with
+    /// coordinates `<x, y>`.
+    pub fn point(x: T, y: T) -> Self {
+        let mut multivector = Self::zeros();
+        multivector[4] = y; // e01, which is dual to e2
+        multivector[5] = x; // e20, which is dual to e1
+        multivector[6] = T::one();
+        multivector
+    }
+
+    /// Constructs a multivector that represents an ideal point (i.e. a point at infinity,
+    /// grade-2 element) with ideal coordinates `<x, y>`.
+    pub fn ideal_point(x: T, y: T) -> Self {
+        let mut multivector = Self::zeros();
+        multivector[4] = y;
+        multivector[5] = x;
+        multivector
+    }
+
+    /// Construct a multivector that represents a line (grade-1 element) with the equation:
+    /// `ax + by + c = 0`.
+    pub fn line(a: T, b: T, c: T) -> Self {
+        let mut multivector = Self::zeros();
+        multivector[1] = c;
+        multivector[2] = a;
+        multivector[3] = b;
+        multivector
+    }
+
+    /// Converts a floating-point multivector (exactly, if the scalar type allows it).
+    pub fn from_multivector(multivector: &Multivector) -> Self {
+        let mut result = Self::zeros();
+        for index in 0..BASIS_COUNT {
+            result[index] = T::from_f32(multivector[index]);
+        }
+        result
+    }
+
+    /// Converts this multivector to a floating-point multivector (for display, or to hand off
+    /// to the rest of the crate).
+    pub fn to_multivector(&self) -> Multivector {
+        let mut coeff = [0.0; BASIS_COUNT];
+        for index in 0..BASIS_COUNT {
+            coeff[index] = self[index].to_f32();
+        }
+        Multivector::with_coefficients(&coeff)
+    }
+
+    /// Returns the scalar part of the multivector.
+    pub fn scalar(&self) -> T {
+        self[0].clone()
+    }
+
+    /// Returns the e0 part of the multivector.
+    pub fn e0(&self) -> T {
+        self[1].clone()
+    }
+
+    /// Returns the e1 part of the multivector.
+    pub fn e1(&self) -> T {
+        self[2].clone()
+    }
+
+    /// Returns the e2 part of the multivector.
+    pub fn e2(&self) -> T {
+        self[3].clone()
+    }
+
+    /// Returns the e01 part of the multivector.
+    pub fn e01(&self) -> T {
+        self[4].clone()
+    }
+
+    /// Returns the e20 part of the multivector.
+    pub fn e20(&self) -> T {
+        self[5].clone()
+    }
+
+    /// Returns the e12 part of the multivector.
+    pub fn e12(&self) -> T {
+        self[6].clone()
+    }
+
+    /// Returns the e012 part of the multivector.
+    pub fn e012(&self) -> T {
+        self[7].clone()
+    }
+
+    /// Computes the Clifford conjugate of the multivector (see `Multivector::conjugation`).
+    pub fn conjugation(&self) -> Self {
+        let mut multivector = self.clone();
+        for index in 1..7 {
+            multivector[index] = -self[index].clone();
+        }
+        multivector
+    }
+
+    /// Computes the join of two multivectors `!(!B ^ !A)` (see `Multivector::join`).
+    pub fn join(&self, rhs: &Self) -> Self {
+        !(!rhs.clone() ^ !self.clone())
+    }
+
+    /// Computes the meet of two multivectors `A ^ B` (see `Multivector::meet`).
+    pub fn meet(&self, rhs: &Self) -> Self {
+        self.clone() ^ rhs.clone()
+    }
+
+    /// Returns the norm of the multivector, or `None` if the scalar type cannot represent it
+    /// (see `Scalar::sqrt`).
+    pub fn norm(&self) -> Option<T> {
+        let multivector = self.clone() * self.conjugation();
+        let squared = multivector.scalar();
+
+        // Equivalent to taking the absolute value (for any ordered scalar type)
+        squared.sqrt().or_else(|| (-multivector.scalar()).sqrt())
+    }
+
+    /// Returns a normalized version of the multivector, or `None` if its norm cannot be
+    /// represented or is zero.
+    pub fn normalized(&self) -> Option<Self> {
+        let norm = self.norm()?;
+        if norm.is_zero() {
+            return None;
+        }
+        Some(self.clone() / norm)
+    }
+
+    /// Divides a point by its homogeneous (e12) coordinate, so that its e20 and e01 parts are
+    /// its Euclidean `x` and `y` coordinates. Unlike `normalized`, this never requires a square
+    /// root. Returns `None` for ideal points.
+    pub fn to_euclidean(&self) -> Option<Self> {
+        let weight = self.e12();
+        if weight.is_zero() {
+            return None;
+        }
+        Some(self.clone() / weight)
+    }
+
+    /// Combines the blades of `self` and `rhs` under the geometric product, keeping only the
+    /// blade products for which `keep(grade of lhs blade, grade of rhs blade, grade of result)`
+    /// is `true`. The inner and outer products are grade-selected versions of the geometric
+    /// product.
+    fn product_filtered(&self, rhs: &Self, keep: fn(u32, u32, u32) -> bool) -> Self {
+        let mut multivector = Self::zeros();
+
+        for i in 0..BASIS_COUNT {
+            if self[i].is_zero() {
+                continue;
+            }
+            for j in 0..BASIS_COUNT {
+                if rhs[j].is_zero() {
+                    continue;
+                }
+                if let Some((sign, k)) = blade_product(&BLADES, i, j) {
+                    if keep(blade_grade(i), blade_grade(j), blade_grade(k)) {
+                        let term = self[i].clone() * rhs[j].clone();
+                        let term = if sign < 0.0 { -term } else { term };
+                        multivector[k] = multivector[k].clone() + term;
+                    }
+                }
+            }
+        }
+
+        multivector
+    }
+}
+
+impl<T: Scalar> Index<usize> for GenericMultivector<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.coeff[index]
+    }
+}
+
+impl<T: Scalar> IndexMut<usize> for GenericMultivector<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.coeff[index]
+    }
+}
+
+/// Computes the join between two multivectors `A & B`.
+impl<T: Scalar> BitAnd for GenericMultivector<T> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.join(&rhs)
+    }
+}
+
+/// Computes the (symmetric) inner product between two multivectors `A | B`: the `|k - s|` grade
+/// part of the product of each grade-`k` blade of `A` with each grade-`s` blade of `B`.
+impl<T: Scalar> BitOr for GenericMultivector<T> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.product_filtered(&rhs, |k, s, grade| {
+            grade == (k as i32 - s as i32).abs() as u32
+        })
+    }
+}
+
+/// Computes the outer product between two multivectors `A ^ B`: the `k + s` grade part of the
+/// product of each grade-`k` blade of `A` with each grade-`s` blade of `B`.
+impl<T: Scalar> BitXor for GenericMultivector<T> {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.product_filtered(&rhs, |k, s, grade| grade == k + s)
+    }
+}
+
+/// Computes the full geometric product between two multivectors `A * B`.
+impl<T: Scalar> Mul for GenericMultivector<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.product_filtered(&rhs, |_, _, _| true)
+    }
+}
+
+/// Multiplies the multivector by a scalar.
+impl<T: Scalar> Mul<T> for GenericMultivector<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        let mut multivector = self;
+        multivector
+            .coeff
+            .iter_mut()
+            .for_each(|elem| *elem = elem.clone() * rhs.clone());
+        multivector
+    }
+}
+
+/// Divides the multivector by a scalar.
+impl<T: Scalar> Div<T> for GenericMultivector<T> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        let mut multivector = self;
+        multivector
+            .coeff
+            .iter_mut()
+            .for_each(|elem| *elem = elem.clone() / rhs.clone());
+        multivector
+    }
+}
+
+/// Adds two multivectors component-wise `A + B`.
+impl<T: Scalar> Add for GenericMultivector<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut multivector = self;
+        for i in 0..BASIS_COUNT {
+            multivector[i] = multivector[i].clone() + rhs[i].clone();
+        }
+        multivector
+    }
+}
+
+/// Subtracts two multivectors component-wise `A - B`.
+impl<T: Scalar> Sub for GenericMultivector<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + (-rhs)
+    }
+}
+
+/// Negates all components of the multivector.
+impl<T: Scalar> Neg for GenericMultivector<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        let mut multivector = self;
+        multivector
+            .coeff
+            .iter_mut()
+            .for_each(|elem| *elem = -elem.clone());
+        multivector
+    }
+}
+
+/// Computes the Poincare dual of this multivector (see `Multivector`'s implementation).
+impl<T: Scalar> Not for GenericMultivector<T> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        let mut multivector = Self::zeros();
+        for i in 0..BASIS_COUNT {
+            multivector[i] = self[BASIS_COUNT - i - 1].clone();
+        }
+        multivector
+    }
+}
+
+// The functions below mirror their counterparts in `geometry` and `axioms` (and are checked
+// against them in `test_axioms_match`, so that the two don't drift apart). The only
+// difference is that points are made Euclidean via `to_euclidean` (which only requires
+// division) rather than `normalized`, and lines are left unnormalized (since lines are
+// homogeneous, this doesn't change the line itself), so that no square roots are required.
+
+/// Returns the midpoint between two Euclidean points `p1` and `p2` (see `geometry::midpoint`).
+pub fn midpoint<T: Scalar>(
+    p1: &GenericMultivector<T>,
+    p2: &GenericMultivector<T>,
+) -> GenericMultivector<T> {
+    let p1 = p1.to_euclidean().expect("Expected a Euclidean point");
+    let p2 = p2.to_euclidean().expect("Expected a Euclidean point");
+
+    (p1 + p2) / (T::one() + T::one())
+}
+
+/// Projects multivector `target` onto multivector `onto` (see `geometry::project`).
+pub fn project<T: Scalar>(
+    target: &GenericMultivector<T>,
+    onto: &GenericMultivector<T>,
+) -> GenericMultivector<T> {
+    (target.clone() | onto.clone()) * onto.clone()
+}
+
+/// Computes the line orthogonal to line `l` that passes through point `p` (see
+/// `geometry::orthogonal`).
+pub fn orthogonal<T: Scalar>(
+    p: &GenericMultivector<T>,
+    l: &GenericMultivector<T>,
+) -> GenericMultivector<T> {
+    p.clone() | l.clone()
+}
+
+/// Reflects the multivector `target` across the multivector `across` (see `geometry::reflect`).
+pub fn reflect<T: Scalar>(
+    target: &GenericMultivector<T>,
+    across: &GenericMultivector<T>,
+) -> GenericMultivector<T> {
+    across.clone() * target.clone() * across.clone()
+}
+
+/// Returns the angle bisector of two lines `l1` and `l2` (see `geometry::bisector`). This
+/// requires normalizing both lines, so it returns `None` if either norm cannot be represented.
+pub fn bisector<T: Scalar>(
+    l1: &GenericMultivector<T>,
+    l2: &GenericMultivector<T>,
+) -> Option<GenericMultivector<T>> {
+    Some(l1.normalized()? + l2.normalized()?)
+}
+
+/// See `axioms::axiom_1`.
+pub fn axiom_1<T: Scalar>(
+    p0: &GenericMultivector<T>,
+    p1: &GenericMultivector<T>,
+) -> GenericMultivector<T> {
+    p0.join(p1)
+}
+
+/// See `axioms::axiom_2`.
+pub fn axiom_2<T: Scalar>(
+    p0: &GenericMultivector<T>,
+    p1: &GenericMultivector<T>,
+) -> GenericMultivector<T> {
+    let l = p0.join(p1);
+    orthogonal(&midpoint(p0, p1), &l)
+}
+
+/// See `axioms::axiom_3`. Returns `None` if the bisector cannot be represented (see `bisector`).
+pub fn axiom_3<T: Scalar>(
+    l0: &GenericMultivector<T>,
+    l1: &GenericMultivector<T>,
+) -> Option<GenericMultivector<T>> {
+    bisector(l0, l1)
+}
+
+/// See `axioms::axiom_4`.
+pub fn axiom_4<T: Scalar>(
+    p: &GenericMultivector<T>,
+    l: &GenericMultivector<T>,
+) -> GenericMultivector<T> {
+    orthogonal(p, l)
+}
+
+/// Returns `r² * |l|² - (p1 ^ l)²`, where `r` is the distance between `p0` and `p1`. The sign of
+/// this quantity determines how many folds place `p0` onto `l` while passing through `p1`: it is
+/// positive if the circle centered at `p1` that passes through `p0` crosses `l` twice (2 folds),
+/// zero if the circle is tangent to `l` (1 fold), and negative otherwise (no folds). Only
+/// products and sums are required (no square roots).
+pub fn axiom_5_discriminant<T: Scalar>(
+    p0: &GenericMultivector<T>,
+    p1: &GenericMultivector<T>,
+    l: &GenericMultivector<T>,
+) -> T {
+    let p0 = p0.to_euclidean().expect("Expected a Euclidean point");
+    let p1 = p1.to_euclidean().expect("Expected a Euclidean point");
+
+    // For Euclidean points with unit weight, the e1 and e2 parts of their join are the
+    // components of the vector between them
+    let chord = p0.join(&p1);
+    let r_squared = chord.e1() * chord.e1() + chord.e2() * chord.e2();
+    let l_squared = l.e1() * l.e1() + l.e2() * l.e2();
+    let distance = (p1 ^ l.clone()).e012();
+
+    r_squared * l_squared - distance.clone() * distance
+}
+
+/// Returns the images of `p0` on the line `l` under the folds that pass through `p1` (see
+/// `axioms::axiom_5`), in the same order as `geometry::Circle::intersect_line`. There will be
+/// 0, 1, or 2 of them, depending on `axiom_5_discriminant` (see `Scalar::sqrt` for how a
+/// discriminant that cannot be represented is treated).
+pub fn axiom_5_images<T: Scalar>(
+    p0: &GenericMultivector<T>,
+    p1: &GenericMultivector<T>,
+    l: &GenericMultivector<T>,
+) -> Vec<GenericMultivector<T>> {
+    let root = match axiom_5_discriminant(p0, p1, l).sqrt() {
+        Some(root) => root,
+        None => return vec![],
+    };
+
+    // Drop a perpendicular from `p1` to the line, then walk along the line (in the direction
+    // of `geometry::direction`) in either direction
+    let foot = project(p1, l)
+        .to_euclidean()
+        .expect("Expected a Euclidean point");
+    let l_squared = l.e1() * l.e1() + l.e2() * l.e2();
+    let offset = GenericMultivector::ideal_point(l.e2(), -l.e1()) * (root.clone() / l_squared);
+
+    if root.is_zero() {
+        return vec![foot];
+    }
+    vec![foot.clone() + offset.clone(), foot - offset]
+}
+
+/// See `axioms::axiom_5`. Returns every solution, in the same order as `axiom_5_images`.
+pub fn axiom_5<T: Scalar>(
+    p0: &GenericMultivector<T>,
+    p1: &GenericMultivector<T>,
+    l: &GenericMultivector<T>,
+) -> Vec<GenericMultivector<T>> {
+    axiom_5_images(p0, p1, l)
+        .iter()
+        .map(|image| orthogonal(p1, &image.join(p0)))
+        .collect()
+}
+
+/// See `axioms::axiom_7`. Returns `None` if the lines are (certainly) parallel.
+pub fn axiom_7<T: Scalar>(
+    p: &GenericMultivector<T>,
+    l0: &GenericMultivector<T>,
+    l1: &GenericMultivector<T>,
+) -> Option<GenericMultivector<T>> {
+    let shifted = project(l1, p);
+    let intersect = shifted.meet(l0).to_euclidean()?;
+    Some(orthogonal(&midpoint(p, &intersect), l1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axioms;
+    use crate::geometry;
+
+    #[test]
+    fn test_products_match_multivector() {
+        // The products computed from the multiplication table should match the hard-coded
+        // products in `Multivector` exactly
+        let a = Multivector::with_coefficients(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        let b = Multivector::with_coefficients(&[-1.0, 3.0, -2.0, 0.5, 4.0, -6.0, 7.0, -8.0]);
+        let a_generic = GenericMultivector::<f32>::from_multivector(&a);
+        let b_generic = GenericMultivector::<f32>::from_multivector(&b);
+
+        assert_eq!(
+            (a_generic.clone() * b_generic.clone()).to_multivector(),
+            a * b
+        );
+        assert_eq!(
+            (a_generic.clone() ^ b_generic.clone()).to_multivector(),
+            a ^ b
+        );
+        assert_eq!(
+            (a_generic.clone() | b_generic.clone()).to_multivector(),
+            a | b
+        );
+        assert_eq!(a_generic.join(&b_generic).to_multivector(), a.join(&b));
+    }
+
+    #[test]
+    fn test_axioms_match() {
+        let p0 = Multivector::point(0.25, 0.5);
+        let p1 = Multivector::point(-1.0, 2.0);
+        let l0 = Multivector::line(1.0, 2.0, -0.5);
+        let l1 = Multivector::line(-3.0, 1.0, 2.0);
+        let generic = |m: &Multivector| GenericMultivector::<f64>::from_multivector(m);
+        let (g_p0, g_p1, g_l0, g_l1) = (generic(&p0), generic(&p1), generic(&l0), generic(&l1));
+
+        // The generic versions aren't normalized, so compare after normalizing (which only ever
+        // rescales by a positive number, so both orientations of axiom 3 are distinguished)
+        let pairs = [
+            ("axiom 1", axioms::axiom_1(&p0, &p1), axiom_1(&g_p0, &g_p1)),
+            ("axiom 2", axioms::axiom_2(&p0, &p1), axiom_2(&g_p0, &g_p1)),
+            (
+                "axiom 3",
+                axioms::axiom_3(&l0, &l1),
+                axiom_3(&g_l0, &g_l1).unwrap(),
+            ),
+            (
+                "axiom 3 (other bisector)",
+                axioms::axiom_3(&l0, &-l1),
+                axiom_3(&g_l0, &-g_l1.clone()).unwrap(),
+            ),
+            ("axiom 4", axioms::axiom_4(&p0, &l0), axiom_4(&g_p0, &g_l0)),
+            (
+                "midpoint",
+                geometry::midpoint(&p0, &p1),
+                midpoint(&g_p0, &g_p1),
+            ),
+            (
+                "reflect",
+                geometry::reflect(&p0, &l0),
+                reflect(&g_p0, &g_l0),
+            ),
+            (
+                "bisector",
+                geometry::bisector(&l0, &l1),
+                bisector(&g_l0, &g_l1).unwrap(),
+            ),
+            (
+                "orthogonal",
+                geometry::orthogonal(&p1, &l1),
+                orthogonal(&g_p1, &g_l1),
+            ),
+            (
+                "project",
+                geometry::project(&p1, &l1),
+                project(&g_p1, &g_l1),
+            ),
+        ];
+        for (name, expected, result) in pairs.iter() {
+            let expected = expected.normalized();
+            let result = result.normalized().unwrap().to_multivector();
+            println!("{}: {} vs. {}", name, expected, result);
+            for index in 0..BASIS_COUNT {
+                assert!((expected[index] - result[index]).abs() < 0.0001);
+            }
+        }
+    }
+}
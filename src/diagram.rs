@@ -0,0 +1,522 @@
+use crate::geometry;
+use crate::interop::{self, AxiomError, Paper};
+use crate::kinematics::Assignment;
+use crate::layers::{FoldMode, LayeredState};
+use crate::multivector::Multivector;
+use crate::pattern::CreasePattern;
+use crate::validation::Placement;
+
+use std::fmt::Display;
+
+/// The width (and height) of each frame, in pixels.
+const FRAME_SIZE: f32 = 240.0;
+
+/// The number of frames in each row of a multi-frame document.
+const FRAMES_PER_ROW: usize = 3;
+
+const FRONT_COLOR: &str = "#ffffff";
+const BACK_COLOR: &str = "#c8d7e6";
+const VALLEY_COLOR: &str = "#1f5fbf";
+const MOUNTAIN_COLOR: &str = "#bf3f1f";
+
+/// A flat facet of the folded paper.
+#[derive(Clone, Debug)]
+pub struct Facet {
+    /// The vertices of the facet, in folded coordinates.
+    pub vertices: Vec<Multivector>,
+
+    /// Whether the back of the paper faces upwards (i.e. the facet has been flipped an odd
+    /// number of times).
+    pub flipped: bool,
+}
+
+/// The reasons why a fold in a diagram may fail.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DiagramError {
+    /// The axiom doesn't produce a crease (see `interop::evaluate_axiom`).
+    Axiom(AxiomError),
+
+    /// The crease can't be folded (see `LayeredState::fold`).
+    Fold(&'static str),
+}
+
+impl Display for DiagramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagramError::Axiom(error) => write!(f, "{}", error),
+            DiagramError::Fold(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<AxiomError> for DiagramError {
+    fn from(error: AxiomError) -> Self {
+        DiagramError::Axiom(error)
+    }
+}
+
+/// A single step of a diagram: the crease that is folded next, and the point whose motion the
+/// arrow shows.
+#[derive(Copy, Clone, Debug)]
+pub struct DiagramStep {
+    pub crease: Multivector,
+    pub assignment: Assignment,
+
+    /// The point that moves, and the side of the crease that moves with it (if `None`, the
+    /// negative side of the crease moves, as in `Paper::intersect`).
+    pub moving: Option<Multivector>,
+
+    /// Whether the fold goes through every layer, or only the top flap.
+    pub mode: FoldMode,
+}
+
+/// A step-by-step folding diagram: the facets of the paper before each fold, and the fold itself.
+///
+/// Each fold is folded flat (see `layers::LayeredState`).
+#[derive(Clone, Debug)]
+pub struct Diagram {
+    /// The facets of the paper before each step (and, at the end, after the last step), ordered
+    /// from the bottom of the stack to the top.
+    pub frames: Vec<Vec<Facet>>,
+    pub steps: Vec<DiagramStep>,
+
+    /// The folded state after every step so far.
+    pub state: LayeredState,
+}
+
+impl Diagram {
+    /// Starts a new diagram on the (unfolded) paper.
+    pub fn new(paper: &Paper) -> Self {
+        let mut diagram = Self {
+            frames: vec![],
+            steps: vec![],
+            state: LayeredState::new(&paper.vertices()),
+        };
+        diagram.frames.push(diagram.current_facets());
+        diagram
+    }
+
+    /// Returns the facets of the paper after every step so far.
+    pub fn facets(&self) -> &[Facet] {
+        self.frames.last().unwrap()
+    }
+
+    /// Folds the paper flat along the specified crease.
+    pub fn fold(&mut self, step: DiagramStep) -> Result<(), &'static str> {
+        self.state.fold(
+            &step.crease,
+            step.assignment,
+            step.moving.as_ref(),
+            step.mode,
+        )?;
+        self.steps.push(step);
+        self.frames.push(self.current_facets());
+        Ok(())
+    }
+
+    /// Evaluates the specified axiom (see `interop::evaluate_axiom`) in the current, folded
+    /// coordinates of the paper, and folds the paper along the resulting crease. For axioms that
+    /// place a point onto a line or another point, that point is the one that moves.
+    ///
+    /// If `solution` is `None`, the first crease that can be folded on the folded paper is used
+    /// (see `can_fold`), or the first one overall, if none of them can.
+    pub fn fold_axiom(
+        &mut self,
+        axiom: u8,
+        points: &[Multivector],
+        lines: &[Multivector],
+        solution: Option<usize>,
+        assignment: Assignment,
+        mode: FoldMode,
+    ) -> Result<(), DiagramError> {
+        let creases = interop::axiom_creases(axiom, points, lines)?;
+        let crease = match solution {
+            Some(index) => *creases
+                .get(index)
+                .ok_or(AxiomError::UnknownSolution(index))?,
+            None => {
+                let placements = interop::axiom_placements(axiom, points, lines);
+                *creases
+                    .iter()
+                    .find(|crease| self.can_fold(crease, &placements))
+                    .unwrap_or(&creases[0])
+            }
+        };
+        let moving = match axiom {
+            2 | 5 | 6 | 7 => Some(points[0]),
+            _ => None,
+        };
+        self.fold(DiagramStep {
+            crease,
+            assignment,
+            moving,
+            mode,
+        })
+        .map_err(DiagramError::Fold)
+    }
+
+    /// Returns `true` if the crease crosses the folded paper, and each of the `placements`
+    /// starts on the folded paper and lands on it (see `validation::validate_fold`, which checks
+    /// the same on the flat paper).
+    fn can_fold(&self, crease: &Multivector, placements: &[Placement]) -> bool {
+        let polygons = self
+            .state
+            .facets
+            .iter()
+            .map(|facet| facet.folded())
+            .collect::<Vec<_>>();
+        let on_paper = |p: &Multivector| {
+            polygons
+                .iter()
+                .any(|polygon| geometry::polygon_contains(polygon, p))
+        };
+
+        polygons
+            .iter()
+            .any(|polygon| geometry::clip_line(crease, polygon).is_some())
+            && placements.iter().all(|placement| {
+                let point = placement.point / placement.point.e12();
+                let mut image = geometry::reflect(&point, crease);
+                image /= image.e12();
+                on_paper(&point) && on_paper(&image)
+            })
+    }
+
+    /// Unfolds the finished model back to the flat sheet, returning its crease pattern (see
+    /// `LayeredState::unfold`).
+    pub fn unfold(&self) -> CreasePattern {
+        self.state.unfold()
+    }
+
+    /// Returns the facets of the current state, in folded coordinates.
+    fn current_facets(&self) -> Vec<Facet> {
+        self.state
+            .facets
+            .iter()
+            .map(|facet| Facet {
+                vertices: facet.folded(),
+                flipped: facet.flipped,
+            })
+            .collect()
+    }
+
+    /// Returns each frame of the diagram as a standalone SVG document. The frame before each
+    /// step shows the folded paper, the crease (dashed for valley folds, dash-dotted for
+    /// mountain folds), and an arrow from the moving point to its destination. The last frame
+    /// shows the finished model.
+    ///
+    /// The SVG uses the same orientation as the paper's coordinates (i.e. the y-axis points
+    /// down, like the canvas of the sketch).
+    pub fn to_svg_frames(&self) -> Vec<String> {
+        let view = self.view();
+        (0..self.frames.len())
+            .map(|index| {
+                format!(
+                    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\">\n{defs}{frame}</svg>\n",
+                    size = FRAME_SIZE,
+                    defs = DEFS,
+                    frame = self.frame(index, &view)
+                )
+            })
+            .collect()
+    }
+
+    /// Returns every frame of the diagram in a single SVG document, arranged in rows.
+    pub fn to_svg(&self) -> String {
+        let view = self.view();
+        let rows = (self.frames.len() + FRAMES_PER_ROW - 1) / FRAMES_PER_ROW;
+        let columns = self.frames.len().min(FRAMES_PER_ROW);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{defs}",
+            w = columns as f32 * FRAME_SIZE,
+            h = rows as f32 * FRAME_SIZE,
+            defs = DEFS
+        );
+        for index in 0..self.frames.len() {
+            let x = (index % FRAMES_PER_ROW) as f32 * FRAME_SIZE;
+            let y = (index / FRAMES_PER_ROW) as f32 * FRAME_SIZE;
+            svg += &format!(
+                "<g transform=\"translate({} {})\">\n{}</g>\n",
+                x,
+                y,
+                self.frame(index, &view)
+            );
+        }
+        svg += "</svg>\n";
+        svg
+    }
+
+    /// Returns the transformation from paper coordinates to frame coordinates that fits every
+    /// frame (the same for all of them, so that the paper doesn't jump around between steps).
+    fn view(&self) -> View {
+        let mut min = [std::f32::INFINITY; 2];
+        let mut max = [std::f32::NEG_INFINITY; 2];
+        for facet in self.frames.iter().flatten() {
+            for p in facet.vertices.iter() {
+                min[0] = min[0].min(p.e20());
+                min[1] = min[1].min(p.e01());
+                max[0] = max[0].max(p.e20());
+                max[1] = max[1].max(p.e01());
+            }
+        }
+
+        // Leave room around the paper for the step number and any arrows that leave the paper
+        let extent = (max[0] - min[0]).max(max[1] - min[1]).max(0.0001);
+        let scale = FRAME_SIZE * 0.7 / extent;
+        View {
+            scale,
+            offset: [
+                (FRAME_SIZE - (max[0] - min[0]) * scale) * 0.5 - min[0] * scale,
+                (FRAME_SIZE - (max[1] - min[1]) * scale) * 0.5 - min[1] * scale,
+            ],
+        }
+    }
+
+    /// Returns the SVG elements of the frame at `index`.
+    fn frame(&self, index: usize, view: &View) -> String {
+        let mut svg = format!(
+            "<text x=\"8\" y=\"20\" font-family=\"sans-serif\" font-size=\"14\">{}</text>\n",
+            index + 1
+        );
+
+        for facet in self.frames[index].iter() {
+            let points = facet
+                .vertices
+                .iter()
+                .map(|p| {
+                    let [x, y] = view.apply(p);
+                    format!("{:.2},{:.2}", x, y)
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            svg += &format!(
+                "<polygon points=\"{}\" fill=\"{}\" stroke=\"#312d33\" stroke-width=\"1.5\" stroke-linejoin=\"round\"/>\n",
+                points,
+                if facet.flipped { BACK_COLOR } else { FRONT_COLOR }
+            );
+        }
+
+        // The last frame shows the finished model
+        let step = match self.steps.get(index) {
+            Some(step) => step,
+            None => return svg,
+        };
+
+        let (color, dashes) = match step.assignment {
+            Assignment::Mountain => (MOUNTAIN_COLOR, "8 3 2 3"),
+            _ => (VALLEY_COLOR, "6 4"),
+        };
+        for facet in self.frames[index].iter() {
+            if let Some(segment) = geometry::clip_line(&step.crease, &facet.vertices) {
+                let [x1, y1] = view.apply(&segment.start);
+                let [x2, y2] = view.apply(&segment.end);
+                svg += &format!(
+                    "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-width=\"2\" stroke-dasharray=\"{}\"/>\n",
+                    x1, y1, x2, y2, color, dashes
+                );
+            }
+        }
+
+        if let Some(moving) = self.arrow_start(index) {
+            let destination = reflect_point(&moving, &step.crease);
+            let [x1, y1] = view.apply(&moving);
+            let [x2, y2] = view.apply(&destination);
+
+            // Bow the arrow to one side, so that it reads as a motion rather than a line
+            let control = [
+                (x1 + x2) * 0.5 - (y2 - y1) * 0.25,
+                (y1 + y2) * 0.5 + (x2 - x1) * 0.25,
+            ];
+            svg += &format!(
+                "<path d=\"M {:.2} {:.2} Q {:.2} {:.2} {:.2} {:.2}\" fill=\"none\" stroke=\"#312d33\" stroke-width=\"1.5\" marker-end=\"url(#arrow)\"/>\n",
+                x1, y1, control[0], control[1], x2, y2
+            );
+        }
+
+        svg
+    }
+
+    /// Returns the point where the arrow for the step at `index` starts: the moving point, if
+    /// there is one, or else the vertex on the moving side that is farthest from the crease.
+    fn arrow_start(&self, index: usize) -> Option<Multivector> {
+        let step = &self.steps[index];
+        if step.moving.is_some() {
+            return step.moving.map(|p| p / p.e12());
+        }
+
+        self.frames[index]
+            .iter()
+            .flat_map(|facet| facet.vertices.iter())
+            .map(|p| (*p, geometry::dist_point_to_line(p, &step.crease)))
+            .filter(|&(_, distance)| distance < -0.001)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(p, _)| p)
+    }
+}
+
+/// The arrowhead used by every frame.
+const DEFS: &str = "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"9\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto\"><path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#312d33\"/></marker></defs>\n";
+
+/// A uniform scale followed by a translation.
+#[derive(Copy, Clone, Debug)]
+struct View {
+    scale: f32,
+    offset: [f32; 2],
+}
+
+impl View {
+    fn apply(&self, p: &Multivector) -> [f32; 2] {
+        let p = (*p) / p.e12();
+        [
+            p.e20() * self.scale + self.offset[0],
+            p.e01() * self.scale + self.offset[1],
+        ]
+    }
+}
+
+/// Reflects the point across the line, returning a normalized (Euclidean) point.
+fn reflect_point(p: &Multivector, l: &Multivector) -> Multivector {
+    let reflected = geometry::reflect(p, l);
+    reflected / reflected.e12()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::paper;
+
+    #[test]
+    fn test_diagram() {
+        let paper = paper();
+        let mut diagram = Diagram::new(&paper);
+
+        // Fold the upper-left corner onto the lower-right corner (a triangle), then fold the
+        // triangle in half, tucking the flap behind
+        let ul = Multivector::point(0.0, 1.0);
+        let lr = Multivector::point(1.0, 0.0);
+        diagram
+            .fold_axiom(
+                2,
+                &[ul, lr],
+                &[],
+                None,
+                Assignment::Valley,
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+        assert_eq!(diagram.facets().len(), 2);
+        assert!(diagram.facets()[1].flipped);
+
+        // Should be: the folded corner lands on the lower-right corner
+        let moved = &diagram.facets()[1].vertices;
+        assert!(moved
+            .iter()
+            .any(|p| geometry::dist_point_to_point(p, &lr) < 0.001));
+
+        diagram
+            .fold_axiom(
+                1,
+                &[lr, Multivector::point(0.5, 0.5)],
+                &[],
+                None,
+                Assignment::Mountain,
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+        assert_eq!(diagram.frames.len(), 3);
+        assert_eq!(diagram.facets().len(), 4);
+
+        // Should be: 3 frames, with one dashed crease and one arrow in each of the first two
+        let frames = diagram.to_svg_frames();
+        assert_eq!(frames.len(), 3);
+        println!("{}", frames[0]);
+        for frame in frames[..2].iter() {
+            assert!(frame.contains("stroke-dasharray"));
+            assert_eq!(frame.matches("marker-end").count(), 1);
+        }
+        assert!(frames[0].contains(VALLEY_COLOR) && frames[1].contains(MOUNTAIN_COLOR));
+        assert!(!frames[2].contains("stroke-dasharray"));
+
+        let svg = diagram.to_svg();
+        assert_eq!(svg.matches("<g transform").count(), 3);
+        assert_eq!(svg.matches("<svg").count(), 1);
+
+        // Should be: the first diagonal (a valley), and the other diagonal, which is a mountain
+        // from the lower-right corner to the center, and a valley beyond it (where it was folded
+        // through the back of the flap)
+        let pattern = diagram.unfold();
+        assert_eq!(pattern.creases.len(), 3);
+        for crease in pattern.creases.iter() {
+            let touches = |p: &Multivector| {
+                geometry::dist_point_to_point(&crease.segment.start, p) < 0.001
+                    || geometry::dist_point_to_point(&crease.segment.end, p) < 0.001
+            };
+            let expected = if touches(&lr) {
+                Assignment::Mountain
+            } else {
+                Assignment::Valley
+            };
+            assert_eq!(crease.assignment, expected);
+        }
+
+        // Should be: failures keep their type, and leave the diagram unchanged
+        let error = diagram
+            .fold_axiom(3, &[], &[], None, Assignment::Valley, FoldMode::AllLayers)
+            .unwrap_err();
+        assert_eq!(error, DiagramError::Axiom(AxiomError::MissingInputs));
+        let error = diagram
+            .fold_axiom(
+                1,
+                &[Multivector::point(2.0, 0.0), Multivector::point(2.0, 1.0)],
+                &[],
+                None,
+                Assignment::Valley,
+                FoldMode::AllLayers,
+            )
+            .unwrap_err();
+        println!("{}", error);
+        assert!(matches!(error, DiagramError::Fold(_)));
+        assert_eq!(diagram.frames.len(), 3);
+    }
+
+    #[test]
+    fn test_fold_axiom_on_folded_paper() {
+        // Fold the upper-left corner onto the lower-right corner, leaving the lower-right
+        // triangle of the square
+        let mut diagram = Diagram::new(&paper());
+        diagram
+            .fold_axiom(
+                2,
+                &[Multivector::point(0.0, 1.0), Multivector::point(1.0, 0.0)],
+                &[],
+                None,
+                Assignment::Valley,
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+
+        // Bisect two lines that meet in the (now empty) upper-left corner: the first bisector,
+        // `y = x + 0.5`, crosses the flat paper but misses the folded paper. Should be: the other
+        // bisector, `x + y = 1`
+        let lines = [
+            Multivector::line(0.0, 1.0, -0.75),
+            Multivector::line(-1.0, 0.0, 0.25),
+        ];
+        diagram
+            .fold_axiom(
+                3,
+                &[],
+                &lines,
+                None,
+                Assignment::Valley,
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+        let crease = diagram.steps[1].crease;
+        println!("{}", crease);
+        for p in [Multivector::point(0.5, 0.5), Multivector::point(1.0, 0.0)].iter() {
+            assert!(geometry::dist_point_to_line(p, &crease).abs() < 0.001);
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use crate::backend::{self, GenericMultivector};
+use crate::geometry;
+use crate::multivector::{Multivector, BASIS_COUNT};
+use crate::scalar::Scalar;
+
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A dual number `value + derivative * ε`, where `ε² = 0`.
+///
+/// Carrying out a computation with dual numbers (instead of ordinary numbers) computes the
+/// derivative of the result alongside the result itself: this is known as forward-mode
+/// automatic differentiation. To differentiate with respect to a particular input, set that
+/// input's derivative to 1 (and all others to 0) before starting the computation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Dual {
+    pub value: f64,
+    pub derivative: f64,
+}
+
+/// A multivector whose coefficients are dual numbers.
+pub type DualMultivector = GenericMultivector<Dual>;
+
+impl Dual {
+    /// Constructs the dual number `value + derivative * ε`.
+    pub fn new(value: f64, derivative: f64) -> Self {
+        Self { value, derivative }
+    }
+
+    /// Constructs a constant (i.e. a dual number with zero derivative).
+    pub fn constant(value: f64) -> Self {
+        Self::new(value, 0.0)
+    }
+
+    /// Constructs a variable (i.e. a dual number with unit derivative): the input that is being
+    /// differentiated with respect to.
+    pub fn variable(value: f64) -> Self {
+        Self::new(value, 1.0)
+    }
+}
+
+impl Scalar for Dual {
+    fn zero() -> Self {
+        Self::constant(0.0)
+    }
+
+    fn one() -> Self {
+        Self::constant(1.0)
+    }
+
+    fn from_f32(value: f32) -> Self {
+        Self::constant(value as f64)
+    }
+
+    fn to_f32(&self) -> f32 {
+        self.value as f32
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0.0 && self.derivative == 0.0
+    }
+
+    /// The derivative of `√x` is `1 / (2√x)`, which doesn't exist at `x = 0` (unless the
+    /// derivative of `x` itself is zero).
+    fn sqrt(&self) -> Option<Self> {
+        if self.value < 0.0 {
+            return None;
+        }
+        if self.value == 0.0 {
+            return if self.derivative == 0.0 {
+                Some(Self::zero())
+            } else {
+                None
+            };
+        }
+        let root = self.value.sqrt();
+        Some(Self::new(root, self.derivative / (2.0 * root)))
+    }
+}
+
+impl Add for Dual {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.value + rhs.value, self.derivative + rhs.derivative)
+    }
+}
+
+impl Sub for Dual {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.value - rhs.value, self.derivative - rhs.derivative)
+    }
+}
+
+/// The product rule: `(a + a'ε)(b + b'ε) = ab + (a'b + ab')ε`.
+impl Mul for Dual {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.value * rhs.value,
+            self.derivative * rhs.value + self.value * rhs.derivative,
+        )
+    }
+}
+
+/// The quotient rule: `(a + a'ε) / (b + b'ε) = a/b + ((a'b - ab') / b²)ε`.
+impl Div for Dual {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.value / rhs.value,
+            (self.derivative * rhs.value - self.value * rhs.derivative) / (rhs.value * rhs.value),
+        )
+    }
+}
+
+impl Neg for Dual {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.value, -self.derivative)
+    }
+}
+
+impl Display for Dual {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} + {}ε", self.value, self.derivative)
+    }
+}
+
+/// Returns the derivative part of each coefficient of the multivector.
+fn derivative_part(multivector: &DualMultivector) -> Multivector {
+    let mut coeff = [0.0; BASIS_COUNT];
+    for index in 0..BASIS_COUNT {
+        coeff[index] = multivector[index].derivative as f32;
+    }
+    Multivector::with_coefficients(&coeff)
+}
+
+/// Converts the Euclidean point `p` to a point made of dual numbers, differentiating with
+/// respect to its `x` coordinate (`axis = Some(0)`), its `y` coordinate (`axis = Some(1)`), or
+/// neither (`axis = None`).
+fn seed(p: &Multivector, axis: Option<usize>) -> DualMultivector {
+    let p = (*p) / p.e12();
+    let coordinate = |value: f32, seeded: bool| {
+        if seeded {
+            Dual::variable(value as f64)
+        } else {
+            Dual::constant(value as f64)
+        }
+    };
+    DualMultivector::point(
+        coordinate(p.e20(), axis == Some(0)),
+        coordinate(p.e01(), axis == Some(1)),
+    )
+}
+
+/// The derivatives of a (normalized) crease with respect to the coordinates of each of the
+/// points used to construct it.
+#[derive(Clone, Debug)]
+pub struct CreaseDerivatives {
+    /// The crease itself.
+    pub crease: Multivector,
+
+    /// For each input point, the derivatives of the crease's coefficients with respect to the
+    /// point's `x` and `y` coordinates (in that order).
+    pub derivatives: Vec<[Multivector; 2]>,
+}
+
+impl CreaseDerivatives {
+    /// Returns how quickly the crease moves at the point `p` (i.e. the rate of change of the
+    /// distance from `p` to the crease) as the input point at `index` is moved in the worst
+    /// possible direction. For example, if this returns `3.0`, misplacing that input by 1mm
+    /// moves the crease by (up to) roughly 3mm near `p`.
+    pub fn sensitivity_at(&self, p: &Multivector, index: usize) -> f32 {
+        let p = (*p) / p.e12();
+
+        // The signed distance from `p` to the (normalized) crease `ax + by + c = 0` is linear
+        // in the crease's coefficients, so its derivative is too
+        let rate = |derivative: &Multivector| {
+            derivative.e1() * p.e20() + derivative.e2() * p.e01() + derivative.e0()
+        };
+        let [dx, dy] = &self.derivatives[index];
+        rate(dx).hypot(rate(dy))
+    }
+}
+
+/// Differentiates the crease produced by `construction` with respect to the coordinates of
+/// each of the (Euclidean) points in `points`. The construction receives the points (as dual
+/// multivectors) in the same order, and can use any of the functions in `backend`.
+///
+/// Returns `None` if the construction fails for the given points, or if its result can't be
+/// normalized.
+pub fn differentiate<F>(points: &[Multivector], construction: F) -> Option<CreaseDerivatives>
+where
+    F: Fn(&[DualMultivector]) -> Option<DualMultivector>,
+{
+    // Seed (at most) one coordinate of one point at a time: `seeded` is the index of the point
+    // and the axis of the coordinate
+    let evaluate = |seeded: Option<(usize, usize)>| {
+        let inputs = points
+            .iter()
+            .enumerate()
+            .map(|(index, p)| match seeded {
+                Some((seeded, axis)) if seeded == index => seed(p, Some(axis)),
+                _ => seed(p, None),
+            })
+            .collect::<Vec<_>>();
+        construction(&inputs)?.normalized()
+    };
+
+    let crease = evaluate(None)?.to_multivector();
+    let mut derivatives = Vec::with_capacity(points.len());
+    for index in 0..points.len() {
+        let dx = derivative_part(&evaluate(Some((index, 0)))?);
+        let dy = derivative_part(&evaluate(Some((index, 1)))?);
+        derivatives.push([dx, dy]);
+    }
+
+    Some(CreaseDerivatives {
+        crease,
+        derivatives,
+    })
+}
+
+/// Differentiates the solution `crease` of axiom 6 (see `axioms::axiom_6`), where the line `l0`
+/// is given by the two points `a0` and `b0`. Axiom 6 requires solving a cubic, so it has no
+/// counterpart in `backend`: instead, the derivatives are found implicitly.
+///
+/// The crease places `p0` onto the point `q(t) = a0 + t(b0 - a0)` of `l0` (it is the
+/// perpendicular bisector of the two), and `t` is chosen so that the crease also places `p1`
+/// onto `l1`. Starting from the `t` of the (floating-point) crease, a single Newton step on
+/// that condition (with dual numbers) gives `t` along with its derivative, which is then
+/// carried through to the crease. Returns `None` if the condition doesn't change with `t`
+/// (i.e. if this is a double root, so a small misplacement can make the fold disappear).
+pub fn axiom_6(
+    p0: &DualMultivector,
+    p1: &DualMultivector,
+    a0: &DualMultivector,
+    b0: &DualMultivector,
+    l1: &DualMultivector,
+    crease: &Multivector,
+) -> Option<DualMultivector> {
+    fn fold(
+        p0: &DualMultivector,
+        a0: &DualMultivector,
+        b0: &DualMultivector,
+        t: Dual,
+    ) -> DualMultivector {
+        let q = a0.clone() + (b0.clone() - a0.clone()) * t;
+        backend::axiom_2(p0, &q)
+    }
+
+    // How far (up to scale) the image of `p1` lies from `l1`
+    fn residual(
+        p0: &DualMultivector,
+        p1: &DualMultivector,
+        a0: &DualMultivector,
+        b0: &DualMultivector,
+        l1: &DualMultivector,
+        t: Dual,
+    ) -> Option<Dual> {
+        let image = backend::reflect(p1, &fold(p0, a0, b0, t)).to_euclidean()?;
+        Some(l1.e1() * image.e20() + l1.e2() * image.e01() + l1.e0())
+    }
+
+    // The parameter of the image of `p0` under the (floating-point) crease
+    let constant = |m: &DualMultivector| GenericMultivector::from_multivector(&m.to_multivector());
+    let (a, b) = (a0.to_multivector(), b0.to_multivector());
+    let mut image = geometry::reflect(&p0.to_multivector(), crease);
+    image /= image.e12();
+    let (dx, dy) = (b.e20() - a.e20(), b.e01() - a.e01());
+    let t = ((image.e20() - a.e20()) * dx + (image.e01() - a.e01()) * dy) / (dx * dx + dy * dy);
+    let t = t as f64;
+
+    // The derivative of the condition with respect to `t` (holding the inputs fixed), and its
+    // value and derivative with respect to the inputs (holding `t` fixed)
+    let slope = residual(
+        &constant(p0),
+        &constant(p1),
+        &constant(a0),
+        &constant(b0),
+        &constant(l1),
+        Dual::variable(t),
+    )?
+    .derivative;
+    if slope == 0.0 {
+        return None;
+    }
+    let condition = residual(p0, p1, a0, b0, l1, Dual::constant(t))?;
+
+    let t = Dual::new(t - condition.value / slope, -condition.derivative / slope);
+    Some(fold(p0, a0, b0, t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axioms;
+    use crate::backend;
+
+    #[test]
+    fn test_dual_numbers() {
+        // d/dx (x² / (x + 1)) at x = 2 should be: (x² + 2x) / (x + 1)² = 8 / 9
+        let x = Dual::variable(2.0);
+        let result = x * x / (x + Dual::one());
+        assert!((result.value - 4.0 / 3.0).abs() < 1e-12);
+        assert!((result.derivative - 8.0 / 9.0).abs() < 1e-12);
+
+        // d/dx √x at x = 4 should be: 1 / 4
+        let result = Dual::variable(4.0).sqrt().unwrap();
+        assert!((result.derivative - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_differentiate() {
+        // Axiom 2: fold <0, 0> onto <1, 0>
+        let points = [Multivector::point(0.0, 0.0), Multivector::point(1.0, 0.0)];
+        let result = differentiate(&points, |p| Some(backend::axiom_2(&p[0], &p[1]))).unwrap();
+        println!("Crease: {}", result.crease);
+
+        // The crease should match the floating-point version of the axiom
+        let expected = axioms::axiom_2(&points[0], &points[1]);
+        for index in 0..BASIS_COUNT {
+            assert!((result.crease[index] - expected[index]).abs() < 0.0001);
+        }
+
+        // Each derivative should match a finite difference
+        let h = 0.001;
+        for index in 0..points.len() {
+            for axis in 0..2 {
+                let mut moved = points;
+                moved[index] = moved[index]
+                    + Multivector::ideal_point(
+                        if axis == 0 { h } else { 0.0 },
+                        if axis == 1 { h } else { 0.0 },
+                    );
+                let approximate = (axioms::axiom_2(&moved[0], &moved[1]) - expected) / h;
+                let derivative = result.derivatives[index][axis];
+                for coefficient in 0..BASIS_COUNT {
+                    assert!((approximate[coefficient] - derivative[coefficient]).abs() < 0.01);
+                }
+            }
+        }
+
+        // Moving either point by 1 unit moves the perpendicular bisector by 1/2 unit (at the
+        // midpoint)
+        let midpoint = Multivector::point(0.5, 0.0);
+        assert!((result.sensitivity_at(&midpoint, 0) - 0.5).abs() < 0.0001);
+        assert!((result.sensitivity_at(&midpoint, 1) - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_axiom_6() {
+        // Fold <0.2, 0.1> onto the line `x = 1` and <0.7, 0.3> onto the line `y = 1`, where
+        // the lines are given by two points each
+        let points = [
+            Multivector::point(0.2, 0.1),
+            Multivector::point(0.7, 0.3),
+            Multivector::point(1.0, 0.0),
+            Multivector::point(1.0, 1.0),
+            Multivector::point(0.0, 1.0),
+            Multivector::point(1.0, 1.0),
+        ];
+        let solve =
+            |p: &[Multivector]| axioms::axiom_6(&p[0], &p[1], &p[2].join(&p[3]), &p[4].join(&p[5]));
+
+        // Orients the crease `l` the same way as `reference`
+        let align = |l: &Multivector, reference: &Multivector| {
+            let l = l.normalized();
+            if l.e1() * reference.e1() + l.e2() * reference.e2() < 0.0 {
+                -l
+            } else {
+                l
+            }
+        };
+
+        let solutions = solve(&points);
+        assert!(!solutions.is_empty());
+        for expected in solutions.iter() {
+            let result = differentiate(&points, |p| {
+                axiom_6(&p[0], &p[1], &p[2], &p[3], &p[4].join(&p[5]), expected)
+            })
+            .unwrap();
+            println!("Crease: {}", result.crease);
+
+            let expected = expected.normalized();
+            let crease = align(&result.crease, &expected);
+            let flipped =
+                result.crease.e1() * expected.e1() + result.crease.e2() * expected.e2() < 0.0;
+            for index in 0..BASIS_COUNT {
+                assert!((crease[index] - expected[index]).abs() < 0.0001);
+            }
+
+            // Each derivative should match a finite difference (following the same solution)
+            let h = 0.0001;
+            for index in 0..points.len() {
+                for axis in 0..2 {
+                    let mut moved = points;
+                    moved[index] = moved[index]
+                        + Multivector::ideal_point(
+                            if axis == 0 { h } else { 0.0 },
+                            if axis == 1 { h } else { 0.0 },
+                        );
+                    let perturbed = solve(&moved)
+                        .iter()
+                        .map(|l| align(l, &expected))
+                        .min_by(|a, b| {
+                            let distance = |l: &Multivector| {
+                                let d = *l - expected;
+                                d.e0().abs() + d.e1().abs() + d.e2().abs()
+                            };
+                            distance(a).partial_cmp(&distance(b)).unwrap()
+                        })
+                        .unwrap();
+                    let approximate = (perturbed - expected) / h;
+                    let derivative = if flipped {
+                        -result.derivatives[index][axis]
+                    } else {
+                        result.derivatives[index][axis]
+                    };
+                    for coefficient in 0..BASIS_COUNT {
+                        assert!((approximate[coefficient] - derivative[coefficient]).abs() < 0.01);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gradient_descent() {
+        // Find the point `p1` on the x-axis such that folding <0, 0> onto `p1` (axiom 2) gives a
+        // crease that passes through the target <0.75, 0>. Should be: <1.5, 0>
+        let p0 = Multivector::point(0.0, 0.0);
+        let target = Multivector::point(0.75, 0.0);
+        let mut p1 = Multivector::point(0.5, 0.0);
+
+        for _ in 0..100 {
+            let result =
+                differentiate(&[p0, p1], |p| Some(backend::axiom_2(&p[0], &p[1]))).unwrap();
+
+            // Minimize the squared distance from the target to the crease
+            let distance = target.e20() * result.crease.e1() + result.crease.e0();
+            let [dx, _] = result.derivatives[1];
+            let gradient = 2.0 * distance * (target.e20() * dx.e1() + dx.e0());
+            p1 = Multivector::point(p1.e20() - gradient, 0.0);
+        }
+        assert!((p1.e20() - 1.5).abs() < 0.001);
+    }
+}
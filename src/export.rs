@@ -0,0 +1,414 @@
+use crate::folding::{self, Crease, Panel};
+use crate::geometry;
+use crate::interop::Paper;
+use crate::multivector::Multivector;
+use crate::pga3d::Multivector3;
+
+use serde_json::json;
+
+/// The colors of the front and back of the paper (as linear RGB), used by both exporters.
+const FRONT_COLOR: [f32; 3] = [0.95, 0.95, 0.9];
+const BACK_COLOR: [f32; 3] = [0.8, 0.3, 0.25];
+
+/// A folded sheet of paper, as a set of flat (convex) facets in 3D.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    /// The vertices of each facet, wound counter-clockwise when seen from the front of the
+    /// paper (i.e. the side of the paper that faces `+z` before it is folded).
+    pub facets: Vec<Vec<[f32; 3]>>,
+}
+
+impl Mesh {
+    /// Constructs a mesh from the folded panels (see `folding::FoldedState`).
+    pub fn from_panels(panels: &[Panel]) -> Self {
+        let facets = panels
+            .iter()
+            .map(|panel| {
+                // Wind each panel counter-clockwise (in the flat paper), so that its front
+                // faces `+z` before it is folded
+                let mut panel = panel.clone();
+                if geometry::polygon_area(&panel.vertices) < 0.0 {
+                    panel.vertices.reverse();
+                }
+                panel.positions()
+            })
+            .collect();
+
+        Self { facets }
+    }
+
+    /// Folds the paper along `crease` by `angle` radians (see `folding::Crease`), and returns
+    /// the two resulting facets. The facets are the polygons on either side of the crease (see
+    /// `Paper::intersect`): the positive side stays in the plane `z = 0`, and the negative side
+    /// is rotated about the crease. A fold angle of π reproduces the flat-folded result of
+    /// `Paper::intersect` (seen from above).
+    pub fn fold(paper: &Paper, crease: &Multivector, angle: f32) -> Self {
+        let (positive, negative) = paper.intersect(crease);
+
+        // `Paper::intersect` reflects the negative side across the crease (i.e. folds it flat),
+        // so reflect it back to recover its original position
+        let positive = positive
+            .into_iter()
+            .map(|p| p.into())
+            .collect::<Vec<Multivector>>();
+        let negative = negative
+            .into_iter()
+            .map(|p| {
+                let mut p = geometry::reflect(&p.into(), crease);
+                p /= p.e12();
+                p
+            })
+            .collect::<Vec<Multivector>>();
+
+        let mut panels = Vec::new();
+        if positive.len() >= 3 {
+            panels.push(Panel {
+                vertices: positive,
+                motor: Multivector3::scalar_value(1.0),
+            });
+        }
+        if negative.len() >= 3 {
+            let centroid = negative
+                .iter()
+                .fold(Multivector::zeros(), |sum, p| sum + *p);
+            let motor =
+                folding::crease_rotation(&Crease::new(crease, angle), &(centroid / centroid.e12()));
+            panels.push(Panel {
+                vertices: negative,
+                motor,
+            });
+        }
+
+        Self::from_panels(&panels)
+    }
+
+    /// Returns the Wavefront OBJ representation of the mesh. Each facet is written twice: once
+    /// with the `front` material, and once (with its winding reversed) with the `back` material.
+    /// The materials are defined in the companion material library (see `to_mtl`), which is
+    /// referenced by name as `material_library`.
+    pub fn to_obj(&self, material_library: &str) -> String {
+        let mut obj = String::from("# Exported by pga_axioms\n");
+        obj.push_str(&format!("mtllib {}\no paper\n", material_library));
+
+        for facet in self.facets.iter() {
+            for [x, y, z] in facet.iter() {
+                obj.push_str(&format!("v {} {} {}\n", x, y, z));
+            }
+        }
+
+        // OBJ indices are 1-based
+        let mut faces = |material: &str, reversed: bool| {
+            obj.push_str(&format!("usemtl {}\n", material));
+            let mut offset = 1;
+            for facet in self.facets.iter() {
+                let mut indices = (offset..offset + facet.len())
+                    .map(|index| index.to_string())
+                    .collect::<Vec<_>>();
+                if reversed {
+                    indices.reverse();
+                }
+                obj.push_str(&format!("f {}\n", indices.join(" ")));
+                offset += facet.len();
+            }
+        };
+        faces("front", false);
+        faces("back", true);
+
+        obj
+    }
+
+    /// Returns the Wavefront material library that defines the `front` and `back` materials
+    /// used by `to_obj`.
+    pub fn to_mtl(&self) -> String {
+        let material = |name: &str, [r, g, b]: [f32; 3]| {
+            format!(
+                "newmtl {}\nKa 0 0 0\nKd {} {} {}\nKs 0 0 0\nd 1\n",
+                name, r, g, b
+            )
+        };
+        format!(
+            "{}\n{}",
+            material("front", FRONT_COLOR),
+            material("back", BACK_COLOR)
+        )
+    }
+
+    /// Returns the binary glTF (`.glb`) representation of the mesh: a single mesh with two
+    /// primitives (the front and back faces of the paper), each with its own material. The
+    /// materials are single-sided, so each side of the paper is only visible from that side.
+    ///
+    /// glTF doesn't allow empty accessors (and requires bounds for the `POSITION` accessor), so
+    /// this returns an error if the mesh doesn't have any facets with at least 3 vertices.
+    pub fn to_glb(&self) -> Result<Vec<u8>, &'static str> {
+        if !self.facets.iter().any(|facet| facet.len() >= 3) {
+            return Err("Cannot export an empty mesh as glTF");
+        }
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut views = Vec::new();
+        let mut accessors = Vec::new();
+        let mut primitives = Vec::new();
+
+        for (material, reversed) in [(0, false), (1, true)].iter() {
+            let mut positions = Vec::new();
+            let mut normals = Vec::new();
+            let mut indices: Vec<u32> = Vec::new();
+
+            for facet in self.facets.iter() {
+                let mut facet = facet.clone();
+                if *reversed {
+                    facet.reverse();
+                }
+                let normal = newell_normal(&facet);
+
+                // Triangulate the (convex) facet as a fan
+                let base = positions.len() as u32;
+                positions.extend(facet.iter().cloned());
+                normals.extend(facet.iter().map(|_| normal));
+                for index in 1..(facet.len() as u32).saturating_sub(1) {
+                    indices.extend(&[base, base + index, base + index + 1]);
+                }
+            }
+
+            let (min, max) = bounds(&positions);
+            let position_view = push_view(&mut buffer, &mut views, &flatten(&positions), 34962);
+            let normal_view = push_view(&mut buffer, &mut views, &flatten(&normals), 34962);
+            let index_bytes = indices
+                .iter()
+                .flat_map(|index| index.to_le_bytes().to_vec())
+                .collect::<Vec<_>>();
+            let index_view = push_view(&mut buffer, &mut views, &index_bytes, 34963);
+
+            let first = accessors.len();
+            accessors.push(json!({
+                "bufferView": position_view,
+                "componentType": 5126,
+                "count": positions.len(),
+                "type": "VEC3",
+                "min": min,
+                "max": max,
+            }));
+            accessors.push(json!({
+                "bufferView": normal_view,
+                "componentType": 5126,
+                "count": normals.len(),
+                "type": "VEC3",
+            }));
+            accessors.push(json!({
+                "bufferView": index_view,
+                "componentType": 5125,
+                "count": indices.len(),
+                "type": "SCALAR",
+            }));
+            primitives.push(json!({
+                "attributes": { "POSITION": first, "NORMAL": first + 1 },
+                "indices": first + 2,
+                "material": material,
+            }));
+        }
+
+        let material = |name: &str, [r, g, b]: [f32; 3]| {
+            json!({
+                "name": name,
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": [r, g, b, 1.0],
+                    "metallicFactor": 0.0,
+                    "roughnessFactor": 0.9,
+                },
+            })
+        };
+        let document = json!({
+            "asset": { "version": "2.0", "generator": "pga_axioms" },
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [{ "mesh": 0, "name": "paper" }],
+            "meshes": [{ "primitives": primitives }],
+            "materials": [material("front", FRONT_COLOR), material("back", BACK_COLOR)],
+            "buffers": [{ "byteLength": buffer.len() }],
+            "bufferViews": views,
+            "accessors": accessors,
+        });
+
+        // Both chunks must be padded to a multiple of 4 bytes: the JSON with spaces, and the
+        // binary data with zeros
+        let mut json = document.to_string().into_bytes();
+        while json.len() % 4 != 0 {
+            json.push(b' ');
+        }
+        while buffer.len() % 4 != 0 {
+            buffer.push(0);
+        }
+
+        let length = 12 + 8 + json.len() + 8 + buffer.len();
+        let mut glb = Vec::with_capacity(length);
+        glb.extend(b"glTF");
+        glb.extend(&2u32.to_le_bytes());
+        glb.extend(&(length as u32).to_le_bytes());
+        glb.extend(&(json.len() as u32).to_le_bytes());
+        glb.extend(b"JSON");
+        glb.extend(&json);
+        glb.extend(&(buffer.len() as u32).to_le_bytes());
+        glb.extend(b"BIN\0");
+        glb.extend(&buffer);
+        Ok(glb)
+    }
+}
+
+/// Returns the (unit) normal of the polygon, via Newell's method.
+fn newell_normal(polygon: &[[f32; 3]]) -> [f32; 3] {
+    let mut normal = [0.0f32; 3];
+    for index in 0..polygon.len() {
+        let a = polygon[index];
+        let b = polygon[(index + 1) % polygon.len()];
+        normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+    }
+    let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+    if length > 0.0 {
+        normal.iter_mut().for_each(|n| *n /= length);
+    }
+    normal
+}
+
+/// Returns the component-wise minimum and maximum of the points (required by glTF for the
+/// `POSITION` attribute). The points must not be empty.
+fn bounds(points: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    let mut min = [std::f32::INFINITY; 3];
+    let mut max = [std::f32::NEG_INFINITY; 3];
+    for point in points.iter() {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(point[axis]);
+            max[axis] = max[axis].max(point[axis]);
+        }
+    }
+    (min, max)
+}
+
+/// Returns the little-endian bytes of each coordinate of each point.
+fn flatten(points: &[[f32; 3]]) -> Vec<u8> {
+    points
+        .iter()
+        .flat_map(|point| point.iter().flat_map(|c| c.to_le_bytes().to_vec()))
+        .collect()
+}
+
+/// Appends `bytes` to the binary buffer as a new buffer view (with the specified target),
+/// returning the index of the view.
+fn push_view(
+    buffer: &mut Vec<u8>,
+    views: &mut Vec<serde_json::Value>,
+    bytes: &[u8],
+    target: u32,
+) -> usize {
+    // Accessors must be aligned to the size of their components (4 bytes, here)
+    while buffer.len() % 4 != 0 {
+        buffer.push(0);
+    }
+    views.push(json!({
+        "buffer": 0,
+        "byteOffset": buffer.len(),
+        "byteLength": bytes.len(),
+        "target": target,
+    }));
+    buffer.extend(bytes);
+    views.len() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::paper;
+
+    #[test]
+    fn test_fold_mesh() {
+        // Fold the left half of the paper up by 90 degrees
+        let crease = Multivector::line(1.0, 0.0, -0.5);
+        let mesh = Mesh::fold(&paper(), &crease, std::f32::consts::FRAC_PI_2);
+        assert_eq!(mesh.facets.len(), 2);
+
+        // Should be: one facet lies flat (facing up), and the other stands upright
+        let normals = mesh
+            .facets
+            .iter()
+            .map(|facet| newell_normal(facet))
+            .collect::<Vec<_>>();
+        println!("Normals: {:?}", normals);
+        assert!(normals.iter().any(|n| (n[2] - 1.0).abs() < 0.0001));
+        assert!(normals.iter().any(|n| n[2].abs() < 0.0001));
+
+        // Folding flat reproduces `Paper::intersect`, with the moved facet upside-down
+        let mesh = Mesh::fold(&paper(), &crease, std::f32::consts::PI);
+        let points = mesh.facets.iter().flatten().collect::<Vec<_>>();
+        assert!(points.iter().all(|p| p[2].abs() < 0.0001));
+        assert!(
+            points.iter().all(|p| p[0] > 0.5 - 0.0001)
+                || points.iter().all(|p| p[0] < 0.5 + 0.0001)
+        );
+        assert!(mesh.facets.iter().any(|f| newell_normal(f)[2] < -0.9999));
+    }
+
+    #[test]
+    fn test_obj() {
+        let crease = Multivector::line(1.0, -1.0, 0.0);
+        let mesh = Mesh::fold(&paper(), &crease, 1.0);
+        let obj = mesh.to_obj("paper.mtl");
+        println!("{}", obj);
+
+        // Should be: 6 vertices (two triangles), and each triangle written once per side
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 6);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 4);
+        assert!(obj.contains("usemtl front") && obj.contains("usemtl back"));
+        assert!(mesh.to_mtl().contains("newmtl back"));
+    }
+
+    #[test]
+    fn test_glb() {
+        let crease = Multivector::line(1.0, 0.0, -0.25);
+        let glb = Mesh::fold(&paper(), &crease, 2.0).to_glb().unwrap();
+
+        let word = |offset: usize| {
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(&glb[offset..offset + 4]);
+            u32::from_le_bytes(bytes) as usize
+        };
+
+        // The header and chunk layout
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(word(4), 2);
+        assert_eq!(word(8), glb.len());
+        assert_eq!(glb.len() % 4, 0);
+        let json_length = word(12);
+        assert_eq!(&glb[16..20], b"JSON");
+        let binary_length = word(20 + json_length);
+        assert_eq!(&glb[24 + json_length..28 + json_length], b"BIN\0");
+        assert_eq!(28 + json_length + binary_length, glb.len());
+
+        // The document should reference the binary chunk correctly
+        let document: serde_json::Value =
+            serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+        assert_eq!(
+            document["meshes"][0]["primitives"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(document["materials"][1]["name"], "back");
+        assert!(document["buffers"][0]["byteLength"].as_u64().unwrap() as usize <= binary_length);
+
+        // Every position accessor should have finite bounds
+        for accessor in document["accessors"].as_array().unwrap().iter() {
+            if let Some(min) = accessor["min"].as_array() {
+                assert!(min.iter().all(|value| value.is_f64()));
+            }
+        }
+
+        // An empty mesh can't be exported (its bounds would be infinite)
+        assert!(Mesh::default().to_glb().is_err());
+        let degenerate = Mesh {
+            facets: vec![vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]]],
+        };
+        assert!(degenerate.to_glb().is_err());
+    }
+}
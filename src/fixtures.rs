@@ -0,0 +1,24 @@
+//! Shapes that are shared by the tests of several modules.
+
+use crate::interop::{Paper, Point};
+use crate::multivector::Multivector;
+
+/// The corners of the unit square, counterclockwise from the lower-left corner at the origin.
+pub fn unit_square() -> Vec<Multivector> {
+    vec![
+        Multivector::point(0.0, 0.0),
+        Multivector::point(1.0, 0.0),
+        Multivector::point(1.0, 1.0),
+        Multivector::point(0.0, 1.0),
+    ]
+}
+
+/// The unit square as a sheet of paper.
+pub fn paper() -> Paper {
+    Paper::new(
+        Point::new(0.0, 1.0),
+        Point::new(1.0, 1.0),
+        Point::new(1.0, 0.0),
+        Point::new(0.0, 0.0),
+    )
+}
@@ -0,0 +1,239 @@
+use crate::geometry;
+use crate::multivector::Multivector;
+use crate::pga3d::Multivector3;
+
+use std::collections::VecDeque;
+
+/// A crease in a flat fold state, along with how far it is folded.
+#[derive(Copy, Clone, Debug)]
+pub struct Crease {
+    /// The (infinite) crease line, which must cut all the way across the paper.
+    pub line: Multivector,
+
+    /// The fold angle, in radians, between 0 (flat) and π (folded flat, i.e. 180 degrees).
+    /// Positive angles are valley folds (the panels on either side rise towards `+z`), and
+    /// negative angles are mountain folds.
+    pub angle: f32,
+}
+
+impl Crease {
+    /// Constructs a new crease.
+    pub fn new(line: &Multivector, angle: f32) -> Self {
+        Self { line: *line, angle }
+    }
+}
+
+/// A rigid, flat region of the paper that is bounded by creases (and/or the edges of the paper).
+#[derive(Clone, Debug)]
+pub struct Panel {
+    /// The vertices of the panel, in the flat (unfolded) paper.
+    pub vertices: Vec<Multivector>,
+
+    /// The rigid motion that carries the panel from the plane `z = 0` to its folded position.
+    pub motor: Multivector3,
+}
+
+impl Panel {
+    /// Returns the folded (3D) position of each of the panel's vertices.
+    pub fn positions(&self) -> Vec<[f32; 3]> {
+        self.vertices
+            .iter()
+            .map(|p| {
+                Multivector3::point(p.e20(), p.e01(), 0.0)
+                    .transformed(&self.motor)
+                    .coordinates()
+            })
+            .collect()
+    }
+
+    /// Returns the centroid of the panel's (flat) vertices.
+    fn centroid(&self) -> Multivector {
+        let sum = self
+            .vertices
+            .iter()
+            .fold(Multivector::zeros(), |sum, p| sum + *p);
+        sum / sum.e12()
+    }
+}
+
+/// A flat fold state that has been lifted into 3D.
+#[derive(Clone, Debug)]
+pub struct FoldedState {
+    pub panels: Vec<Panel>,
+}
+
+impl FoldedState {
+    /// Lifts the paper (a convex polygon) into 3D by folding it along each of the creases. The
+    /// creases divide the paper into rigid panels: the panel that contains the first vertex of
+    /// the paper stays fixed in the plane `z = 0`, and every other panel is rotated about the
+    /// crease axes that separate it from the fixed panel.
+    ///
+    /// Each panel is reached from the fixed panel by crossing one crease at a time, and the
+    /// rotation about each crease is applied in the frame of the panel that it is crossed from.
+    /// If creases cross each other, a panel may be reachable along several paths that disagree
+    /// (because there is no rigid folded state with the requested angles): in that case, the
+    /// first path found is used.
+    pub fn lift(paper: &[Multivector], creases: &[Crease]) -> Result<Self, &'static str> {
+        if paper.len() < 3 {
+            return Err("The paper must have at least 3 vertices");
+        }
+        if creases
+            .iter()
+            .any(|crease| crease.angle.abs() > std::f32::consts::PI + 0.0001)
+        {
+            return Err("Fold angles must be between -π and π radians");
+        }
+
+        // Cut the paper into panels, one crease at a time
+        let mut polygons = vec![paper.iter().map(|p| (*p) / p.e12()).collect::<Vec<_>>()];
+        for crease in creases.iter() {
+            polygons = polygons
+                .iter()
+                .flat_map(|polygon| {
+                    let (positive, negative) = geometry::split_polygon(polygon, &crease.line);
+                    vec![positive, negative]
+                })
+                .filter(|polygon| !polygon.is_empty())
+                .collect();
+        }
+        let mut panels = polygons
+            .into_iter()
+            .map(|vertices| Panel {
+                vertices,
+                motor: Multivector3::scalar_value(1.0),
+            })
+            .collect::<Vec<_>>();
+
+        // Which side of each crease is each panel on?
+        let sides = panels
+            .iter()
+            .map(|panel| {
+                let centroid = panel.centroid();
+                creases
+                    .iter()
+                    .map(|crease| geometry::dist_point_to_line(&centroid, &crease.line) > 0.0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let fixed = panels
+            .iter()
+            .position(|panel| geometry::polygon_contains(&panel.vertices, &paper[0]))
+            .unwrap_or(0);
+
+        // Two panels are adjacent across a crease if they lie on the same side of every other
+        // crease (the segment between their centroids crosses only that crease)
+        let mut visited = vec![false; panels.len()];
+        let mut queue = VecDeque::new();
+        visited[fixed] = true;
+        queue.push_back(fixed);
+
+        while let Some(current) = queue.pop_front() {
+            for next in 0..panels.len() {
+                if visited[next] {
+                    continue;
+                }
+                let differences = (0..creases.len())
+                    .filter(|&index| sides[current][index] != sides[next][index])
+                    .collect::<Vec<_>>();
+                if differences.len() != 1 {
+                    continue;
+                }
+
+                let crease = &creases[differences[0]];
+                let rotation = crease_rotation(crease, &panels[next].centroid());
+                panels[next].motor = panels[current].motor * rotation;
+                visited[next] = true;
+                queue.push_back(next);
+            }
+        }
+
+        Ok(Self { panels })
+    }
+
+    /// Returns the folded (3D) position of each vertex of each panel.
+    pub fn vertex_positions(&self) -> Vec<Vec<[f32; 3]>> {
+        self.panels.iter().map(|panel| panel.positions()).collect()
+    }
+}
+
+/// Returns the rotor that folds the (flat) side of `crease` containing the point `moving` by
+/// the crease's fold angle, so that a valley fold lifts it towards `+z`.
+pub fn crease_rotation(crease: &Crease, moving: &Multivector) -> Multivector3 {
+    let line = crease.line.normalized();
+    let axis = Multivector3::line_in_plane(line.e1(), line.e2(), line.e0());
+
+    // The direction of rotation depends on the orientation of the axis, so check which way a
+    // small rotation moves the point
+    let probe = Multivector3::point(moving.e20(), moving.e01(), 0.0)
+        .transformed(&Multivector3::rotor(0.1, &axis))
+        .coordinates();
+    let orientation = if probe[2] >= 0.0 { 1.0 } else { -1.0 };
+
+    Multivector3::rotor(orientation * crease.angle, &axis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::unit_square;
+
+    #[test]
+    fn test_lift() {
+        // A valley fold down the middle, at 90 degrees: the right half should stand upright
+        let crease = Crease::new(
+            &Multivector::line(1.0, 0.0, -0.5),
+            std::f32::consts::FRAC_PI_2,
+        );
+        let state = FoldedState::lift(&unit_square(), &[crease]).unwrap();
+        assert_eq!(state.panels.len(), 2);
+        for positions in state.vertex_positions().iter() {
+            println!("Panel: {:?}", positions);
+            for &[x, _, z] in positions.iter() {
+                assert!(x > 0.5 - 0.0001 && x < 0.5 + 0.0001 || z.abs() < 0.0001);
+                assert!(z > -0.0001 && z < 0.5 + 0.0001);
+            }
+        }
+
+        // Folded flat (180 degrees) as a mountain fold: the right half lands on top of the left
+        // half, underneath it
+        let crease = Crease::new(&Multivector::line(1.0, 0.0, -0.5), -std::f32::consts::PI);
+        let state = FoldedState::lift(&unit_square(), &[crease]).unwrap();
+        for positions in state.vertex_positions().iter() {
+            for &[x, _, z] in positions.iter() {
+                assert!(x < 0.5 + 0.0001);
+                assert!(z.abs() < 0.0001);
+            }
+        }
+
+        // Two parallel creases: the panels are folded in sequence, so the far panel inherits
+        // the rotation of the middle panel
+        let creases = [
+            Crease::new(
+                &Multivector::line(1.0, 0.0, -0.25),
+                std::f32::consts::FRAC_PI_2,
+            ),
+            Crease::new(
+                &Multivector::line(1.0, 0.0, -0.75),
+                std::f32::consts::FRAC_PI_2,
+            ),
+        ];
+        let state = FoldedState::lift(&unit_square(), &creases).unwrap();
+        assert_eq!(state.panels.len(), 3);
+        let far = state
+            .panels
+            .iter()
+            .find(|panel| panel.vertices.iter().all(|p| p.e20() > 0.75 - 0.0001))
+            .unwrap();
+
+        // Should be: the far panel lies flat again, upside-down, at height 0.5 (like a "U")
+        for &[x, _, z] in far.positions().iter() {
+            assert!((z - 0.5).abs() < 0.0001);
+            assert!(x < 0.25 + 0.0001);
+        }
+
+        // Angles beyond 180 degrees aren't allowed
+        let crease = Crease::new(&Multivector::line(1.0, 0.0, -0.5), 4.0);
+        assert!(FoldedState::lift(&unit_square(), &[crease]).is_err());
+    }
+}
@@ -1,4 +1,11 @@
-use crate::multivector::Multivector;
+use crate::interval::{self, Existence};
+use crate::multivector::*;
+use crate::predicates;
+use crate::utils;
+
+/// How close to a line a vertex of a transformed polygon has to be to lie on it, relative to the
+/// size of the polygon's coordinates (see `split_polygon`).
+pub const SPLIT_TOLERANCE: f32 = 0.0001;
 
 /// Intersect two lines by taking their wedge (outer) product. This is sometimes
 /// called the "meet" operator, as it (unconditionally) calculates the point where
@@ -126,6 +133,679 @@ pub fn translate(m: &Multivector, delta_x: f32, delta_y: f32) -> Multivector {
     T * (*m) * T.conjugation()
 }
 
+/// Returns the (unit) direction of the line `l`, represented by an ideal point. Multiplying a
+/// line by e012 "pulls out" its normal direction, also represented by an ideal point (this is
+/// also known as metric polarity): the line itself runs perpendicular to this direction.
+pub fn direction(l: &Multivector) -> Multivector {
+    let mut normal = (*l) * e012;
+    normal /= normal.ideal_norm();
+    Multivector::ideal_point(normal.e01(), -normal.e20())
+}
+
+/// Translates the multivector by `distance` units in the direction of the line `l`.
+pub fn translate_along(m: &Multivector, l: &Multivector, distance: f32) -> Multivector {
+    let direction = direction(l) * distance;
+    translate(m, direction.e20(), direction.e01())
+}
+
+/// A circle with center `center` (a Euclidean point) and radius `radius`.
+#[derive(Copy, Clone, Debug)]
+pub struct Circle {
+    pub center: Multivector,
+    pub radius: f32,
+}
+
+impl Circle {
+    /// Constructs a new circle with the specified center and radius.
+    pub fn new(center: &Multivector, radius: f32) -> Self {
+        Self {
+            center: (*center) / center.e12(),
+            radius,
+        }
+    }
+
+    /// Constructs the circle centered at `center` that passes through the point `p`.
+    pub fn through(center: &Multivector, p: &Multivector) -> Self {
+        Self::new(center, dist_point_to_point(center, p))
+    }
+
+    /// Returns `true` if the point `p` lies on the circle (within tolerance).
+    pub fn contains(&self, p: &Multivector) -> bool {
+        (dist_point_to_point(&self.center, p) - self.radius).abs() < 0.001
+    }
+
+    /// Returns the points where the line `l` intersects the circle: there will be 0, 1 (if
+    /// the line is tangent to the circle), or 2 such points.
+    pub fn intersect_line(&self, l: &Multivector) -> Vec<Multivector> {
+        let dist_from_line_to_center = dist_point_to_line(&self.center, l).abs();
+
+        if dist_from_line_to_center > self.radius + 0.001 {
+            return vec![];
+        }
+
+        // Drop a perpendicular from the center to the line: the points of intersection are
+        // found by translating the foot of this perpendicular along the line in either direction
+        let mut foot = intersect_lines(&orthogonal(&self.center, l), l);
+        foot /= foot.e12();
+
+        // Pythagoras' theorem: find the length of the third side of the triangle whose
+        // hypotenuse is the radius and other side is `dist_from_line_to_center`
+        let d = (self.radius * self.radius - dist_from_line_to_center * dist_from_line_to_center)
+            .max(0.0)
+            .sqrt();
+
+        if d < 0.001 {
+            return vec![foot];
+        }
+
+        [d, -d]
+            .iter()
+            .map(|&distance| {
+                let mut point = translate_along(&foot, l, distance);
+                point /= point.e12();
+                point
+            })
+            .collect()
+    }
+
+    /// Returns the lines that pass through the point `p` and are tangent to the circle: there
+    /// will be 0 (if `p` is inside of the circle), 1 (if `p` is on the circle), or 2 such lines.
+    pub fn tangents_from(&self, p: &Multivector) -> Vec<Multivector> {
+        let dist_to_center = dist_point_to_point(&self.center, p);
+
+        if (dist_to_center - self.radius).abs() < 0.001 {
+            return vec![orthogonal(p, &self.center.join(p)).normalized()];
+        }
+
+        if dist_to_center < self.radius {
+            return vec![];
+        }
+
+        // Rotate the line from `p` to the center about `p` until it just grazes the circle
+        let to_center = p.join(&self.center);
+        let angle = (self.radius / dist_to_center).asin();
+        let p = (*p) / p.e12();
+
+        [angle, -angle]
+            .iter()
+            .map(|&angle| rotate(&to_center, angle, p.e20(), p.e01()).normalized())
+            .collect()
+    }
+
+    /// Returns the lines that are tangent to both circles: there will be up to 4 such lines
+    /// (2 "external" tangents, which do not pass between the circles, and 2 "internal"
+    /// tangents, which do).
+    pub fn common_tangents(&self, other: &Circle) -> Vec<Multivector> {
+        let mut tangents = Vec::new();
+
+        // The external tangents pass through the external center of similitude (or are parallel
+        // to the line joining the centers, if the radii are equal - in which case the center of
+        // similitude is an ideal point)
+        if (self.radius - other.radius).abs() < 0.001 {
+            let between = self.center.join(&other.center).normalized();
+            tangents.push(between + e0 * self.radius);
+            tangents.push(between - e0 * self.radius);
+        } else {
+            let external = self.center * other.radius - other.center * self.radius;
+            tangents.extend(self.tangents_from(&external));
+        }
+
+        // The internal tangents pass through the internal center of similitude, which always
+        // lies between the two centers
+        let internal = self.center * other.radius + other.center * self.radius;
+        tangents.extend(self.tangents_from(&internal));
+
+        tangents
+    }
+}
+
+/// A parabola, defined as the set of points that are equidistant from the point `focus` and
+/// the line `directrix`.
+///
+/// Every fold that places the focus onto the directrix is tangent to the parabola (and vice
+/// versa), which is why parabolas show up in axioms 5 and 6.
+#[derive(Copy, Clone, Debug)]
+pub struct Parabola {
+    pub focus: Multivector,
+    pub directrix: Multivector,
+}
+
+impl Parabola {
+    /// Constructs a new parabola with the specified focus and directrix.
+    pub fn new(focus: &Multivector, directrix: &Multivector) -> Self {
+        Self {
+            focus: (*focus) / focus.e12(),
+            directrix: directrix.normalized(),
+        }
+    }
+
+    /// Returns `true` if the point `p` lies on the parabola (within tolerance).
+    pub fn contains(&self, p: &Multivector) -> bool {
+        let to_focus = dist_point_to_point(&self.focus, p);
+        let to_directrix = dist_point_to_line(p, &self.directrix).abs();
+        (to_focus - to_directrix).abs() < 0.001
+    }
+
+    /// Returns the line tangent to the parabola at the point `p`, which must lie on the parabola.
+    /// This is the perpendicular bisector of the focus and the foot of the perpendicular from
+    /// `p` to the directrix.
+    pub fn tangent_at(&self, p: &Multivector) -> Multivector {
+        let mut foot = intersect_lines(&orthogonal(p, &self.directrix), &self.directrix);
+        foot /= foot.e12();
+        perpendicular_bisector(&self.focus, &foot).normalized()
+    }
+
+    /// Returns the point on the directrix at (signed) distance `t` from the foot of the
+    /// perpendicular dropped from the focus. This is the natural parameter of the parabola:
+    /// folding the focus onto this point produces the tangent at `point_at(t)`.
+    pub fn directrix_point(&self, t: f32) -> Multivector {
+        let mut anchor = project(&self.focus, &self.directrix);
+        anchor /= anchor.e12();
+
+        let mut point = translate_along(&anchor, &self.directrix, t);
+        point /= point.e12();
+        point
+    }
+
+    /// Returns the line tangent to the parabola at parameter `t` (see `directrix_point`). This is
+    /// the crease that folds the focus onto `directrix_point(t)`.
+    pub fn tangent_at_parameter(&self, t: f32) -> Multivector {
+        perpendicular_bisector(&self.focus, &self.directrix_point(t)).normalized()
+    }
+
+    /// Returns the point on the parabola at parameter `t` (see `directrix_point`). This is where
+    /// the tangent at `t` meets the perpendicular to the directrix through `directrix_point(t)`.
+    pub fn point_at(&self, t: f32) -> Multivector {
+        let image = self.directrix_point(t);
+        let perpendicular = orthogonal(&image, &self.directrix);
+
+        let mut point = intersect_lines(&perpendicular, &self.tangent_at_parameter(t));
+        point /= point.e12();
+        point
+    }
+
+    /// Samples the parabola as a polyline of `count` points, with parameters evenly spaced
+    /// between `t_min` and `t_max` (inclusive).
+    pub fn sample(&self, t_min: f32, t_max: f32, count: usize) -> Vec<Multivector> {
+        if count < 2 {
+            return vec![self.point_at(t_min)];
+        }
+
+        let step = (t_max - t_min) / (count - 1) as f32;
+        (0..count)
+            .map(|index| self.point_at(t_min + step * index as f32))
+            .collect()
+    }
+
+    /// Returns the points where the line `l` intersects the parabola: there will be 0, 1, or 2
+    /// such points.
+    pub fn intersect_line(&self, l: &Multivector) -> Vec<Multivector> {
+        // Parametrize the line as `x(t) = anchor + t * direction`, then solve the quadratic
+        // `|x(t) - focus|² = dist(x(t), directrix)²`
+        let mut anchor = project(&self.focus, l);
+        anchor /= anchor.e12();
+        let mut along = translate_along(&anchor, l, 1.0);
+        along /= along.e12();
+
+        let (dx, dy) = (along.e20() - anchor.e20(), along.e01() - anchor.e01());
+        let (fx, fy) = (
+            anchor.e20() - self.focus.e20(),
+            anchor.e01() - self.focus.e01(),
+        );
+        let (a, b, c) = (
+            self.directrix.e1(),
+            self.directrix.e2(),
+            self.directrix.e0(),
+        );
+        let k = a * anchor.e20() + b * anchor.e01() + c;
+        let j = a * dx + b * dy;
+
+        let roots = utils::solve_quadratic(
+            (1.0 - j * j) as f64,
+            (2.0 * (fx * dx + fy * dy) - 2.0 * k * j) as f64,
+            (fx * fx + fy * fy - k * k) as f64,
+        );
+
+        roots
+            .into_iter()
+            .map(|t| {
+                let mut point = translate_along(&anchor, l, t as f32);
+                point /= point.e12();
+                point
+            })
+            .collect()
+    }
+
+    /// Returns the lines that pass through the point `p` and are tangent to the parabola. Each
+    /// of these is a fold that places the focus onto the directrix and passes through `p`
+    /// (i.e. the solutions to axiom 5).
+    ///
+    /// The focus must land on the circle centered at `p` that passes through the focus, so the
+    /// folded focus can only be one of the points where this circle meets the directrix.
+    pub fn tangents_from(&self, p: &Multivector) -> Vec<Multivector> {
+        // Whether or not the circle crosses the directrix is decided with interval arithmetic
+        // (rather than the fixed tolerance of `Circle::intersect_line`), and a tangent circle
+        // gives a single (ambiguous) solution
+        let existence = interval::certify_axiom_5(&self.focus, p, &self.directrix).existence;
+        if existence == Existence::Impossible {
+            return vec![];
+        }
+        let mut images = Circle::through(p, &self.focus).intersect_line(&self.directrix);
+        if existence == Existence::Ambiguous {
+            images.truncate(1);
+        }
+
+        // Each tangent passes through `p` and is perpendicular to the chord from the focus to
+        // its image (there is no tangent that folds the focus onto itself)
+        images
+            .iter()
+            .filter(|image| dist_point_to_point(image, &self.focus) > 0.001)
+            .map(|image| orthogonal(p, &image.join(&self.focus)).normalized())
+            .collect()
+    }
+
+    /// Returns the lines that are tangent to both parabolas. Each of these is a fold that
+    /// simultaneously places each focus onto its corresponding directrix (i.e. the solutions
+    /// to axiom 6). There are up to 3 such lines.
+    pub fn common_tangents(&self, other: &Parabola) -> Vec<Multivector> {
+        // Parametrize the image of this parabola's focus along its directrix:
+        // `q(t) = anchor + t * direction`, where `anchor` is the point on the directrix closest
+        // to the focus
+        let mut anchor = project(&self.focus, &self.directrix);
+        anchor /= anchor.e12();
+        let mut along = translate_along(&anchor, &self.directrix, 1.0);
+        along /= along.e12();
+
+        // Fold the focus onto `q(t)`: the crease is the perpendicular bisector of the two, with
+        // normal `n(t) = q(t) - focus` and passing through the midpoint `m(t)`. Reflecting the
+        // other focus across this crease and requiring that the result lies on the other
+        // directrix (after multiplying through by `|n(t)|²` to clear the denominator) gives a
+        // cubic in `t`
+        let focus = (self.focus.e20() as f64, self.focus.e01() as f64);
+        let (dx, dy) = (
+            (along.e20() - anchor.e20()) as f64,
+            (along.e01() - anchor.e01()) as f64,
+        );
+        let (n0x, n0y) = (anchor.e20() as f64 - focus.0, anchor.e01() as f64 - focus.1);
+        let (m0x, m0y) = (
+            (anchor.e20() as f64 + focus.0) * 0.5,
+            (anchor.e01() as f64 + focus.1) * 0.5,
+        );
+        let (p1x, p1y) = (other.focus.e20() as f64, other.focus.e01() as f64);
+        let (a1, b1, c1) = (
+            other.directrix.e1() as f64,
+            other.directrix.e2() as f64,
+            other.directrix.e0() as f64,
+        );
+
+        let k = a1 * p1x + b1 * p1y + c1;
+        let s = n0x * n0x + n0y * n0y;
+        let u = (p1x - m0x) * n0x + (p1y - m0y) * n0y;
+        let v = (p1x - m0x) * dx + (p1y - m0y) * dy;
+        let alpha = a1 * n0x + b1 * n0y;
+        let beta = a1 * dx + b1 * dy;
+
+        let roots = utils::solve_cubic(
+            beta,
+            k - 2.0 * v * beta + alpha,
+            -2.0 * (u * beta + v * alpha),
+            k * s - 2.0 * u * alpha,
+        );
+
+        roots
+            .into_iter()
+            .filter_map(|t| {
+                let image = translate_along(&anchor, &self.directrix, t as f32);
+
+                // If the focus already lies on the directrix, `q(t) = focus` is a spurious root
+                // of the cubic (there is no crease that maps a point onto itself in this way)
+                if dist_point_to_point(&self.focus, &image) < 0.001 {
+                    return None;
+                }
+
+                Some(perpendicular_bisector(&self.focus, &image).normalized())
+            })
+            .collect()
+    }
+}
+
+/// A line segment between two Euclidean points `start` and `end`.
+#[derive(Copy, Clone, Debug)]
+pub struct Segment {
+    pub start: Multivector,
+    pub end: Multivector,
+}
+
+impl Segment {
+    /// Constructs a new segment between the points `start` and `end`.
+    pub fn new(start: &Multivector, end: &Multivector) -> Self {
+        Self {
+            start: (*start) / start.e12(),
+            end: (*end) / end.e12(),
+        }
+    }
+
+    /// Returns the (infinite) line that contains this segment, oriented from `start` to `end`.
+    pub fn line(&self) -> Multivector {
+        self.start.join(&self.end)
+    }
+
+    /// Returns the length of the segment.
+    pub fn length(&self) -> f32 {
+        dist_point_to_point(&self.start, &self.end)
+    }
+
+    /// Returns the midpoint of the segment.
+    pub fn midpoint(&self) -> Multivector {
+        let mut midpoint = midpoint(&self.start, &self.end);
+        midpoint /= midpoint.e12();
+        midpoint
+    }
+
+    /// Returns the ideal point (direction) from `start` to `end`. Note that the difference
+    /// between two normalized Euclidean points is always an ideal point.
+    pub fn direction(&self) -> Multivector {
+        self.end - self.start
+    }
+
+    /// Returns the parameter of the point `p` along the segment, such that `start` is at 0 and
+    /// `end` is at 1. The point is first projected onto the segment's line.
+    pub fn parameter(&self, p: &Multivector) -> f32 {
+        let p = (*p) / p.e12();
+        let direction = self.direction();
+        let offset = p - self.start;
+        let length_squared = direction.e20().powi(2) + direction.e01().powi(2);
+
+        (offset.e20() * direction.e20() + offset.e01() * direction.e01()) / length_squared
+    }
+
+    /// Returns the point at parameter `t` along the segment (see `parameter`).
+    pub fn point_at(&self, t: f32) -> Multivector {
+        self.start + self.direction() * t
+    }
+
+    /// Returns `true` if the point `p` lies on the segment (within tolerance).
+    pub fn contains(&self, p: &Multivector) -> bool {
+        let tolerance = 0.001 / self.length().max(0.001);
+        let t = self.parameter(p);
+
+        dist_point_to_line(p, &self.line()).abs() < 0.001 && t >= -tolerance && t <= 1.0 + tolerance
+    }
+
+    /// Returns the point where the (infinite) line `l` crosses the segment, if any.
+    pub fn intersect_line(&self, l: &Multivector) -> Option<Multivector> {
+        let intersection = intersect_lines(&self.line(), l);
+
+        // Parallel lines meet at an ideal point, which can't be on the segment
+        if intersection.e12().abs() < 0.0001 * intersection.norm().max(intersection.ideal_norm()) {
+            return None;
+        }
+
+        let intersection = intersection / intersection.e12();
+        if self.contains(&intersection) {
+            Some(intersection)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the point where the two segments cross, if any. Overlapping collinear segments are
+    /// not considered to intersect.
+    pub fn intersect_segment(&self, other: &Segment) -> Option<Multivector> {
+        self.intersect_line(&other.line())
+            .filter(|intersection| other.contains(intersection))
+    }
+
+    /// Returns the point where this segment crosses the ray, if any.
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<Multivector> {
+        ray.intersect_segment(self)
+    }
+}
+
+/// A ray that starts at the Euclidean point `origin` and extends infinitely far in `direction`
+/// (an ideal point).
+#[derive(Copy, Clone, Debug)]
+pub struct Ray {
+    pub origin: Multivector,
+    pub direction: Multivector,
+}
+
+impl Ray {
+    /// Constructs a new ray with the specified origin and direction (an ideal point).
+    pub fn new(origin: &Multivector, direction: &Multivector) -> Self {
+        Self {
+            origin: (*origin) / origin.e12(),
+            direction: (*direction) / direction.ideal_norm(),
+        }
+    }
+
+    /// Constructs a new ray that starts at `origin` and passes through the point `p`.
+    pub fn through(origin: &Multivector, p: &Multivector) -> Self {
+        let origin = (*origin) / origin.e12();
+        let p = (*p) / p.e12();
+        Self::new(&origin, &(p - origin))
+    }
+
+    /// Returns the (infinite) line that contains this ray, oriented along its direction.
+    pub fn line(&self) -> Multivector {
+        self.origin.join(&(self.origin + self.direction))
+    }
+
+    /// Returns the (signed) distance from the origin of the ray to the point `p`, measured along
+    /// the ray. The point is first projected onto the ray's line.
+    pub fn parameter(&self, p: &Multivector) -> f32 {
+        let offset = (*p) / p.e12() - self.origin;
+        offset.e20() * self.direction.e20() + offset.e01() * self.direction.e01()
+    }
+
+    /// Returns `true` if the point `p` lies on the ray (within tolerance).
+    pub fn contains(&self, p: &Multivector) -> bool {
+        dist_point_to_line(p, &self.line()).abs() < 0.001 && self.parameter(p) >= -0.001
+    }
+
+    /// Returns the point where the (infinite) line `l` crosses the ray, if any.
+    pub fn intersect_line(&self, l: &Multivector) -> Option<Multivector> {
+        let intersection = intersect_lines(&self.line(), l);
+
+        if intersection.e12().abs() < 0.0001 * intersection.norm().max(intersection.ideal_norm()) {
+            return None;
+        }
+
+        let intersection = intersection / intersection.e12();
+        if self.contains(&intersection) {
+            Some(intersection)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the point where the ray crosses the segment, if any.
+    pub fn intersect_segment(&self, segment: &Segment) -> Option<Multivector> {
+        self.intersect_line(&segment.line())
+            .filter(|intersection| segment.contains(intersection))
+    }
+
+    /// Returns the point where the two rays cross, if any.
+    pub fn intersect_ray(&self, other: &Ray) -> Option<Multivector> {
+        self.intersect_line(&other.line())
+            .filter(|intersection| other.contains(intersection))
+    }
+}
+
+/// Returns the signed area of the polygon with the specified vertices (positive if it is wound
+/// counter-clockwise).
+pub fn polygon_area(polygon: &[Multivector]) -> f32 {
+    let twice: f32 = (0..polygon.len())
+        .map(|index| {
+            let a = polygon[index] / polygon[index].e12();
+            let b = polygon[(index + 1) % polygon.len()];
+            let b = b / b.e12();
+            a.e20() * b.e01() - b.e20() * a.e01()
+        })
+        .sum();
+    twice / 2.0
+}
+
+/// Returns `true` if the point `p` lies inside of (or on the boundary of) the convex polygon with
+/// the specified vertices, which may be in either winding order. The point is inside if it lies
+/// on the same side of every edge.
+pub fn polygon_contains(polygon: &[Multivector], p: &Multivector) -> bool {
+    let distances = (0..polygon.len())
+        .map(|index| {
+            let edge = polygon[index].join(&polygon[(index + 1) % polygon.len()]);
+            dist_point_to_line(p, &edge)
+        })
+        .collect::<Vec<_>>();
+
+    distances.iter().all(|&distance| distance >= -0.001)
+        || distances.iter().all(|&distance| distance <= 0.001)
+}
+
+/// Clips the (infinite) line `l` to the convex polygon with the specified vertices, returning
+/// the portion of the line that lies inside of the polygon (or `None` if the line misses the
+/// polygon entirely, or only touches one of its vertices).
+pub fn clip_line(l: &Multivector, polygon: &[Multivector]) -> Option<Segment> {
+    let mut crossings: Vec<Multivector> = Vec::new();
+
+    for index in 0..polygon.len() {
+        let edge = Segment::new(&polygon[index], &polygon[(index + 1) % polygon.len()]);
+
+        if let Some(crossing) = edge.intersect_line(l) {
+            // A line that passes through a vertex will cross both of the edges that meet there
+            if crossings
+                .iter()
+                .all(|existing| dist_point_to_point(existing, &crossing) > 0.001)
+            {
+                crossings.push(crossing);
+            }
+        }
+    }
+
+    if crossings.len() < 2 {
+        return None;
+    }
+
+    // For a convex polygon, there will be exactly two crossings (unless the line runs along an
+    // edge, in which case we take the two extremes)
+    let ray = Ray::new(&crossings[0], &direction(l));
+    let parameter = |p: &Multivector| ray.parameter(p);
+    let first = crossings
+        .iter()
+        .min_by(|a, b| parameter(a).partial_cmp(&parameter(b)).unwrap())?;
+    let last = crossings
+        .iter()
+        .max_by(|a, b| parameter(a).partial_cmp(&parameter(b)).unwrap())?;
+
+    Some(Segment::new(first, last))
+}
+
+/// Walks around the polygon with the specified vertices, returning each vertex (made Euclidean)
+/// paired with the side of the line `l` that it lies on (see `predicates::side_of_line`), with
+/// the points where edges cross the line inserted between their endpoints. Crossings are paired
+/// with `0.0`: they lie on the line by construction, and classifying their computed coordinates
+/// again could put them on the wrong side because of round-off error.
+///
+/// The sides are exact, so a vertex is only treated as lying on the line if it does so exactly,
+/// regardless of scale. That only makes sense for vertices and lines that are represented
+/// exactly (such as the corners of the paper and the creases through them). Geometry that has
+/// been transformed (reflected, rotated, ...) only approximates where it should be, so a vertex
+/// that belongs on the line can end up on either side of it: use `cut_polygon_within` instead.
+pub fn cut_polygon(polygon: &[Multivector], l: &Multivector) -> Vec<(Multivector, f32)> {
+    let sides = polygon
+        .iter()
+        .map(|p| predicates::side_of_line(p, l))
+        .collect::<Vec<_>>();
+    cut_polygon_with(polygon, l, &sides)
+}
+
+/// Like `cut_polygon`, but any vertex within `tolerance` of the line `l` is treated as lying on
+/// it.
+pub fn cut_polygon_within(
+    polygon: &[Multivector],
+    l: &Multivector,
+    tolerance: f32,
+) -> Vec<(Multivector, f32)> {
+    let sides = polygon
+        .iter()
+        .map(|p| {
+            let distance = dist_point_to_line(p, l);
+            if distance > tolerance {
+                1.0
+            } else if distance < -tolerance {
+                -1.0
+            } else {
+                0.0
+            }
+        })
+        .collect::<Vec<_>>();
+    cut_polygon_with(polygon, l, &sides)
+}
+
+/// Walks around the polygon, given the side of the line `l` that each of its vertices lies on
+/// (see `cut_polygon`).
+fn cut_polygon_with(
+    polygon: &[Multivector],
+    l: &Multivector,
+    sides: &[f32],
+) -> Vec<(Multivector, f32)> {
+    let mut cut_points = Vec::new();
+    for index in 0..polygon.len() {
+        let next = (index + 1) % polygon.len();
+        cut_points.push(((polygon[index]) / polygon[index].e12(), sides[index]));
+
+        // The edge crosses the line strictly between its endpoints
+        if sides[index] * sides[next] < 0.0 {
+            let mut crossing = polygon[index].join(&polygon[next]).meet(l);
+            crossing /= crossing.e12();
+            cut_points.push((crossing, 0.0));
+        }
+    }
+    cut_points
+}
+
+/// Splits the convex polygon with the specified vertices along the line `l`, returning the
+/// pieces on the positive and negative sides of the line (in that order), with the same winding
+/// order as the original polygon. A piece is empty if the line doesn't cut through the polygon
+/// on that side.
+///
+/// The polygon may have been transformed (for example, a facet of folded paper), so vertices
+/// that lie on the line up to round-off error belong to both pieces (see `cut_polygon_within`).
+/// The tolerance scales with the size of the polygon's coordinates, since that is what the
+/// round-off error does.
+pub fn split_polygon(
+    polygon: &[Multivector],
+    l: &Multivector,
+) -> (Vec<Multivector>, Vec<Multivector>) {
+    let scale = polygon
+        .iter()
+        .map(|p| (p.e20() / p.e12()).abs().max((p.e01() / p.e12()).abs()))
+        .fold(0.0f32, f32::max);
+    let cut_points = cut_polygon_within(polygon, l, SPLIT_TOLERANCE * scale.max(1e-6));
+    let positive = cut_points
+        .iter()
+        .filter(|(_, sign)| *sign >= 0.0)
+        .map(|(p, _)| *p)
+        .collect::<Vec<_>>();
+    let negative = cut_points
+        .iter()
+        .filter(|(_, sign)| *sign <= 0.0)
+        .map(|(p, _)| *p)
+        .collect::<Vec<_>>();
+
+    if positive.len() < 3 || negative.len() < 3 {
+        // The line only touches the polygon, so everything is on one side
+        let euclidean = || polygon.iter().map(|p| (*p) / p.e12()).collect();
+        if cut_points.iter().any(|(_, sign)| *sign > 0.0) {
+            return (euclidean(), vec![]);
+        }
+        if cut_points.iter().any(|(_, sign)| *sign < 0.0) {
+            return (vec![], euclidean());
+        }
+    }
+
+    (positive, negative)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,4 +823,165 @@ mod tests {
         println!("Projection of l onto p, (p | l) * p = {:?}", result);
         // Should be: Multivector { coeff: [0.0, 42.0, -36.0, -45.0, 0.0, 0.0, 0.0, 0.0] }
     }
+
+    #[test]
+    fn test_circles() {
+        // Should be: <-3, 4> and <3, 4>
+        let circle = Circle::new(&Multivector::point(0.0, 0.0), 5.0);
+        let l = Multivector::line(0.0, 1.0, -4.0);
+        let intersections = circle.intersect_line(&l);
+        assert_eq!(intersections.len(), 2);
+        for p in intersections.iter() {
+            println!("Intersection: <{}, {}>", p.e20(), p.e01());
+            assert!(circle.contains(p));
+        }
+
+        // Both tangents from <10, 0> should touch the circle exactly once
+        let tangents = circle.tangents_from(&Multivector::point(10.0, 0.0));
+        assert_eq!(tangents.len(), 2);
+        for tangent in tangents.iter() {
+            assert_eq!(circle.intersect_line(tangent).len(), 1);
+        }
+
+        // Two disjoint circles have 4 common tangents
+        let other = Circle::new(&Multivector::point(10.0, 0.0), 2.0);
+        let tangents = circle.common_tangents(&other);
+        assert_eq!(tangents.len(), 4);
+        for tangent in tangents.iter() {
+            assert!((dist_point_to_line(&circle.center, tangent).abs() - 5.0).abs() < 0.001);
+            assert!((dist_point_to_line(&other.center, tangent).abs() - 2.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_parabolas() {
+        // The parabola `y = x² / 4`, with focus <0, 1> and directrix `y = -1`
+        let parabola = Parabola::new(
+            &Multivector::point(0.0, 1.0),
+            &Multivector::line(0.0, 1.0, 1.0),
+        );
+
+        // Should be: <-2, 1> and <2, 1>
+        let intersections = parabola.intersect_line(&Multivector::line(0.0, 1.0, -1.0));
+        assert_eq!(intersections.len(), 2);
+        for p in intersections.iter() {
+            println!("Intersection: <{}, {}>", p.e20(), p.e01());
+            assert!(parabola.contains(p));
+
+            // Each tangent should only touch the parabola once
+            let tangent = parabola.tangent_at(p);
+            assert_eq!(parabola.intersect_line(&tangent).len(), 1);
+        }
+
+        // Every sample should lie on the parabola, and each tangent should fold the focus onto
+        // the directrix
+        for (index, p) in parabola.sample(-4.0, 4.0, 9).iter().enumerate() {
+            assert!(parabola.contains(p));
+            let t = -4.0 + index as f32;
+            let image = reflect(&parabola.focus, &parabola.tangent_at_parameter(t));
+            assert!(dist_point_to_line(&image, &parabola.directrix).abs() < 0.001);
+        }
+
+        // Every tangent through a point below the parabola folds the focus onto the directrix
+        let tangents = parabola.tangents_from(&Multivector::point(1.0, -0.5));
+        assert_eq!(tangents.len(), 2);
+        for tangent in tangents.iter() {
+            let image = reflect(&parabola.focus, tangent);
+            assert!(dist_point_to_line(&image, &parabola.directrix).abs() < 0.001);
+        }
+
+        // Common tangents fold each focus onto its own directrix
+        let other = Parabola::new(
+            &Multivector::point(1.0, 0.0),
+            &Multivector::line(1.0, 0.0, 1.0),
+        );
+        let tangents = parabola.common_tangents(&other);
+        assert!(!tangents.is_empty());
+        for tangent in tangents.iter() {
+            let image = reflect(&parabola.focus, tangent);
+            assert!(dist_point_to_line(&image, &parabola.directrix).abs() < 0.001);
+            let image = reflect(&other.focus, tangent);
+            assert!(dist_point_to_line(&image, &other.directrix).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_segments_and_rays() {
+        let horizontal = Segment::new(&Multivector::point(0.0, 0.0), &Multivector::point(2.0, 0.0));
+        let vertical = Segment::new(
+            &Multivector::point(1.0, -1.0),
+            &Multivector::point(1.0, 1.0),
+        );
+
+        // Should be: <1, 0>
+        let intersection = horizontal.intersect_segment(&vertical).unwrap();
+        assert!(dist_point_to_point(&intersection, &Multivector::point(1.0, 0.0)) < 0.001);
+
+        // The infinite lines meet at <3, 0>, but the segments don't
+        let short = Segment::new(
+            &Multivector::point(3.0, -1.0),
+            &Multivector::point(3.0, 1.0),
+        );
+        assert!(horizontal.intersect_segment(&short).is_none());
+        assert!(horizontal.intersect_line(&short.line()).is_none());
+
+        // A ray pointing towards the segment hits it, but one pointing away does not
+        let towards = Ray::through(&Multivector::point(3.0, 0.0), &Multivector::point(2.0, 0.0));
+        let away = Ray::through(&Multivector::point(3.0, 0.0), &Multivector::point(4.0, 0.0));
+        assert!(towards.intersect_segment(&vertical).is_some());
+        assert!(away.intersect_segment(&vertical).is_none());
+
+        // Clip a diagonal line to the unit square: should be the segment from <0, 0> to <1, 1>
+        let square = [
+            Multivector::point(0.0, 0.0),
+            Multivector::point(1.0, 0.0),
+            Multivector::point(1.0, 1.0),
+            Multivector::point(0.0, 1.0),
+        ];
+        let diagonal = Multivector::line(1.0, -1.0, 0.0);
+        let clipped = clip_line(&diagonal, &square).unwrap();
+        assert!((clipped.length() - 2.0f32.sqrt()).abs() < 0.001);
+        assert!(clip_line(&Multivector::line(1.0, 0.0, -2.0), &square).is_none());
+
+        // Split the square along the same diagonal: should be two triangles
+        let (positive, negative) = split_polygon(&square, &diagonal);
+        assert_eq!(positive.len(), 3);
+        assert_eq!(negative.len(), 3);
+        let (positive, negative) = split_polygon(&square, &Multivector::line(1.0, 0.0, -0.5));
+        assert_eq!(positive.len(), 4);
+        assert_eq!(negative.len(), 4);
+        let (positive, negative) = split_polygon(&square, &Multivector::line(1.0, 0.0, -2.0));
+        assert!(positive.is_empty() ^ negative.is_empty());
+
+        // Crossings are tagged as lying on the line, and a line that's very close to (but not
+        // exactly on) an edge still cuts off a sliver
+        let cut_points = cut_polygon(&square, &Multivector::line(1.0, 0.0, -0.00001));
+        assert_eq!(cut_points.len(), 6);
+        assert_eq!(
+            cut_points.iter().filter(|(_, sign)| *sign == 0.0).count(),
+            2
+        );
+        let (positive, negative) = split_polygon(&square, &Multivector::line(1.0, 0.0, -0.00001));
+        assert_eq!(positive.len(), 4);
+        assert!(negative.is_empty());
+
+        // Within a tolerance, the same line runs along the edge instead
+        let cut_points = cut_polygon_within(&square, &Multivector::line(1.0, 0.0, -0.00001), 0.001);
+        assert_eq!(cut_points.len(), 4);
+        assert_eq!(
+            cut_points.iter().filter(|(_, sign)| *sign == 0.0).count(),
+            2
+        );
+
+        // Rotate the square (which leaves its corners slightly off of its rotated diagonal):
+        // should still be two triangles
+        let R = Multivector::rotor(30.0f32.to_radians(), 0.0, 0.0);
+        let rotated = square
+            .iter()
+            .map(|p| R * (*p) * R.conjugation())
+            .collect::<Vec<_>>();
+        let (positive, negative) = split_polygon(&rotated, &rotated[0].join(&rotated[2]));
+        assert_eq!(positive.len(), 3);
+        assert_eq!(negative.len(), 3);
+    }
 }
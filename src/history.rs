@@ -0,0 +1,264 @@
+use crate::interop::{Line, Point};
+use crate::inverse;
+use crate::multivector::Multivector;
+
+use serde::{Deserialize, Serialize};
+
+/// A fold that has been applied to the paper: the axiom, the inputs that were passed to it, and
+/// which of its creases was chosen.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Fold {
+    pub axiom: u8,
+
+    /// The inputs to the axiom, as a flat list of points (see `session::FoldSession`).
+    pub inputs: Vec<Point>,
+
+    /// The index of the chosen crease among all of the creases that the axiom produces for these
+    /// inputs.
+    pub solution: usize,
+
+    /// The crease itself (which is stored, rather than recomputed, so that a saved tree always
+    /// restores exactly the same creases).
+    pub crease: Line,
+}
+
+/// A node in a `FoldTree`: a state of the paper, reached from its parent state by applying a
+/// single fold.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FoldNode {
+    /// The state that this one was reached from (or `None` for the flat, unfolded paper).
+    pub parent: Option<usize>,
+
+    /// The fold that was applied to the parent state (or `None` for the flat, unfolded paper).
+    pub fold: Option<Fold>,
+
+    /// The states that have been reached from this one, in the order that they were created.
+    pub children: Vec<usize>,
+
+    /// The child that was most recently visited, which is where redoing returns to.
+    last_child: Option<usize>,
+}
+
+/// Every fold that has been applied during a session, as a tree of paper states: undoing a fold
+/// moves back to the parent state, and applying a different fold from there starts a new branch
+/// (rather than discarding the old one).
+///
+/// Node 0 is always the flat, unfolded paper.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FoldTree {
+    nodes: Vec<FoldNode>,
+    current: usize,
+}
+
+impl FoldTree {
+    /// Constructs a new tree that contains only the flat, unfolded paper.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![FoldNode {
+                parent: None,
+                fold: None,
+                children: vec![],
+                last_child: None,
+            }],
+            current: 0,
+        }
+    }
+
+    /// Returns the index of the current state.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Returns the node at `index`, if there is one.
+    pub fn node(&self, index: usize) -> Option<&FoldNode> {
+        self.nodes.get(index)
+    }
+
+    /// Returns the total number of states in the tree (including the flat paper).
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Applies `fold` to the current state, and moves to the resulting state. If the same crease
+    /// has already been applied to the current state, the existing branch is reused.
+    pub fn apply(&mut self, fold: Fold) -> usize {
+        let crease: Multivector = fold.crease.into();
+        let existing = self.nodes[self.current]
+            .children
+            .iter()
+            .cloned()
+            .find(|&child| match &self.nodes[child].fold {
+                Some(other) => inverse::same_line(&crease, &other.crease.into(), 0.001),
+                None => false,
+            });
+
+        let child = match existing {
+            Some(child) => child,
+            None => {
+                self.nodes.push(FoldNode {
+                    parent: Some(self.current),
+                    fold: Some(fold),
+                    children: vec![],
+                    last_child: None,
+                });
+                let child = self.nodes.len() - 1;
+                self.nodes[self.current].children.push(child);
+                child
+            }
+        };
+        self.nodes[self.current].last_child = Some(child);
+        self.current = child;
+        child
+    }
+
+    /// Moves back to the parent of the current state, returning the fold that was undone (or
+    /// `None` if the current state is the flat paper).
+    pub fn undo(&mut self) -> Option<&Fold> {
+        let parent = self.nodes[self.current].parent?;
+        let undone = self.current;
+        self.current = parent;
+        self.nodes[undone].fold.as_ref()
+    }
+
+    /// Moves forward to the most recently visited child of the current state, returning the fold
+    /// that was redone (or `None` if there is no such child).
+    pub fn redo(&mut self) -> Option<&Fold> {
+        let child = self.nodes[self.current].last_child?;
+        self.current = child;
+        self.nodes[child].fold.as_ref()
+    }
+
+    /// Moves to the state at `index`, which may be on any branch.
+    pub fn checkout(&mut self, index: usize) -> Result<(), &'static str> {
+        if index >= self.nodes.len() {
+            return Err("There is no state with this index");
+        }
+
+        // Remember the path to the new state, so that redoing from any of its ancestors returns
+        // to it
+        let mut child = index;
+        while let Some(parent) = self.nodes[child].parent {
+            self.nodes[parent].last_child = Some(child);
+            child = parent;
+        }
+        self.current = index;
+        Ok(())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.nodes[self.current].parent.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.nodes[self.current].last_child.is_some()
+    }
+
+    /// Returns the folds that lead from the flat paper to the current state, in order.
+    pub fn path(&self) -> Vec<&Fold> {
+        let mut folds = vec![];
+        let mut index = self.current;
+        while let Some(fold) = &self.nodes[index].fold {
+            folds.push(fold);
+            index = match self.nodes[index].parent {
+                Some(parent) => parent,
+                None => break,
+            };
+        }
+        folds.reverse();
+        folds
+    }
+
+    /// Serializes the tree (including the current state) to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Restores a tree that was serialized with `to_json`.
+    pub fn from_json(json: &str) -> Result<Self, &'static str> {
+        let tree: Self = serde_json::from_str(json).map_err(|_| "The session could not be read")?;
+
+        // Make sure that the nodes actually form a tree rooted at the flat paper, so that none of
+        // the methods above can index out of bounds (or loop forever)
+        let valid = !tree.nodes.is_empty()
+            && tree.current < tree.nodes.len()
+            && tree.nodes[0].parent.is_none()
+            && tree.nodes.iter().enumerate().all(|(index, node)| {
+                let parent_ok = index == 0
+                    || match node.parent {
+                        Some(parent) => parent < index && node.fold.is_some(),
+                        None => false,
+                    };
+                let children_ok = node.children.iter().all(|&child| {
+                    child < tree.nodes.len() && tree.nodes[child].parent == Some(index)
+                });
+                let last_child_ok = match node.last_child {
+                    Some(child) => node.children.contains(&child),
+                    None => true,
+                };
+                parent_ok && children_ok && last_child_ok
+            });
+        if !valid {
+            return Err("The session does not describe a valid fold tree");
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold(axiom: u8, solution: usize, crease: Line) -> Fold {
+        Fold {
+            axiom,
+            inputs: vec![],
+            solution,
+            crease,
+        }
+    }
+
+    #[test]
+    fn test_fold_tree() {
+        let mut tree = FoldTree::new();
+        assert!(!tree.can_undo());
+        assert!(!tree.can_redo());
+
+        // Two folds in sequence
+        let first = tree.apply(fold(3, 0, Line::new(1.0, 1.0, -1.0)));
+        let second = tree.apply(fold(1, 0, Line::new(1.0, 0.0, -0.5)));
+        assert_eq!(tree.path().len(), 2);
+        assert_eq!(tree.node(second).unwrap().parent, Some(first));
+
+        // Go back to the flat paper and try the other bisector: both branches are kept
+        assert_eq!(tree.undo().unwrap().axiom, 1);
+        assert_eq!(tree.undo().unwrap().solution, 0);
+        assert!(tree.undo().is_none());
+        let other = tree.apply(fold(3, 1, Line::new(1.0, -1.0, 0.0)));
+        assert_eq!(tree.node(0).unwrap().children, vec![first, other]);
+        assert_eq!(tree.len(), 4);
+
+        // Applying a crease that already exists reuses its branch
+        tree.undo();
+        assert_eq!(tree.apply(fold(3, 0, Line::new(-2.0, -2.0, 2.0))), first);
+        assert_eq!(tree.len(), 4);
+
+        // Redoing follows the most recently visited branch
+        tree.checkout(other).unwrap();
+        tree.undo();
+        assert_eq!(tree.redo().unwrap().solution, 1);
+        tree.checkout(second).unwrap();
+        assert_eq!(tree.path().len(), 2);
+        assert!(tree.checkout(10).is_err());
+
+        // Saving and restoring
+        let restored = FoldTree::from_json(&tree.to_json()).unwrap();
+        assert_eq!(restored.current(), second);
+        assert_eq!(restored.len(), 4);
+        assert_eq!(restored.path().len(), 2);
+
+        // Should be: an error (node 1 claims to be its own parent)
+        let json = tree.to_json().replacen("\"parent\":0", "\"parent\":1", 1);
+        assert!(FoldTree::from_json(&json).is_err());
+        assert!(FoldTree::from_json("{}").is_err());
+    }
+}
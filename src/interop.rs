@@ -1,15 +1,26 @@
-use crate::axioms;
+use crate::diagram::{Diagram, DiagramStep};
+use crate::export;
 use crate::geometry;
+use crate::geometry::Segment;
+use crate::interval::{self, Existence};
+use crate::inverse;
+use crate::kinematics::{Assignment, CreaseSegment};
+use crate::layers::FoldMode;
+use crate::lill;
 use crate::multivector::Multivector;
-use crate::utils;
+use crate::recipes::{self, Construction};
+use crate::script::Script;
+use crate::tolerance::ToleranceReport;
+use crate::validation::{self, FoldError, Placement};
 
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 use wasm_bindgen::prelude::*;
 
 /// Convenience line struct for passing data to-from WASM. Represents the line
 /// `ax + by + c = 0`.
 #[wasm_bindgen]
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Line {
     pub a: f32,
     pub b: f32,
@@ -38,7 +49,7 @@ impl From<Multivector> for Line {
 
 /// Convenience point struct for passing data to-from WASM.
 #[wasm_bindgen]
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
@@ -64,21 +75,220 @@ impl From<Multivector> for Point {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// A crease segment of a crease pattern (see `pattern::CreasePattern`), for passing to WASM.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug)]
+pub struct PatternCrease {
+    pub start: Point,
+    pub end: Point,
+
+    /// Whether the crease is a mountain fold (seen from the front of the paper), rather than a
+    /// valley fold.
+    pub mountain: bool,
+}
+
+impl From<&CreaseSegment> for PatternCrease {
+    fn from(crease: &CreaseSegment) -> Self {
+        Self {
+            start: crease.segment.start.into(),
+            end: crease.segment.end.into(),
+            mountain: crease.assignment == Assignment::Mountain,
+        }
+    }
+}
+
+/// How far a crease may be off, given how far its inputs may be off (see
+/// `tolerance::ToleranceReport`).
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug)]
+pub struct CreaseTolerance {
+    /// The crease's angle may be off by up to this many radians (in either direction).
+    pub angle: f32,
+
+    /// Near the middle of the crease, the crease may be off by up to this many units (in either
+    /// direction).
+    pub offset: f32,
+}
+
+impl From<ToleranceReport> for CreaseTolerance {
+    fn from(report: ToleranceReport) -> Self {
+        Self {
+            angle: report.angle,
+            offset: report.offset,
+        }
+    }
+}
+
+/// The result of an axiom: the crease, along with the two halves of the paper on either side of
+/// it (the negative half is reflected across the crease, as if it had been folded flat).
+#[wasm_bindgen]
+#[derive(Clone)]
 pub struct AxiomResult {
-    pub line: Line,
+    crease: Line,
     positive: Vec<Point>,
     negative: Vec<Point>,
+
+    /// The portion of the crease that lies on the paper (if the fold is valid).
+    segment: Option<(Point, Point)>,
+
+    /// If the fold can't actually be performed on the paper, the reason why.
+    reason: Option<String>,
+
+    /// The index of the crease among all of the creases that the axiom produces for the same
+    /// inputs, and the number of such creases.
+    solution: usize,
+    solution_count: usize,
+
+    /// Whether the inputs are too close to a degenerate configuration to be sure that the crease
+    /// exists (see `interval::Existence`).
+    ambiguous: bool,
 }
 
 impl AxiomResult {
-    pub fn new(line: &Line, positive: &Vec<Point>, negative: &Vec<Point>) -> Self {
+    pub fn new(crease: &Line, positive: &Vec<Point>, negative: &Vec<Point>) -> Self {
         Self {
-            line: *line,
+            crease: *crease,
             positive: positive.clone(),
             negative: negative.clone(),
+            segment: None,
+            reason: None,
+            solution: 0,
+            solution_count: 1,
+            ambiguous: false,
         }
     }
+
+    /// Records which of the axiom's creases this is.
+    pub fn with_solution(mut self, solution: usize, solution_count: usize) -> Self {
+        self.solution = solution;
+        self.solution_count = solution_count;
+        self
+    }
+
+    /// Records whether the crease certainly exists (see `interval::Existence`).
+    pub fn with_existence(mut self, existence: Existence) -> Self {
+        self.ambiguous = existence == Existence::Ambiguous;
+        self
+    }
+
+    /// Records the outcome of validating the fold against the paper.
+    pub fn with_validation(mut self, validation: Result<Segment, FoldError>) -> Self {
+        match validation {
+            Ok(segment) => self.segment = Some((segment.start.into(), segment.end.into())),
+            Err(error) => self.reason = Some(error.to_string()),
+        }
+        self
+    }
+}
+
+#[wasm_bindgen]
+impl AxiomResult {
+    /// The crease itself.
+    #[wasm_bindgen(getter)]
+    pub fn crease(&self) -> Line {
+        self.crease
+    }
+
+    /// The vertices of the half of the paper that stays in place.
+    #[wasm_bindgen(getter)]
+    pub fn positive(&self) -> Vec<Point> {
+        self.positive.clone()
+    }
+
+    /// The vertices of the half of the paper that is folded over (after folding).
+    #[wasm_bindgen(getter)]
+    pub fn negative(&self) -> Vec<Point> {
+        self.negative.clone()
+    }
+
+    /// The endpoints of the portion of the crease that lies on the paper, or an empty array if
+    /// the fold is invalid.
+    #[wasm_bindgen(getter)]
+    pub fn segment(&self) -> Vec<Point> {
+        match self.segment {
+            Some((start, end)) => vec![start, end],
+            None => vec![],
+        }
+    }
+
+    /// If the fold can't actually be performed on the paper, the reason why (otherwise,
+    /// `undefined`).
+    #[wasm_bindgen(getter)]
+    pub fn reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    /// The index of the crease among all of the creases that the axiom produces for the same
+    /// inputs.
+    #[wasm_bindgen(getter)]
+    pub fn solution(&self) -> usize {
+        self.solution
+    }
+
+    /// The number of creases that the axiom produces for the same inputs.
+    #[wasm_bindgen(getter)]
+    pub fn solution_count(&self) -> usize {
+        self.solution_count
+    }
+
+    /// Whether the inputs are too close to a degenerate configuration (for example, a circle
+    /// that is tangent to a line in axiom 5) to tell if the crease really exists. If so, the
+    /// crease is only the best guess.
+    #[wasm_bindgen(getter)]
+    pub fn ambiguous(&self) -> bool {
+        self.ambiguous
+    }
+}
+
+/// The reasons why an axiom may not produce a crease at all.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AxiomError {
+    /// The inputs admit no solution (for example, a point that is too far from a line to be
+    /// folded onto it).
+    NoSolution,
+
+    /// The only solution is the line at infinity (for example, bisecting two parallel lines
+    /// that point in opposite directions).
+    LineAtInfinity,
+
+    /// There is no axiom with this number.
+    UnknownAxiom(u8),
+
+    /// Not enough points and/or lines were provided for the axiom.
+    MissingInputs,
+
+    /// The axiom doesn't produce this many creases for these inputs.
+    UnknownSolution(usize),
+}
+
+impl Display for AxiomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AxiomError::NoSolution => write!(f, "No crease exists for this configuration"),
+            AxiomError::LineAtInfinity => {
+                write!(
+                    f,
+                    "The only crease for this configuration is the line at infinity"
+                )
+            }
+            AxiomError::UnknownAxiom(axiom) => write!(f, "There is no axiom {}", axiom),
+            AxiomError::UnknownSolution(index) => {
+                write!(f, "There is no solution {} for this configuration", index)
+            }
+            AxiomError::MissingInputs => {
+                write!(
+                    f,
+                    "Not enough points and lines were provided for this axiom"
+                )
+            }
+        }
+    }
+}
+
+impl From<AxiomError> for JsError {
+    fn from(error: AxiomError) -> Self {
+        JsError::new(&error.to_string())
+    }
 }
 
 #[wasm_bindgen]
@@ -104,64 +314,50 @@ impl Paper {
         vec![self.ul, self.ur, self.lr, self.ll]
     }
 
+    /// Returns the corners of the paper as full multivectors.
+    pub fn vertices(&self) -> Vec<Multivector> {
+        self.points().iter().map(|&vertex| vertex.into()).collect()
+    }
+
+    /// Returns the edges of the paper, in the order: top, right, bottom, left.
+    pub fn edges(&self) -> Vec<Segment> {
+        let vertices = self.vertices();
+        (0..vertices.len())
+            .map(|index| Segment::new(&vertices[index], &vertices[(index + 1) % vertices.len()]))
+            .collect()
+    }
+
+    /// Returns the portion of the crease that actually lies on the paper (or `None` if the crease
+    /// misses the paper entirely).
+    pub fn clip(&self, crease: &Multivector) -> Option<Segment> {
+        geometry::clip_line(crease, &self.vertices())
+    }
+
     pub fn intersect(&self, crease: &Multivector) -> (Vec<Point>, Vec<Point>) {
         // Convert points to full multivectors before continuing
-        let mut vertices: Vec<Multivector> =
-            self.points().iter().map(|&vertex| vertex.into()).collect();
-
-        // Which side of the crease is each corner on?
-        let signs = vertices
-            .iter()
-            .map(|p| utils::sign_with_tolerance(geometry::dist_point_to_line(p, crease)))
-            .collect::<Vec<_>>();
-
-        let mut cut_points = Vec::new();
-
-        for vertex_index in 0..vertices.len() {
-            // The index of the other vertex that forms this edge of the paper
-            let pair_index = (vertex_index + 1) % vertices.len();
-
-            // Always include the corner points
-            cut_points.push(vertices[vertex_index]);
-
-            // Check if the two vertices that form this edge are on opposite sides of the crease
-            // (and not *exactly* incident to it)
-            if signs[vertex_index] != 0.0
-                && signs[pair_index] != 0.0
-                && (signs[vertex_index] != signs[pair_index])
-            {
-                // Insert cut point (where this face's edge intersects the crease)
-                let edge = vertices[vertex_index].join(&vertices[pair_index]);
-                let intersection = edge.meet(crease);
-
-                cut_points.push(intersection)
-            }
-        }
+        let vertices = self.vertices();
 
-        // Which side of the crease is each cut point on (recalculate?
-        let signs = cut_points
-            .iter()
-            .map(|p| utils::sign_with_tolerance(geometry::dist_point_to_line(p, crease)))
-            .collect::<Vec<_>>();
+        // Each cut point is paired with its side of the crease (see `geometry::cut_polygon`)
+        let cut_points = geometry::cut_polygon(&vertices, crease);
 
         let mut positive = Vec::new();
         let mut negative = Vec::new();
 
-        for (index, sign) in signs.into_iter().enumerate() {
+        for (point, sign) in cut_points.into_iter() {
             // Normalize the point
-            let mut point = cut_points[index].normalized();
+            let mut point = point.normalized();
 
             // In both cases below, we normalize the point and divide by its e12
             // (homogeneous coordinate) before returning - the only difference is,
             // for one set of cut points, we reflect them across the crease first
             // (to simulate folding behavior)
-            if sign <= 0.0 || sign.abs() < 0.001 {
+            if sign <= 0.0 {
                 point = geometry::reflect(&point, crease);
                 point /= point.e12();
                 negative.push(point.into());
             }
 
-            if sign >= 0.0 || sign.abs() < 0.001 {
+            if sign >= 0.0 {
                 point /= point.e12();
                 positive.push(point.into());
             }
@@ -171,25 +367,196 @@ impl Paper {
     }
 }
 
-pub fn bundle_results(paper: &Paper, crease: &Multivector) -> JsValue {
+pub fn bundle_results(
+    paper: &Paper,
+    crease: &Multivector,
+    placements: &[Placement],
+) -> AxiomResult {
     // Find where the crease intersects the paper and return
     let (positive, negative) = paper.intersect(crease);
     let line = Line::new(crease.e1(), crease.e2(), crease.e0());
-    let result = AxiomResult::new(&line, &positive, &negative);
+    let validation = validation::validate_fold(&paper.vertices(), crease, placements);
+    AxiomResult::new(&line, &positive, &negative).with_validation(validation)
+}
+
+/// Samples the parabola with focus `p0` and directrix `l0` at `count` points, covering (at least)
+/// the entire paper. The resulting polyline approximates the envelope of all creases that place
+/// `p0` onto `l0` (see axioms 5 and 6).
+#[wasm_bindgen]
+pub fn parabola_envelope(
+    paper: &Paper,
+    p0: Point,
+    l0_src: Point,
+    l0_dst: Point,
+    count: usize,
+) -> Vec<Point> {
+    let l = Into::<Multivector>::into(l0_src) & Into::<Multivector>::into(l0_dst);
+    let parabola = geometry::Parabola::new(&p0.into(), &l);
+
+    // Any parameter beyond the furthest corner of the paper (as measured from the foot of the
+    // perpendicular from the focus to the directrix) produces a point that is off of the paper
+    let vertex = parabola.directrix_point(0.0);
+    let extent = paper
+        .points()
+        .iter()
+        .map(|&corner| geometry::dist_point_to_point(&corner.into(), &vertex))
+        .fold(0.0, f32::max);
+
+    parabola
+        .sample(-extent, extent, count)
+        .into_iter()
+        .map(|point| point.into())
+        .collect()
+}
+
+/// Returns the line tangent to the parabola with focus `p0` and directrix `l0` at parameter `t`,
+/// i.e. the crease that folds `p0` onto the point on `l0` at distance `t` from the foot of the
+/// perpendicular dropped from `p0`.
+#[wasm_bindgen]
+pub fn parabola_tangent(p0: Point, l0_src: Point, l0_dst: Point, t: f32) -> Line {
+    let l = Into::<Multivector>::into(l0_src) & Into::<Multivector>::into(l0_dst);
+    let parabola = geometry::Parabola::new(&p0.into(), &l);
+
+    parabola.tangent_at_parameter(t).into()
+}
+
+/// Returns the number of points and lines (in that order) that the specified axiom takes as
+/// inputs, or `None` if there is no such axiom.
+pub fn axiom_arity(axiom: u8) -> Option<(usize, usize)> {
+    match axiom {
+        1 | 2 => Some((2, 0)),
+        3 => Some((0, 2)),
+        4 => Some((1, 1)),
+        5 => Some((2, 1)),
+        6 => Some((2, 2)),
+        7 => Some((1, 2)),
+        _ => None,
+    }
+}
+
+/// Evaluates the specified axiom with the specified inputs (see `axiom_arity`), returning the
+/// crease and the halves of the paper on either side of it.
+///
+/// Axioms 3, 5, and 6 may produce more than one crease: `solution` selects one of them by index.
+/// If it is `None`, the first crease that can actually be folded on the paper is used (or the
+/// first one overall, if none of them can).
+pub fn evaluate_axiom(
+    paper: &Paper,
+    axiom: u8,
+    points: &[Multivector],
+    lines: &[Multivector],
+    solution: Option<usize>,
+) -> Result<AxiomResult, AxiomError> {
+    let creases = axiom_creases(axiom, points, lines)?;
+    let placements = axiom_placements(axiom, points, lines);
+
+    let index = match solution {
+        Some(index) if index < creases.len() => index,
+        Some(index) => return Err(AxiomError::UnknownSolution(index)),
+        None => {
+            let vertices = paper.vertices();
+            creases
+                .iter()
+                .position(|crease| {
+                    validation::validate_fold(&vertices, crease, &placements).is_ok()
+                })
+                .unwrap_or(0)
+        }
+    };
+
+    Ok(bundle_results(paper, &creases[index], &placements)
+        .with_solution(index, creases.len())
+        .with_existence(axiom_existence(axiom, points, lines)))
+}
+
+/// Returns every crease that the specified axiom produces for the inputs (see
+/// `inverse::evaluate`), or an error if there isn't a single one.
+pub fn axiom_creases(
+    axiom: u8,
+    points: &[Multivector],
+    lines: &[Multivector],
+) -> Result<Vec<Multivector>, AxiomError> {
+    let (point_count, line_count) = axiom_arity(axiom).ok_or(AxiomError::UnknownAxiom(axiom))?;
+    if points.len() < point_count || lines.len() < line_count {
+        return Err(AxiomError::MissingInputs);
+    }
+
+    // Sometimes, an axiom will return a line at infinity (i.e. a line whose only non-zero
+    // coefficient is e0) - while mathematically correct, we want to avoid passing this to the
+    // drawing application
+    //
+    // This occurs when, for example, axiom 3 bisects two lines that are parallel but oriented in
+    // opposite directions
+    let solutions = inverse::evaluate(axiom, points, lines);
+    let creases = solutions
+        .iter()
+        .filter(|crease| crease.norm().abs() >= 0.001)
+        .cloned()
+        .collect::<Vec<_>>();
+    if creases.is_empty() {
+        return Err(if solutions.is_empty() {
+            AxiomError::NoSolution
+        } else {
+            AxiomError::LineAtInfinity
+        });
+    }
+    Ok(creases)
+}
+
+/// Returns whether the specified axiom certainly produces a crease for the inputs, as decided with
+/// interval arithmetic. Only axioms 5 and 7 (see `interval::certify_axiom_5` and
+/// `interval::certify_axiom_7`) can be ambiguous: the others are always certain.
+pub fn axiom_existence(axiom: u8, points: &[Multivector], lines: &[Multivector]) -> Existence {
+    match axiom {
+        5 => interval::certify_axiom_5(&points[0], &points[1], &lines[0]).existence,
+        7 => interval::certify_axiom_7(&points[0], &lines[0], &lines[1]).existence,
+        _ => Existence::Certain,
+    }
+}
+
+/// Returns the points that the specified axiom moves, and where they have to land (see
+/// `validation::validate_fold`).
+pub fn axiom_placements(
+    axiom: u8,
+    points: &[Multivector],
+    lines: &[Multivector],
+) -> Vec<Placement> {
+    match axiom {
+        2 => vec![Placement::free(&points[0])],
+        5 | 7 => vec![Placement::onto(&points[0], &lines[0])],
+        6 => vec![
+            Placement::onto(&points[0], &lines[0]),
+            Placement::onto(&points[1], &lines[1]),
+        ],
+        _ => vec![],
+    }
+}
 
-    JsValue::from_serde(&result).unwrap()
+/// Joins the two segment endpoints to form the line between them.
+fn segment_line(src: Point, dst: Point) -> Multivector {
+    Into::<Multivector>::into(src) & Into::<Multivector>::into(dst)
 }
 
 #[wasm_bindgen]
-pub fn axiom_1(paper: &Paper, p0: Point, p1: Point) -> JsValue {
-    let crease = axioms::axiom_1(&p0.into(), &p1.into());
-    bundle_results(paper, &crease)
+pub fn axiom_1(paper: &Paper, p0: Point, p1: Point) -> Result<AxiomResult, JsError> {
+    Ok(evaluate_axiom(
+        paper,
+        1,
+        &[p0.into(), p1.into()],
+        &[],
+        None,
+    )?)
 }
 
 #[wasm_bindgen]
-pub fn axiom_2(paper: &Paper, p0: Point, p1: Point) -> JsValue {
-    let crease = axioms::axiom_2(&p0.into(), &p1.into());
-    bundle_results(paper, &crease)
+pub fn axiom_2(paper: &Paper, p0: Point, p1: Point) -> Result<AxiomResult, JsError> {
+    Ok(evaluate_axiom(
+        paper,
+        2,
+        &[p0.into(), p1.into()],
+        &[],
+        None,
+    )?)
 }
 
 #[wasm_bindgen]
@@ -199,42 +566,58 @@ pub fn axiom_3(
     l0_dst: Point,
     l1_src: Point,
     l1_dst: Point,
-) -> JsValue {
-    let l0 = Into::<Multivector>::into(l0_src) & Into::<Multivector>::into(l0_dst);
-    let l1 = Into::<Multivector>::into(l1_src) & Into::<Multivector>::into(l1_dst);
-    let crease = axioms::axiom_3(&l0, &l1);
-
-    // Sometimes, this axiom will return a line at infinity (i.e. a line whose only non-zero
-    // coefficient is e0) - while mathematically correct, we want to avoid passing this to the
-    // drawing application
-    //
-    // This occurs when, for example, the two lines are parallel but oriented in opposite
-    // directions
-    if crease.norm().abs() < 0.001 {
-        return JsValue::null();
-    }
-    bundle_results(paper, &crease)
+) -> Result<AxiomResult, JsError> {
+    let lines = [segment_line(l0_src, l0_dst), segment_line(l1_src, l1_dst)];
+    Ok(evaluate_axiom(paper, 3, &[], &lines, None)?)
 }
 
 #[wasm_bindgen]
-pub fn axiom_4(paper: &Paper, p0: Point, l0_src: Point, l0_dst: Point) -> JsValue {
-    // Join the two segment endpoints to form the line between them
-    let l = Into::<Multivector>::into(l0_src) & Into::<Multivector>::into(l0_dst);
-    let crease = axioms::axiom_4(&p0.into(), &l);
-    bundle_results(paper, &crease)
+pub fn axiom_4(
+    paper: &Paper,
+    p0: Point,
+    l0_src: Point,
+    l0_dst: Point,
+) -> Result<AxiomResult, JsError> {
+    let lines = [segment_line(l0_src, l0_dst)];
+    Ok(evaluate_axiom(paper, 4, &[p0.into()], &lines, None)?)
 }
 
 #[wasm_bindgen]
-pub fn axiom_5(paper: &Paper, p0: Point, p1: Point, l0_src: Point, l0_dst: Point) -> JsValue {
-    // Join the two segment endpoints to form the line between them
-    let l = Into::<Multivector>::into(l0_src) & Into::<Multivector>::into(l0_dst);
-    let maybe_crease = axioms::axiom_5(&p0.into(), &p1.into(), &l);
-
-    if let Some(crease) = maybe_crease {
-        return bundle_results(paper, &crease);
-    }
+pub fn axiom_5(
+    paper: &Paper,
+    p0: Point,
+    p1: Point,
+    l0_src: Point,
+    l0_dst: Point,
+) -> Result<AxiomResult, JsError> {
+    let lines = [segment_line(l0_src, l0_dst)];
+    Ok(evaluate_axiom(
+        paper,
+        5,
+        &[p0.into(), p1.into()],
+        &lines,
+        None,
+    )?)
+}
 
-    JsValue::null()
+#[wasm_bindgen]
+pub fn axiom_6(
+    paper: &Paper,
+    p0: Point,
+    p1: Point,
+    l0_src: Point,
+    l0_dst: Point,
+    l1_src: Point,
+    l1_dst: Point,
+) -> Result<AxiomResult, JsError> {
+    let lines = [segment_line(l0_src, l0_dst), segment_line(l1_src, l1_dst)];
+    Ok(evaluate_axiom(
+        paper,
+        6,
+        &[p0.into(), p1.into()],
+        &lines,
+        None,
+    )?)
 }
 
 #[wasm_bindgen]
@@ -245,15 +628,119 @@ pub fn axiom_7(
     l0_dst: Point,
     l1_src: Point,
     l1_dst: Point,
-) -> JsValue {
-    // Join the two segment endpoints to form the line between them
-    let l0 = Into::<Multivector>::into(l0_src) & Into::<Multivector>::into(l0_dst);
-    let l1 = Into::<Multivector>::into(l1_src) & Into::<Multivector>::into(l1_dst);
-    let maybe_crease = axioms::axiom_7(&p0.into(), &l0, &l1);
+) -> Result<AxiomResult, JsError> {
+    let lines = [segment_line(l0_src, l0_dst), segment_line(l1_src, l1_dst)];
+    Ok(evaluate_axiom(paper, 7, &[p0.into()], &lines, None)?)
+}
 
-    if let Some(crease) = maybe_crease {
-        return bundle_results(paper, &crease);
+/// Folds the paper along `crease` by `angle` radians (a valley fold, if positive) and returns the
+/// result as a Wavefront OBJ file, which references the `front` and `back` materials defined in
+/// `material_library` (see `export_mtl`).
+#[wasm_bindgen]
+pub fn export_obj(paper: &Paper, crease: Line, angle: f32, material_library: &str) -> String {
+    export::Mesh::fold(paper, &crease.into(), angle).to_obj(material_library)
+}
+
+/// Returns the material library that accompanies the OBJ files produced by `export_obj`.
+#[wasm_bindgen]
+pub fn export_mtl() -> String {
+    export::Mesh::default().to_mtl()
+}
+
+/// Folds the paper along `crease` by `angle` radians (a valley fold, if positive) and returns the
+/// result as a binary glTF file.
+#[wasm_bindgen]
+pub fn export_glb(paper: &Paper, crease: Line, angle: f32) -> Result<Vec<u8>, JsError> {
+    export::Mesh::fold(paper, &crease.into(), angle)
+        .to_glb()
+        .map_err(JsError::new)
+}
+
+/// Runs a fold script (see `script::Script`) on the paper, returning the crease of each fold that
+/// it performs, in order.
+#[wasm_bindgen]
+pub fn run_script(paper: &Paper, source: &str) -> Result<Vec<Line>, JsError> {
+    let run = Script::parse(source)
+        .and_then(|script| script.run(paper))
+        .map_err(|error| JsError::new(&error.to_string()))?;
+    Ok(run.steps.iter().map(|step| step.crease.into()).collect())
+}
+
+/// Returns the creases of a construction, in order, on the unit square (see `recipes`).
+fn construction_creases(construction: Construction) -> Vec<Line> {
+    construction
+        .creases
+        .iter()
+        .map(|&crease| crease.into())
+        .collect()
+}
+
+/// Trisects the angle `theta` (in radians) at the lower-left corner of the unit square (see
+/// `recipes::trisect_angle`), returning the creases in order: the last one is the trisector.
+#[wasm_bindgen]
+pub fn trisect_angle(theta: f32) -> Result<Vec<Line>, JsError> {
+    recipes::trisect_angle(theta)
+        .map(construction_creases)
+        .ok_or_else(|| JsError::new("This angle is too small to trisect on the paper"))
+}
+
+/// Doubles the cube on the unit square (see `recipes::double_cube`), returning the creases in
+/// order: the last one places the lower-right corner onto the left edge, which it divides in the
+/// ratio `∛2 : 1`.
+#[wasm_bindgen]
+pub fn double_cube() -> Result<Vec<Line>, JsError> {
+    recipes::double_cube()
+        .map(construction_creases)
+        .ok_or_else(|| JsError::new("The construction failed"))
+}
+
+/// Finds the point `<1/n, 0>` on the bottom edge of the unit square (see
+/// `recipes::divide_edge`), returning the creases in order: the last one passes through it.
+#[wasm_bindgen]
+pub fn divide_edge(n: usize) -> Vec<Line> {
+    construction_creases(recipes::divide_edge(n))
+}
+
+/// Solves the cubic `ax³ + bx² + cx + d = 0` by folding (see `lill::solve`), returning each real
+/// root that was found.
+#[wasm_bindgen]
+pub fn solve_cubic(a: f32, b: f32, c: f32, d: f32) -> Result<Vec<f32>, JsError> {
+    let solution = lill::solve(a, b, c, d).map_err(JsError::new)?;
+    Ok(solution.roots.iter().map(|root| root.value).collect())
+}
+
+/// Runs a fold script (see `script::Script`) on the paper, and records each of its folds in a
+/// diagram (through every layer, with the negative side of the crease moving, as in the script).
+fn diagram(paper: &Paper, source: &str) -> Result<Diagram, JsError> {
+    let run = Script::parse(source)
+        .and_then(|script| script.run(paper))
+        .map_err(|error| JsError::new(&error.to_string()))?;
+
+    let mut diagram = Diagram::new(paper);
+    for step in run.steps.iter() {
+        diagram
+            .fold(DiagramStep {
+                crease: step.crease,
+                assignment: step.assignment,
+                moving: None,
+                mode: FoldMode::AllLayers,
+            })
+            .map_err(JsError::new)?;
     }
+    Ok(diagram)
+}
 
-    JsValue::null()
+/// Runs a fold script on the paper, returning its step-by-step folding diagram as a single SVG
+/// document (see `diagram::Diagram::to_svg`).
+#[wasm_bindgen]
+pub fn diagram_svg(paper: &Paper, source: &str) -> Result<String, JsError> {
+    Ok(diagram(paper, source)?.to_svg())
+}
+
+/// Runs a fold script on the paper, then unfolds the result, returning its crease pattern (see
+/// `layers::LayeredState::unfold`).
+#[wasm_bindgen]
+pub fn crease_pattern(paper: &Paper, source: &str) -> Result<Vec<PatternCrease>, JsError> {
+    let pattern = diagram(paper, source)?.unfold();
+    Ok(pattern.creases.iter().map(PatternCrease::from).collect())
 }
@@ -0,0 +1,387 @@
+use crate::backend::{self, GenericMultivector};
+use crate::multivector::Multivector;
+use crate::predicates;
+use crate::scalar::Scalar;
+
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Returns the next representable `f64` above `x` (or `x` itself, if it is infinite or NaN).
+fn next_up(x: f64) -> f64 {
+    if x.is_nan() || x == std::f64::INFINITY {
+        return x;
+    }
+    if x == 0.0 {
+        return std::f64::MIN_POSITIVE * std::f64::EPSILON;
+    }
+    let bits = x.to_bits();
+    f64::from_bits(if x > 0.0 { bits + 1 } else { bits - 1 })
+}
+
+/// Returns the next representable `f64` below `x`.
+fn next_down(x: f64) -> f64 {
+    -next_up(-x)
+}
+
+/// A closed interval `[lo, hi]` of real numbers.
+///
+/// Every operation rounds its result outwards (by one unit in the last place), so the interval
+/// is guaranteed to contain the true result of the computation (i.e. the result that would have
+/// been obtained with exact arithmetic). If the interval doesn't contain zero, the sign of the
+/// true result is known for certain.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+/// A multivector whose coefficients are intervals.
+pub type IntervalMultivector = GenericMultivector<Interval>;
+
+impl Interval {
+    /// Constructs the interval `[lo, hi]`.
+    pub fn new(lo: f64, hi: f64) -> Self {
+        Self { lo, hi }
+    }
+
+    /// Constructs the degenerate interval `[value, value]`.
+    pub fn point(value: f64) -> Self {
+        Self::new(value, value)
+    }
+
+    /// The interval that contains every real number.
+    pub fn entire() -> Self {
+        Self::new(std::f64::NEG_INFINITY, std::f64::INFINITY)
+    }
+
+    /// Rounds the interval outwards, to account for the round-off error of the operation that
+    /// produced it.
+    fn widened(lo: f64, hi: f64) -> Self {
+        Self::new(next_down(lo), next_up(hi))
+    }
+
+    /// The midpoint of the interval.
+    pub fn midpoint(&self) -> f64 {
+        (self.lo + self.hi) * 0.5
+    }
+
+    /// The width of the interval.
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    /// Returns `true` if the interval contains `value`.
+    pub fn contains(&self, value: f64) -> bool {
+        self.lo <= value && value <= self.hi
+    }
+
+    /// Returns `true` if every number in the interval is strictly positive.
+    pub fn is_positive(&self) -> bool {
+        self.lo > 0.0
+    }
+
+    /// Returns `true` if every number in the interval is strictly negative.
+    pub fn is_negative(&self) -> bool {
+        self.hi < 0.0
+    }
+}
+
+impl Scalar for Interval {
+    fn zero() -> Self {
+        Self::point(0.0)
+    }
+
+    fn one() -> Self {
+        Self::point(1.0)
+    }
+
+    fn from_f32(value: f32) -> Self {
+        Self::point(value as f64)
+    }
+
+    fn to_f32(&self) -> f32 {
+        self.midpoint() as f32
+    }
+
+    /// Only the degenerate interval `[0, 0]` is certainly zero.
+    fn is_zero(&self) -> bool {
+        self.lo == 0.0 && self.hi == 0.0
+    }
+
+    /// The square root is taken over the non-negative part of the interval (i.e. the part where
+    /// it is defined), so it is only `None` if the interval is certainly negative.
+    fn sqrt(&self) -> Option<Self> {
+        if self.is_negative() {
+            return None;
+        }
+        if self.is_zero() {
+            return Some(Self::zero());
+        }
+        Some(Self::new(
+            next_down(self.lo.max(0.0).sqrt()).max(0.0),
+            next_up(self.hi.sqrt()),
+        ))
+    }
+}
+
+impl Add for Interval {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::widened(self.lo + rhs.lo, self.hi + rhs.hi)
+    }
+}
+
+impl Sub for Interval {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::widened(self.lo - rhs.hi, self.hi - rhs.lo)
+    }
+}
+
+impl Mul for Interval {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // Products with an exact zero are exact (this keeps zero coefficients from "spreading"
+        // when multiplying multivectors)
+        if self.is_zero() || rhs.is_zero() {
+            return Self::zero();
+        }
+
+        let products = [
+            self.lo * rhs.lo,
+            self.lo * rhs.hi,
+            self.hi * rhs.lo,
+            self.hi * rhs.hi,
+        ];
+        let lo = products.iter().cloned().fold(std::f64::INFINITY, f64::min);
+        let hi = products
+            .iter()
+            .cloned()
+            .fold(std::f64::NEG_INFINITY, f64::max);
+        Self::widened(lo, hi)
+    }
+}
+
+impl Div for Interval {
+    type Output = Self;
+
+    /// Dividing by an interval that contains zero results in the entire real line.
+    fn div(self, rhs: Self) -> Self::Output {
+        if rhs.contains(0.0) {
+            return Self::entire();
+        }
+        let reciprocal = Self::widened(1.0 / rhs.hi, 1.0 / rhs.lo);
+        self * reciprocal
+    }
+}
+
+impl Neg for Interval {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.hi, -self.lo)
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+/// Whether or not a fold exists, as determined by interval arithmetic.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Existence {
+    /// The fold certainly exists.
+    Certain,
+
+    /// The fold certainly does not exist.
+    Impossible,
+
+    /// The inputs are too close to a degenerate configuration (for example, a tangency) to
+    /// decide either way with floating-point arithmetic.
+    Ambiguous,
+}
+
+/// The result of running an axiom over intervals.
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    pub existence: Existence,
+
+    /// Enclosures of each crease (empty if the fold certainly does not exist).
+    pub creases: Vec<IntervalMultivector>,
+}
+
+impl Certificate {
+    /// Returns the (normalized) midpoint of each crease's enclosure.
+    pub fn approximate_creases(&self) -> Vec<Multivector> {
+        self.creases
+            .iter()
+            .map(|crease| crease.to_multivector().normalized())
+            .collect()
+    }
+}
+
+/// Converts the sign of a quantity into an `Existence`, where the fold exists if and only if
+/// the quantity is non-negative.
+fn existence_from_sign(value: &Interval) -> Existence {
+    if value.is_positive() {
+        Existence::Certain
+    } else if value.is_negative() {
+        Existence::Impossible
+    } else {
+        Existence::Ambiguous
+    }
+}
+
+/// Certifies axiom 5 (see `axioms::axiom_5`): the folds that place `p0` onto `l` and pass
+/// through `p1`.
+///
+/// There are two such folds when `backend::axiom_5_discriminant` is positive, and none when it
+/// is negative. If the discriminant's enclosure contains zero (the circle centered at `p1` is
+/// tangent to `l`, or nearly so), the result is ambiguous, and a single enclosure (containing
+/// the tangent fold, if it exists) is returned.
+pub fn certify_axiom_5(p0: &Multivector, p1: &Multivector, l: &Multivector) -> Certificate {
+    let p0 = IntervalMultivector::from_multivector(p0);
+    let p1 = IntervalMultivector::from_multivector(p1);
+    let l = IntervalMultivector::from_multivector(l);
+
+    let existence = existence_from_sign(&backend::axiom_5_discriminant(&p0, &p1, &l));
+
+    let mut images = backend::axiom_5_images(&p0, &p1, &l);
+    if existence == Existence::Ambiguous {
+        images.truncate(1);
+    }
+
+    let creases = images
+        .iter()
+        .map(|image| backend::orthogonal(&p1, &image.join(&p0)))
+        .collect();
+
+    Certificate { existence, creases }
+}
+
+/// Certifies axiom 7 (see `axioms::axiom_7`): the fold that places `p` onto `l0` and is
+/// perpendicular to `l1`.
+///
+/// The fold exists if and only if `l0` and `l1` are not parallel, i.e. if they meet at a
+/// Euclidean point (one whose e12 coefficient is non-zero). Exactly parallel lines are
+/// detected with an exact predicate (see `predicates::meet_weight_sign`), since the interval
+/// enclosure of the e12 coefficient widens with every operation and would contain zero for
+/// parallel lines that aren't axis-aligned. Lines that aren't parallel, but whose meet is so
+/// far away that the enclosure still contains zero, are ambiguous.
+pub fn certify_axiom_7(p: &Multivector, l0: &Multivector, l1: &Multivector) -> Certificate {
+    let parallel = predicates::meet_weight_sign(l0, l1) == 0.0;
+    let p = IntervalMultivector::from_multivector(p);
+    let l0 = IntervalMultivector::from_multivector(l0);
+    let l1 = IntervalMultivector::from_multivector(l1);
+
+    let weight = l0.meet(&l1).e12();
+    let existence = if parallel {
+        Existence::Impossible
+    } else if weight.contains(0.0) {
+        Existence::Ambiguous
+    } else {
+        Existence::Certain
+    };
+
+    let creases = backend::axiom_7(&p, &l0, &l1).into_iter().collect();
+
+    Certificate { existence, creases }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axioms;
+    use crate::geometry;
+
+    #[test]
+    fn test_intervals() {
+        // Should contain 0.1 + 0.2 = 0.3, even though the floating-point sum isn't 0.3
+        let sum = Interval::point(0.1) + Interval::point(0.2);
+        assert!(sum.contains(0.1 + 0.2) && sum.lo <= 0.3 && 0.3 <= sum.hi);
+
+        let product = Interval::new(-1.0, 2.0) * Interval::new(3.0, 4.0);
+        assert!(product.contains(-4.0) && product.contains(8.0));
+
+        assert_eq!(
+            Interval::new(-1.0, 1.0) / Interval::new(-1.0, 1.0),
+            Interval::entire()
+        );
+        assert!(Interval::new(-4.0, -1.0).sqrt().is_none());
+        assert!(Interval::new(-1.0, 4.0).sqrt().unwrap().contains(2.0));
+    }
+
+    #[test]
+    fn test_certify_axiom_5() {
+        // The line `y = 0`
+        let l = Multivector::line(0.0, 1.0, 0.0);
+        let p0 = Multivector::point(0.0, 2.0);
+
+        // The circle centered at `p1` that passes through `p0` crosses the line twice: 2 folds
+        let certificate = certify_axiom_5(&p0, &Multivector::point(0.0, 0.5), &l);
+        assert_eq!(certificate.existence, Existence::Certain);
+        assert_eq!(certificate.creases.len(), 2);
+        for crease in certificate.approximate_creases().iter() {
+            let image = geometry::reflect(&p0, crease);
+            assert!(geometry::dist_point_to_line(&image, &l).abs() < 0.0001);
+        }
+
+        // The circle misses the line entirely: no folds
+        let certificate = certify_axiom_5(&p0, &Multivector::point(0.0, 1.5), &l);
+        assert_eq!(certificate.existence, Existence::Impossible);
+        assert!(certificate.creases.is_empty());
+
+        // Should be: the line `y = 1` (the circle is exactly tangent to the line, so this can't
+        // be decided either way)
+        let certificate = certify_axiom_5(&p0, &Multivector::point(0.0, 1.0), &l);
+        assert_eq!(certificate.existence, Existence::Ambiguous);
+        assert_eq!(certificate.creases.len(), 1);
+        let crease = certificate.approximate_creases()[0];
+        assert!(
+            geometry::dist_point_to_line(&Multivector::point(3.0, 1.0), &crease).abs() < 0.0001
+        );
+
+        // Nudging the line towards `p1` by much less than any fixed tolerance still gives two
+        // folds for certain
+        let l = Multivector::line(0.0, 1.0, -1.0e-7);
+        let certificate = certify_axiom_5(&p0, &Multivector::point(0.0, 1.0), &l);
+        assert_eq!(certificate.existence, Existence::Certain);
+    }
+
+    #[test]
+    fn test_certify_axiom_7() {
+        let p = Multivector::point(0.1, 0.5);
+        let l0 = Multivector::line(0.0, 1.0, -0.9);
+
+        let certificate = certify_axiom_7(&p, &l0, &Multivector::line(1.0, 0.0, -0.5));
+        assert_eq!(certificate.existence, Existence::Certain);
+
+        // Exactly parallel lines
+        let certificate = certify_axiom_7(&p, &l0, &Multivector::line(0.0, 2.0, 1.0));
+        assert_eq!(certificate.existence, Existence::Impossible);
+
+        // Should be: impossible (exactly parallel, but not axis-aligned, so the interval enclosure
+        // of the meet's weight contains zero)
+        let certificate = certify_axiom_7(
+            &p,
+            &Multivector::line(1.0, 1.0, 0.0),
+            &Multivector::line(2.0, 2.0, 1.0),
+        );
+        assert_eq!(certificate.existence, Existence::Impossible);
+        assert!(axioms::axiom_7(
+            &p,
+            &Multivector::line(1.0, 1.0, 0.0),
+            &Multivector::line(2.0, 2.0, 1.0)
+        )
+        .is_none());
+
+        // Nearly parallel lines are still certain, as long as the arithmetic can tell them apart
+        let certificate = certify_axiom_7(&p, &l0, &Multivector::line(1.0e-6, 1.0, 1.0));
+        assert_eq!(certificate.existence, Existence::Certain);
+    }
+}
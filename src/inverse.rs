@@ -0,0 +1,454 @@
+use crate::axioms;
+use crate::backend;
+use crate::dual::{self, DualMultivector};
+use crate::geometry;
+use crate::multivector::*;
+use crate::tolerance::{self, UncertainPoint};
+
+/// The points and lines that are available to fold with: for example, the corners and edges of
+/// the paper, along with any creases that have already been made.
+#[derive(Clone, Debug, Default)]
+pub struct References {
+    pub points: Vec<Multivector>,
+    pub lines: Vec<Multivector>,
+
+    /// The corners of the (convex) paper: new reference points are only added where creases
+    /// cross on the paper. If this is empty, the paper is unbounded.
+    pub paper: Vec<Multivector>,
+}
+
+impl References {
+    /// The corners and edges of the unit square (with the lower-left corner at the origin).
+    pub fn unit_square() -> Self {
+        Self::polygon(&[
+            Multivector::point(0.0, 0.0),
+            Multivector::point(1.0, 0.0),
+            Multivector::point(1.0, 1.0),
+            Multivector::point(0.0, 1.0),
+        ])
+    }
+
+    /// The corners and edges of the polygon with the specified corners (for example, a sheet of
+    /// paper).
+    pub fn polygon(corners: &[Multivector]) -> Self {
+        let lines = (0..corners.len())
+            .map(|index| corners[index].join(&corners[(index + 1) % corners.len()]))
+            .collect();
+
+        Self {
+            points: corners.to_vec(),
+            lines,
+            paper: corners.to_vec(),
+        }
+    }
+
+    /// Adds a crease (which can then be used as a reference line), along with the points where
+    /// it crosses each of the existing reference lines. Crossings that lie off of the paper, or
+    /// that coincide with an existing reference point (for example, a corner that the crease
+    /// passes through), are skipped.
+    pub fn add_crease(&mut self, crease: &Multivector) {
+        for index in 0..self.lines.len() {
+            let mut intersection = self.lines[index].meet(crease);
+            if intersection.e12().abs() <= 0.001 {
+                continue;
+            }
+            intersection /= intersection.e12();
+
+            let on_paper = geometry::polygon_contains(&self.paper, &intersection);
+            let known = self
+                .points
+                .iter()
+                .any(|p| geometry::dist_point_to_point(p, &intersection) <= 0.001);
+            if on_paper && !known {
+                self.points.push(intersection);
+            }
+        }
+        self.lines.push(*crease);
+    }
+}
+
+/// One of the inputs to an axiom, as an index into `References::points` or `References::lines`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Input {
+    Point(usize),
+    Line(usize),
+}
+
+/// A single-axiom way of producing a particular crease.
+#[derive(Clone, Debug)]
+pub struct Solution {
+    /// Which axiom (1-7) produces the crease.
+    pub axiom: u8,
+
+    /// The inputs to the axiom (points first, then lines, in the same order as the arguments to
+    /// the corresponding function in `axioms`).
+    pub inputs: Vec<Input>,
+
+    /// The crease produced by the axiom.
+    pub crease: Multivector,
+
+    /// How far the crease moves (near the reference points) per unit of misplacement of its
+    /// inputs, to first order: smaller is more robust. This is infinite if a small misplacement
+    /// can cause the fold to stop existing altogether.
+    pub sensitivity: f32,
+}
+
+/// Returns `true` if the lines `a` and `b` are the same (regardless of orientation or scale),
+/// within `tolerance`.
+pub fn same_line(a: &Multivector, b: &Multivector, tolerance: f32) -> bool {
+    line_difference(a, b) <= tolerance
+}
+
+/// Returns the largest difference between the coefficients of the lines `a` and `b`, after
+/// normalizing both and orienting them the same way.
+fn line_difference(a: &Multivector, b: &Multivector) -> f32 {
+    let a = a.normalized();
+    let mut b = b.normalized();
+
+    // A degenerate crease (for example, the "line" joining two coincident points, or the "line
+    // at infinity" that bisects two parallel lines) matches nothing
+    let finite = |l: &Multivector| [l.e0(), l.e1(), l.e2()].iter().all(|c| c.is_finite());
+    if !finite(&a) || !finite(&b) {
+        return std::f32::INFINITY;
+    }
+
+    if a.e1() * b.e1() + a.e2() * b.e2() < 0.0 {
+        b = -b;
+    }
+
+    [a.e0() - b.e0(), a.e1() - b.e1(), a.e2() - b.e2()]
+        .iter()
+        .fold(0.0f32, |max, d| max.max(d.abs()))
+}
+
+/// Returns every crease produced by the specified axiom with the specified inputs (axioms 3, 5,
+/// and 6 may have more than one).
+pub fn evaluate(axiom: u8, points: &[Multivector], lines: &[Multivector]) -> Vec<Multivector> {
+    match axiom {
+        1 => vec![axioms::axiom_1(&points[0], &points[1])],
+        2 => vec![axioms::axiom_2(&points[0], &points[1])],
+        // Both angle bisectors
+        3 => vec![
+            axioms::axiom_3(&lines[0], &lines[1]),
+            axioms::axiom_3(&lines[0], &-lines[1]),
+        ],
+        4 => vec![axioms::axiom_4(&points[0], &lines[0])],
+        5 => axioms::axiom_5(&points[0], &points[1], &lines[0]),
+        6 => axioms::axiom_6(&points[0], &points[1], &lines[0], &lines[1]),
+        7 => axioms::axiom_7(&points[0], &lines[0], &lines[1])
+            .into_iter()
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns every combination of inputs for the specified axiom. Combinations that can only
+/// produce the same creases as another combination (for example, swapping the two points of
+/// axiom 1) are skipped.
+fn combinations(axiom: u8, references: &References) -> Vec<Vec<Input>> {
+    let points = references.points.len();
+    let lines = references.lines.len();
+    let mut result = Vec::new();
+
+    match axiom {
+        1 | 2 => {
+            for i in 0..points {
+                for j in (i + 1)..points {
+                    result.push(vec![Input::Point(i), Input::Point(j)]);
+                }
+            }
+        }
+        3 => {
+            for i in 0..lines {
+                for j in (i + 1)..lines {
+                    result.push(vec![Input::Line(i), Input::Line(j)]);
+                }
+            }
+        }
+        4 => {
+            for i in 0..points {
+                for j in 0..lines {
+                    result.push(vec![Input::Point(i), Input::Line(j)]);
+                }
+            }
+        }
+        5 => {
+            for i in 0..points {
+                for j in (0..points).filter(|&j| j != i) {
+                    for k in 0..lines {
+                        result.push(vec![Input::Point(i), Input::Point(j), Input::Line(k)]);
+                    }
+                }
+            }
+        }
+        6 => {
+            // Swapping both the points and the lines gives the same folds
+            for i in 0..points {
+                for j in (i + 1)..points {
+                    for k in 0..lines {
+                        for l in (0..lines).filter(|&l| l != k) {
+                            result.push(vec![
+                                Input::Point(i),
+                                Input::Point(j),
+                                Input::Line(k),
+                                Input::Line(l),
+                            ]);
+                        }
+                    }
+                }
+            }
+        }
+        7 => {
+            for i in 0..points {
+                for k in 0..lines {
+                    for l in (0..lines).filter(|&l| l != k) {
+                        result.push(vec![Input::Point(i), Input::Line(k), Input::Line(l)]);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    result
+}
+
+/// Splits the inputs into the corresponding points and lines.
+fn resolve(inputs: &[Input], references: &References) -> (Vec<Multivector>, Vec<Multivector>) {
+    let mut points = Vec::new();
+    let mut lines = Vec::new();
+    for input in inputs.iter() {
+        match *input {
+            Input::Point(index) => points.push(references.points[index]),
+            Input::Line(index) => lines.push(references.lines[index]),
+        }
+    }
+    (points, lines)
+}
+
+/// Returns how far `crease` moves at the point `reference` per unit of misplacement of each
+/// input, summed over all of the inputs (see `tolerance::crease_tolerance`). Each line is given
+/// by two points on it, one unit apart, starting from the point on the line closest to
+/// `reference`. This is infinite if the crease can't be differentiated (for example, at a
+/// double root of axiom 6).
+fn sensitivity(
+    axiom: u8,
+    points: &[Multivector],
+    lines: &[Multivector],
+    crease: &Multivector,
+    reference: &Multivector,
+) -> f32 {
+    let mut inputs = points
+        .iter()
+        .map(|p| UncertainPoint::new(p, 1.0))
+        .collect::<Vec<_>>();
+    for line in lines.iter() {
+        let mut foot = geometry::project(reference, line);
+        foot /= foot.e12();
+        inputs.push(UncertainPoint::new(&foot, 1.0));
+        inputs.push(UncertainPoint::new(
+            &geometry::translate_along(&foot, line, 1.0),
+            1.0,
+        ));
+    }
+
+    let report = tolerance::crease_tolerance(
+        &inputs,
+        reference,
+        construction(axiom, points.len(), crease),
+    );
+    report.map_or(std::f32::INFINITY, |report| report.offset)
+}
+
+/// Returns the construction (see `dual::differentiate`) that produces the solution `crease` of
+/// the specified axiom, from the axiom's `point_count` points followed by two points on each of
+/// its lines (the same layout as the inputs of `session::FoldSession`). For axioms with more
+/// than one solution, the construction picks the one closest to `crease`.
+pub fn construction(
+    axiom: u8,
+    point_count: usize,
+    crease: &Multivector,
+) -> impl Fn(&[DualMultivector]) -> Option<DualMultivector> {
+    let crease = crease.normalized();
+    let closest = move |candidates: Vec<DualMultivector>| {
+        candidates.into_iter().min_by(|a, b| {
+            line_difference(&a.to_multivector(), &crease)
+                .partial_cmp(&line_difference(&b.to_multivector(), &crease))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    };
+
+    move |p: &[DualMultivector]| {
+        let line = |index: usize| p[point_count + 2 * index].join(&p[point_count + 2 * index + 1]);
+        match axiom {
+            1 => Some(backend::axiom_1(&p[0], &p[1])),
+            2 => Some(backend::axiom_2(&p[0], &p[1])),
+            3 => closest(
+                vec![
+                    backend::axiom_3(&line(0), &line(1)),
+                    backend::axiom_3(&line(0), &-line(1)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+            ),
+            4 => Some(backend::axiom_4(&p[0], &line(0))),
+            5 => closest(backend::axiom_5(&p[0], &p[1], &line(0))),
+            6 => dual::axiom_6(&p[0], &p[1], &p[2], &p[3], &line(1), &crease),
+            7 => backend::axiom_7(&p[0], &line(0), &line(1)),
+            _ => None,
+        }
+    }
+}
+
+/// Lists every single-axiom way of producing the crease `target` from the reference points and
+/// lines, ranked by robustness (most robust first). Creases are compared projectively (see
+/// `same_line`), within `tolerance`.
+pub fn find_axioms(references: &References, target: &Multivector, tolerance: f32) -> Vec<Solution> {
+    // Measure how far creases move near the middle of the reference points (or the origin, if
+    // there aren't any)
+    let mut centroid = references
+        .points
+        .iter()
+        .fold(Multivector::zeros(), |sum, p| sum + (*p) / p.e12());
+    if references.points.is_empty() {
+        centroid = Multivector::point(0.0, 0.0);
+    }
+    centroid /= centroid.e12();
+    let mut reference = geometry::project(&centroid, target);
+    reference /= reference.e12();
+
+    let mut solutions = Vec::new();
+    for axiom in 1..=7 {
+        for inputs in combinations(axiom, references) {
+            let (points, lines) = resolve(&inputs, references);
+
+            for crease in evaluate(axiom, &points, &lines) {
+                if same_line(&crease, target, tolerance) {
+                    let sensitivity = sensitivity(axiom, &points, &lines, &crease, &reference);
+                    solutions.push(Solution {
+                        axiom,
+                        inputs: inputs.clone(),
+                        crease: crease.normalized(),
+                        sensitivity,
+                    });
+                }
+            }
+        }
+    }
+
+    solutions.sort_by(|a, b| {
+        a.sensitivity
+            .partial_cmp(&b.sensitivity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    solutions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_axioms() {
+        // The vertical line `x = 0.5`: axiom 2 (corner onto adjacent corner) and axiom 3 (left
+        // edge onto right edge) should be found
+        let references = References::unit_square();
+        let target = Multivector::line(1.0, 0.0, -0.5);
+        let solutions = find_axioms(&references, &target, 0.001);
+        for solution in solutions.iter() {
+            println!(
+                "Axiom {} with {:?}: sensitivity {}",
+                solution.axiom, solution.inputs, solution.sensitivity
+            );
+            assert!(same_line(&solution.crease, &target, 0.001));
+        }
+        assert!(solutions.iter().any(|s| s.axiom == 2));
+        assert!(solutions.iter().any(|s| s.axiom == 3));
+        assert!(!solutions.iter().any(|s| s.axiom == 4));
+        for window in solutions.windows(2) {
+            assert!(window[0].sensitivity <= window[1].sensitivity);
+        }
+
+        // Should be: √2, since misplacing either corner by one unit along either axis moves the
+        // crease by half a unit at the center of the paper (so √½ in the worst direction)
+        let corners = solutions
+            .iter()
+            .find(|s| s.axiom == 2 && s.inputs == vec![Input::Point(0), Input::Point(1)])
+            .unwrap();
+        assert!((corners.sensitivity - 2.0f32.sqrt()).abs() < 0.001);
+
+        // Once the midpoints of the top and bottom edges are available, so is axiom 4 (the
+        // perpendicular to either edge through its midpoint)
+        let mut midpoints = References::unit_square();
+        midpoints.points.push(Multivector::point(0.5, 0.0));
+        midpoints.points.push(Multivector::point(0.5, 1.0));
+        let solutions = find_axioms(&midpoints, &target, 0.001);
+        assert!(solutions
+            .iter()
+            .any(|s| s.axiom == 4 && s.sensitivity.is_finite()));
+
+        // Should be: the diagonal, via axiom 1 (through two corners) among others
+        let diagonal = Multivector::line(1.0, -1.0, 0.0);
+        let solutions = find_axioms(&references, &diagonal, 0.001);
+        assert!(solutions
+            .iter()
+            .any(|s| s.axiom == 1 && s.inputs == vec![Input::Point(0), Input::Point(2)]));
+
+        // Nothing folds the square into this line in a single step
+        let arbitrary = Multivector::line(0.3, 1.0, -0.123);
+        assert!(find_axioms(&references, &arbitrary, 0.001).is_empty());
+
+        // Adding a prior crease makes new solutions available
+        let mut references = References::unit_square();
+        references.add_crease(&target);
+        let quarter = Multivector::line(1.0, 0.0, -0.25);
+        assert!(!find_axioms(&references, &quarter, 0.001).is_empty());
+
+        // Without any reference points, creases are measured near the origin
+        let mut references = References::default();
+        references.lines.push(Multivector::line(1.0, 0.0, 0.0));
+        references.lines.push(Multivector::line(1.0, 0.0, -1.0));
+        let solutions = find_axioms(&references, &target, 0.001);
+        assert_eq!(solutions.len(), 1);
+        assert!(solutions[0].sensitivity.is_finite());
+    }
+
+    #[test]
+    fn test_add_crease() {
+        // An oblique crease `y = 0.5x + 0.25` crosses the left and right edges on the paper, but
+        // the lines through the top and bottom edges off of it
+        let mut references = References::unit_square();
+        references.add_crease(&Multivector::line(0.5, -1.0, 0.25));
+        assert_eq!(references.lines.len(), 5);
+        assert_eq!(references.points.len(), 6);
+        for p in references.points[4..].iter() {
+            println!("Crossing: ({}, {})", p.e20(), p.e01());
+            assert!(geometry::polygon_contains(&references.paper, p));
+        }
+
+        // Should be: (0, 0.25) and (1, 0.75)
+        let expected = [Multivector::point(0.0, 0.25), Multivector::point(1.0, 0.75)];
+        for p in expected.iter() {
+            assert!(references
+                .points
+                .iter()
+                .any(|q| geometry::dist_point_to_point(p, q) < 0.001));
+        }
+
+        // The diagonal passes through two corners (where it crosses two edges each), and crosses
+        // the oblique crease at (0.5, 0.5): that's the only new point
+        references.add_crease(&Multivector::line(1.0, -1.0, 0.0));
+        assert_eq!(references.lines.len(), 6);
+        assert_eq!(references.points.len(), 7);
+        let crossing = references.points[6];
+        assert!((crossing.e20() - 0.5).abs() < 0.001);
+        assert!((crossing.e01() - 0.5).abs() < 0.001);
+
+        // Without a paper, every crossing is kept
+        let mut references = References::default();
+        references.lines.push(Multivector::line(0.0, 1.0, -1.0));
+        references.add_crease(&Multivector::line(0.5, -1.0, 0.25));
+        assert_eq!(references.points.len(), 1);
+    }
+}
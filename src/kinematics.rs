@@ -0,0 +1,502 @@
+use crate::folding::{self, Crease};
+use crate::geometry::{self, Segment};
+use crate::multivector::Multivector;
+use crate::pga3d::{self, Multivector3};
+use crate::utils;
+
+use std::fmt::Display;
+
+/// Whether a crease is folded as a mountain or a valley. This selects which of the (several)
+/// possible rigid motions of a pattern is followed, since every pattern can start folding in
+/// more than one way.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Assignment {
+    Mountain,
+    Valley,
+    Unassigned,
+}
+
+impl Assignment {
+    /// Returns the sign of fold angles with this assignment (see `folding::Crease`).
+    pub fn sign(&self) -> f32 {
+        match self {
+            Assignment::Mountain => -1.0,
+            Assignment::Valley => 1.0,
+            Assignment::Unassigned => 0.0,
+        }
+    }
+}
+
+/// A crease segment in a (flat) crease pattern.
+#[derive(Copy, Clone, Debug)]
+pub struct CreaseSegment {
+    pub segment: Segment,
+    pub assignment: Assignment,
+}
+
+impl CreaseSegment {
+    /// Constructs a new crease segment between the points `start` and `end`.
+    pub fn new(start: &Multivector, end: &Multivector, assignment: Assignment) -> Self {
+        Self {
+            segment: Segment::new(start, end),
+            assignment,
+        }
+    }
+}
+
+/// A vertex in the interior of the paper, where several creases meet.
+#[derive(Clone, Debug)]
+struct Vertex {
+    point: Multivector,
+
+    /// The indices of the creases that meet at this vertex, in counter-clockwise order.
+    creases: Vec<usize>,
+
+    /// For each crease, a point that lies in the panel immediately counter-clockwise of it.
+    panels: Vec<Multivector>,
+}
+
+/// A vertex at which the panels can't be assembled rigidly with the current fold angles.
+#[derive(Copy, Clone, Debug)]
+pub struct ClosureViolation {
+    pub vertex: Multivector,
+
+    /// How far the composition of the rotations about the creases around the vertex is from the
+    /// identity motor (which it would be, if the panels closed up rigidly).
+    pub residual: f32,
+}
+
+/// The reasons why a crease pattern can't be driven to a particular fold angle.
+#[derive(Clone, Debug)]
+pub enum KinematicsError {
+    /// The driving crease doesn't exist.
+    InvalidDriver(usize),
+
+    /// There is no rigid motion of the panels that reaches the driving angle: the motion breaks
+    /// down at `angle`, where the panels fail to close up around each of the `violations`.
+    NoRigidMotion {
+        angle: f32,
+        violations: Vec<ClosureViolation>,
+    },
+}
+
+impl Display for KinematicsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KinematicsError::InvalidDriver(index) => {
+                write!(f, "There is no crease with index {}", index)
+            }
+            KinematicsError::NoRigidMotion { angle, violations } => {
+                write!(
+                    f,
+                    "No rigid motion reaches a driving angle of {} radians: the panels don't close up at",
+                    angle
+                )?;
+                for violation in violations.iter() {
+                    write!(
+                        f,
+                        " <{}, {}> (residual {})",
+                        violation.vertex.e20(),
+                        violation.vertex.e01(),
+                        violation.residual
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A crease pattern whose panels are treated as rigid plates, hinged along the creases.
+///
+/// Around each interior vertex, the panels are connected in a loop: starting from any panel,
+/// crossing each crease in turn (i.e. applying the 3D rotor about each crease's axis, see
+/// `folding::crease_rotation`) must bring the panel back to where it started. This is the
+/// closure constraint, and the fold angles of a rigid motion are exactly those for which the
+/// constraint is satisfied at every interior vertex.
+#[derive(Clone, Debug)]
+pub struct Mechanism {
+    creases: Vec<CreaseSegment>,
+    vertices: Vec<Vertex>,
+}
+
+impl Mechanism {
+    /// Builds the mechanism for a crease pattern on the (convex) paper with the specified
+    /// corners. Creases that share an endpoint meet at a vertex: vertices on the boundary of
+    /// the paper are free, and impose no constraints.
+    pub fn new(paper: &[Multivector], creases: &[CreaseSegment]) -> Self {
+        // Gather the distinct endpoints (and the creases that meet at each)
+        let mut points: Vec<(Multivector, Vec<usize>)> = Vec::new();
+        for (index, crease) in creases.iter().enumerate() {
+            for endpoint in [crease.segment.start, crease.segment.end].iter() {
+                match points
+                    .iter_mut()
+                    .find(|(p, _)| geometry::dist_point_to_point(p, endpoint) < 0.001)
+                {
+                    Some((_, incident)) => incident.push(index),
+                    None => points.push((*endpoint, vec![index])),
+                }
+            }
+        }
+
+        let on_boundary = |p: &Multivector| {
+            (0..paper.len()).any(|index| {
+                let edge = Segment::new(&paper[index], &paper[(index + 1) % paper.len()]);
+                edge.contains(p)
+            })
+        };
+
+        let vertices = points
+            .into_iter()
+            .filter(|(p, incident)| incident.len() > 1 && !on_boundary(p))
+            .map(|(point, incident)| {
+                // The direction of each crease, pointing away from the vertex
+                let outgoing = |index: usize| {
+                    let segment = &creases[index].segment;
+                    let other = if geometry::dist_point_to_point(&segment.start, &point) < 0.001 {
+                        segment.end
+                    } else {
+                        segment.start
+                    };
+                    (other.e01() - point.e01()).atan2(other.e20() - point.e20())
+                };
+
+                let mut incident = incident
+                    .into_iter()
+                    .map(|index| (index, outgoing(index)))
+                    .collect::<Vec<_>>();
+                incident.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+                // A point just inside of each panel, along the bisector of its sector
+                let panels = (0..incident.len())
+                    .map(|k| {
+                        let start = incident[k].1;
+                        let mut end = incident[(k + 1) % incident.len()].1;
+                        if end <= start {
+                            end += 2.0 * std::f32::consts::PI;
+                        }
+                        let bisector = 0.5 * (start + end);
+                        Multivector::point(
+                            point.e20() + 0.01 * bisector.cos(),
+                            point.e01() + 0.01 * bisector.sin(),
+                        )
+                    })
+                    .collect();
+
+                Vertex {
+                    point,
+                    creases: incident.into_iter().map(|(index, _)| index).collect(),
+                    panels,
+                }
+            })
+            .collect();
+
+        Self {
+            creases: creases.to_vec(),
+            vertices,
+        }
+    }
+
+    /// Returns the interior vertices of the pattern.
+    pub fn interior_vertices(&self) -> Vec<Multivector> {
+        self.vertices.iter().map(|vertex| vertex.point).collect()
+    }
+
+    /// Returns the motor obtained by crossing every crease around the vertex in turn: this is
+    /// the identity (up to sign) if the panels close up.
+    fn loop_motor(&self, vertex: &Vertex, angles: &[f32]) -> Multivector3 {
+        vertex.creases.iter().zip(vertex.panels.iter()).fold(
+            Multivector3::scalar_value(1.0),
+            |motor, (&index, panel)| {
+                let crease = Crease::new(&self.creases[index].segment.line(), angles[index]);
+                motor * folding::crease_rotation(&crease, panel)
+            },
+        )
+    }
+
+    /// Returns the coefficients of the difference between the loop motor of each vertex and the
+    /// identity, concatenated.
+    fn residual_vector(&self, angles: &[f32]) -> Vec<f64> {
+        let mut residuals = Vec::with_capacity(self.vertices.len() * pga3d::BASIS_COUNT);
+        for vertex in self.vertices.iter() {
+            let mut motor = self.loop_motor(vertex, angles);
+
+            // A motor and its negation represent the same rigid motion
+            if motor.scalar() < 0.0 {
+                motor = -motor;
+            }
+            motor[0] -= 1.0;
+            residuals.extend((0..pga3d::BASIS_COUNT).map(|index| motor[index] as f64));
+        }
+        residuals
+    }
+
+    /// Returns how far the panels are from closing up around each interior vertex (in the same
+    /// order as `interior_vertices`), with the specified fold angles (one per crease).
+    pub fn closure_residuals(&self, angles: &[f32]) -> Vec<f32> {
+        let residuals = self.residual_vector(angles);
+        residuals
+            .chunks(pga3d::BASIS_COUNT)
+            .map(|chunk| chunk.iter().map(|r| r * r).sum::<f64>().sqrt() as f32)
+            .collect()
+    }
+
+    /// Computes the fold angles of every crease (in radians, see `folding::Crease`) when the
+    /// crease at index `driver` is folded to `angle`, such that the panels stay rigid.
+    ///
+    /// The motion is followed continuously from the flat state, in small steps: the crease
+    /// assignments pick out which of the possible motions to follow, so each crease should be
+    /// assigned (unassigned creases start out unfolded). Creases that don't meet any interior
+    /// vertex are unconstrained, and are left flat. At each step, the closure constraints are
+    /// solved for the fold angles via (damped) Gauss-Newton iteration.
+    pub fn solve(&self, driver: usize, angle: f32) -> Result<Vec<f32>, KinematicsError> {
+        if driver >= self.creases.len() {
+            return Err(KinematicsError::InvalidDriver(driver));
+        }
+
+        // The fold angles that are solved for
+        let unknowns = (0..self.creases.len())
+            .filter(|&index| {
+                index != driver
+                    && self
+                        .vertices
+                        .iter()
+                        .any(|vertex| vertex.creases.contains(&index))
+            })
+            .collect::<Vec<_>>();
+
+        let steps = ((angle.abs() / 0.05).ceil() as usize).max(1);
+        let mut angles = vec![0.0; self.creases.len()];
+        let mut previous = 0.0;
+
+        for step in 1..=steps {
+            let current = angle * (step as f32) / (steps as f32);
+
+            // Predict the next state: at first, from the assignments, and afterwards, by scaling
+            // the previous state (fold angles grow roughly in proportion to each other)
+            for &index in unknowns.iter() {
+                angles[index] = if step == 1 {
+                    self.creases[index].assignment.sign() * current.abs()
+                } else {
+                    angles[index] * current / previous
+                };
+            }
+            angles[driver] = current;
+            previous = current;
+
+            self.correct(&mut angles, &unknowns);
+
+            let violations = self
+                .closure_residuals(&angles)
+                .into_iter()
+                .zip(self.vertices.iter())
+                .filter(|(residual, _)| *residual > 0.001)
+                .map(|(residual, vertex)| ClosureViolation {
+                    vertex: vertex.point,
+                    residual,
+                })
+                .collect::<Vec<_>>();
+            if !violations.is_empty() {
+                return Err(KinematicsError::NoRigidMotion {
+                    angle: current,
+                    violations,
+                });
+            }
+        }
+
+        Ok(angles)
+    }
+
+    /// Refines the `unknowns` of `angles` (in-place) to minimize the closure residuals.
+    fn correct(&self, angles: &mut [f32], unknowns: &[usize]) {
+        let h = 0.001;
+        let damping = 1e-6;
+
+        for _ in 0..50 {
+            let residual = self.residual_vector(angles);
+            if residual.iter().map(|r| r * r).sum::<f64>().sqrt() < 1e-6 {
+                break;
+            }
+
+            // The Jacobian (via central differences), one column per unknown
+            let columns = unknowns
+                .iter()
+                .map(|&index| {
+                    let original = angles[index];
+                    angles[index] = original + h;
+                    let forward = self.residual_vector(angles);
+                    angles[index] = original - h;
+                    let backward = self.residual_vector(angles);
+                    angles[index] = original;
+                    forward
+                        .iter()
+                        .zip(backward.iter())
+                        .map(|(f, b)| (f - b) / (2.0 * h as f64))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            // Solve the normal equations `(JᵀJ + λI) δ = -Jᵀr`
+            let n = unknowns.len();
+            let dot =
+                |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+            let matrix = (0..n)
+                .map(|i| {
+                    (0..n)
+                        .map(|j| dot(&columns[i], &columns[j]) + if i == j { damping } else { 0.0 })
+                        .collect()
+                })
+                .collect();
+            let rhs = (0..n).map(|i| -dot(&columns[i], &residual)).collect();
+
+            match utils::solve_linear(matrix, rhs) {
+                Some(delta) => {
+                    for (k, &index) in unknowns.iter().enumerate() {
+                        angles[index] += delta[k] as f32;
+                    }
+                    if delta.iter().all(|d| d.abs() < 1e-7) {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single vertex at the origin, with creases running out to the specified angles (in
+    /// degrees), on a square sheet of paper around it.
+    fn single_vertex(directions: &[(f32, Assignment)]) -> Mechanism {
+        let paper = [
+            Multivector::point(-2.0, -2.0),
+            Multivector::point(2.0, -2.0),
+            Multivector::point(2.0, 2.0),
+            Multivector::point(-2.0, 2.0),
+        ];
+        let origin = Multivector::point(0.0, 0.0);
+        let creases = directions
+            .iter()
+            .map(|&(degrees, assignment)| {
+                let radians = degrees.to_radians();
+                let end = Multivector::point(radians.cos(), radians.sin());
+                CreaseSegment::new(&origin, &end, assignment)
+            })
+            .collect::<Vec<_>>();
+        Mechanism::new(&paper, &creases)
+    }
+
+    #[test]
+    fn test_straight_fold() {
+        // Two perpendicular straight lines: driving one half of the horizontal line folds the
+        // other half by the same amount, and the vertical line stays flat
+        use Assignment::*;
+        let mechanism = single_vertex(&[
+            (0.0, Valley),
+            (90.0, Unassigned),
+            (180.0, Valley),
+            (270.0, Unassigned),
+        ]);
+        assert_eq!(mechanism.interior_vertices().len(), 1);
+
+        let angles = mechanism.solve(0, 1.0).unwrap();
+        println!("Angles: {:?}", angles);
+        assert!((angles[2] - 1.0).abs() < 0.001);
+        assert!(angles[1].abs() < 0.001 && angles[3].abs() < 0.001);
+    }
+
+    #[test]
+    fn test_degree_4_vertex() {
+        // A flat-foldable (Miura-ori) vertex with sector angles 60, 120, 120, 60 degrees: the
+        // crease between the two small sectors is the odd one out
+        use Assignment::*;
+        let mechanism = single_vertex(&[
+            (0.0, Valley),
+            (60.0, Mountain),
+            (180.0, Mountain),
+            (300.0, Mountain),
+        ]);
+
+        let mut ratios = Vec::new();
+        for &target in [0.5, 1.5, 3.0].iter() {
+            let angles = mechanism.solve(0, target).unwrap();
+            println!("Driving angle {}: {:?}", target, angles);
+            assert!(mechanism.closure_residuals(&angles)[0] < 0.001);
+
+            // Should be: opposite creases fold by the same amount, and the creases follow
+            // their assignments
+            assert!((angles[0].abs() - angles[2].abs()).abs() < 0.001);
+            assert!((angles[1] - angles[3]).abs() < 0.001);
+            assert!(angles[1] < 0.0 && angles[2] < 0.0);
+
+            ratios.push((angles[1] * 0.5).tan() / (angles[0] * 0.5).tan());
+        }
+
+        // The tangents of the half fold angles of a flat-foldable degree-4 vertex stay in a
+        // constant ratio throughout the motion
+        println!("Ratios: {:?}", ratios);
+        for ratio in ratios.iter() {
+            assert!((ratio - ratios[0]).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_miura_ori() {
+        // Two (flat-foldable) vertices that share a crease: <0.5, 0> and <0, 1>
+        use Assignment::*;
+        let paper = [
+            Multivector::point(-1.0, -1.0),
+            Multivector::point(1.0, -1.0),
+            Multivector::point(1.0, 2.0),
+            Multivector::point(-1.0, 2.0),
+        ];
+        let p = |x, y| Multivector::point(x, y);
+        let creases = [
+            CreaseSegment::new(&p(-1.0, 0.0), &p(0.5, 0.0), Valley),
+            CreaseSegment::new(&p(0.5, 0.0), &p(1.0, 0.0), Mountain),
+            CreaseSegment::new(&p(-1.0, 1.0), &p(0.0, 1.0), Mountain),
+            CreaseSegment::new(&p(0.0, 1.0), &p(1.0, 1.0), Valley),
+            CreaseSegment::new(&p(0.0, -1.0), &p(0.5, 0.0), Mountain),
+            CreaseSegment::new(&p(0.5, 0.0), &p(0.0, 1.0), Mountain),
+            CreaseSegment::new(&p(0.0, 1.0), &p(0.5, 2.0), Mountain),
+        ];
+        let mechanism = Mechanism::new(&paper, &creases);
+        assert_eq!(mechanism.interior_vertices().len(), 2);
+
+        let angles = mechanism.solve(0, 1.2).unwrap();
+        println!("Angles: {:?}", angles);
+        for residual in mechanism.closure_residuals(&angles).iter() {
+            assert!(*residual < 0.001);
+        }
+
+        // Should be: the zig-zag creases all fold by the same amount, as do the straight ones
+        assert!((angles[4] - angles[5]).abs() < 0.001);
+        assert!((angles[5] - angles[6]).abs() < 0.001);
+        for index in 1..4 {
+            assert!((angles[index].abs() - angles[0].abs()).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_no_rigid_motion() {
+        // A degree-3 vertex is rigid: it can't fold at all without bending a panel
+        use Assignment::*;
+        let mechanism = single_vertex(&[(0.0, Valley), (120.0, Mountain), (240.0, Mountain)]);
+        match mechanism.solve(0, 1.0) {
+            Err(KinematicsError::NoRigidMotion { violations, .. }) => {
+                println!("{}", mechanism.solve(0, 1.0).unwrap_err());
+                assert_eq!(violations.len(), 1);
+                assert!(violations[0].residual > 0.001);
+            }
+            result => panic!("Expected a closure violation, got {:?}", result),
+        }
+
+        assert!(matches!(
+            mechanism.solve(5, 1.0),
+            Err(KinematicsError::InvalidDriver(5))
+        ));
+    }
+}
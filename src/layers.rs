@@ -0,0 +1,1225 @@
+use crate::axioms;
+use crate::folding::{FoldedState, Panel};
+use crate::geometry::{self, Segment};
+use crate::inverse;
+use crate::kinematics::{Assignment, CreaseSegment};
+use crate::multivector::Multivector;
+use crate::pattern::{self, CreasePattern};
+use crate::pga3d::Multivector3;
+
+/// Which layers of the (already folded) paper a new fold affects.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FoldMode {
+    /// Every layer under the crease is folded together, as if the crease was pressed through
+    /// the whole stack.
+    AllLayers,
+
+    /// Only the top flap is folded: the topmost facet that the crease crosses, along with the
+    /// facets on the moving side that are attached to it.
+    TopFlap,
+}
+
+/// Where a reverse fold puts the part of the flap that is folded.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReverseFold {
+    /// Tucked in between the layers of the flap.
+    Inside,
+
+    /// Wrapped around the outside of the flap.
+    Outside,
+}
+
+/// A flat region of the sheet that moves as a single piece once the paper has been folded.
+#[derive(Clone, Debug)]
+pub struct LayerFacet {
+    /// The vertices of the facet, in (flat) sheet coordinates.
+    pub sheet: Vec<Multivector>,
+
+    /// The composition of the reflections that carry the facet from the sheet to its folded
+    /// position: a point `p` on the sheet lands at `V * p * ~V`.
+    pub versor: Multivector,
+
+    /// Whether the back of the sheet faces upwards (i.e. the facet has been reflected an odd
+    /// number of times).
+    pub flipped: bool,
+}
+
+impl LayerFacet {
+    /// Carries a point (or line) from sheet coordinates to folded coordinates.
+    pub fn to_folded(&self, m: &Multivector) -> Multivector {
+        self.versor * (*m) * self.versor.reversion()
+    }
+
+    /// Carries a point (or line) from folded coordinates back to sheet coordinates.
+    pub fn to_sheet(&self, m: &Multivector) -> Multivector {
+        self.versor.reversion() * (*m) * self.versor
+    }
+
+    /// Returns the vertices of the facet, in folded coordinates.
+    pub fn folded(&self) -> Vec<Multivector> {
+        self.sheet
+            .iter()
+            .map(|p| {
+                let p = self.to_folded(p);
+                p / p.e12()
+            })
+            .collect()
+    }
+
+    /// Returns the rigid motion that carries the facet from the sheet (in the plane `z = 0`) to
+    /// its folded position, which also lies in the plane `z = 0`. Facets whose back faces upwards
+    /// are turned over (about the x-axis) first, so that their front faces `-z`.
+    pub fn motor(&self) -> Multivector3 {
+        let origin = self.to_folded(&Multivector::point(0.0, 0.0));
+        let origin = origin / origin.e12();
+        let unit = self.to_folded(&Multivector::point(1.0, 0.0));
+        let unit = unit / unit.e12();
+        let angle = (unit.e01() - origin.e01()).atan2(unit.e20() - origin.e20());
+
+        // A rotor about the downward vertical axis turns counter-clockwise (seen from above)
+        let vertical = Multivector3::point(0.0, 0.0, 1.0).join(&Multivector3::point(0.0, 0.0, 0.0));
+        let mut motor = Multivector3::translator(origin.e20(), origin.e01(), 0.0)
+            * Multivector3::rotor(angle, &vertical);
+        if self.flipped {
+            motor = motor
+                * Multivector3::rotor(
+                    std::f32::consts::PI,
+                    &Multivector3::line_in_plane(0.0, 1.0, 0.0),
+                );
+        }
+        motor
+    }
+
+    /// Carries a polygon from folded coordinates back to sheet coordinates.
+    fn to_sheet_polygon(&self, polygon: &[Multivector]) -> Vec<Multivector> {
+        polygon
+            .iter()
+            .map(|p| {
+                let p = self.to_sheet(p);
+                p / p.e12()
+            })
+            .collect()
+    }
+
+    /// Splits the facet along `line` (in folded coordinates), returning the pieces on the
+    /// positive and negative sides of the line (see `split_polygon`), in sheet coordinates.
+    fn split(&self, line: &Multivector) -> (Vec<Multivector>, Vec<Multivector>) {
+        let (positive, negative) = split_polygon(&self.folded(), line);
+        (
+            self.to_sheet_polygon(&positive),
+            self.to_sheet_polygon(&negative),
+        )
+    }
+
+    /// Returns the part of this facet with the specified vertices (in sheet coordinates), moved
+    /// from where it lies now by each of the `reflections` (lines in folded coordinates), in
+    /// order.
+    fn piece(&self, sheet: &[Multivector], reflections: &[Multivector]) -> LayerFacet {
+        LayerFacet {
+            sheet: sheet.to_vec(),
+            versor: reflections.iter().fold(self.versor, |versor, reflection| {
+                reflection.normalized() * versor
+            }),
+            flipped: self.flipped != (reflections.len() % 2 == 1),
+        }
+    }
+}
+
+/// A flat-folded sheet of paper, as a stack of facets.
+///
+/// Every fold is folded flat. Valley folds bring the moving facets to the top of the stack
+/// (towards the viewer), and mountain folds tuck them underneath. The creases are recorded in
+/// sheet coordinates, with their assignments as seen from the front of the sheet.
+///
+/// Compound moves (reverse, squash, and petal folds) crease several layers at once, each along
+/// its own line, and are rejected if they would tear the paper apart.
+#[derive(Clone, Debug)]
+pub struct LayeredState {
+    /// The corners of the unfolded sheet.
+    pub paper: Vec<Multivector>,
+
+    /// The facets, ordered from the bottom of the stack to the top.
+    pub facets: Vec<LayerFacet>,
+
+    /// Every crease that has been folded so far, in sheet coordinates.
+    pub creases: Vec<CreaseSegment>,
+}
+
+impl LayeredState {
+    /// Constructs the state of the unfolded sheet (a convex polygon), front side up.
+    pub fn new(paper: &[Multivector]) -> Self {
+        let paper = paper.iter().map(|p| (*p) / p.e12()).collect::<Vec<_>>();
+        Self {
+            paper: paper.clone(),
+            facets: vec![LayerFacet {
+                sheet: paper,
+                versor: Multivector::zeros() + 1.0,
+                flipped: false,
+            }],
+            creases: vec![],
+        }
+    }
+
+    /// Folds the paper flat along `crease` (in folded coordinates). The side of the crease that
+    /// contains `moving` is folded over (or, if `moving` is `None`, the negative side, as in
+    /// `Paper::intersect`).
+    ///
+    /// Each facet that is folded is split along the crease, and the creases are found again (see
+    /// `unfold`). Since the crease is seen from above, it is recorded with the opposite
+    /// assignment on facets whose back faces upwards.
+    pub fn fold(
+        &mut self,
+        crease: &Multivector,
+        assignment: Assignment,
+        moving: Option<&Multivector>,
+        mode: FoldMode,
+    ) -> Result<(), &'static str> {
+        let crease = crease.normalized();
+
+        // Split every facet into the part that stays and the part that moves (either of which
+        // may be empty), in sheet coordinates
+        let pieces = self.pieces(&crease, moving);
+
+        // Which facets are actually folded?
+        let folded = match mode {
+            FoldMode::AllLayers => pieces.iter().map(|(_, moved)| !moved.is_empty()).collect(),
+            FoldMode::TopFlap => {
+                let top = (0..self.facets.len())
+                    .rev()
+                    .find(|&index| !pieces[index].0.is_empty() && !pieces[index].1.is_empty())
+                    .ok_or("The crease does not cross the paper")?;
+                flap(&pieces, top)
+            }
+        };
+        if !pieces
+            .iter()
+            .zip(folded.iter())
+            .any(|((fixed, moved), &folded)| folded && !fixed.is_empty() && !moved.is_empty())
+        {
+            return Err("The crease does not cross the paper");
+        }
+
+        let mut stationary = vec![];
+        let mut moved = vec![];
+        for (index, facet) in self.facets.iter().enumerate() {
+            if !folded[index] {
+                stationary.push(facet.clone());
+                continue;
+            }
+            let (fixed_piece, moved_piece) = &pieces[index];
+            if !fixed_piece.is_empty() {
+                stationary.push(LayerFacet {
+                    sheet: fixed_piece.clone(),
+                    ..facet.clone()
+                });
+            }
+            moved.push(LayerFacet {
+                sheet: moved_piece.clone(),
+                versor: crease * facet.versor,
+                flipped: !facet.flipped,
+            });
+        }
+
+        // Folding flips the stack of moving facets over
+        moved.reverse();
+        self.facets = match assignment {
+            Assignment::Mountain => moved.into_iter().chain(stationary).collect(),
+            _ => stationary.into_iter().chain(moved).collect(),
+        };
+        self.rebuild_creases();
+        Ok(())
+    }
+
+    /// Turns the paper over by reflecting it across `axis` (in folded coordinates). The order of
+    /// the stack is reversed, and every facet shows its other side.
+    pub fn turn_over(&mut self, axis: &Multivector) {
+        let axis = axis.normalized();
+        for facet in self.facets.iter_mut() {
+            facet.versor = axis * facet.versor;
+            facet.flipped = !facet.flipped;
+        }
+        self.facets.reverse();
+    }
+
+    /// Reverse folds the top flap along `crease` (in folded coordinates). The side of the crease
+    /// that contains `moving` is folded, as in `fold`.
+    ///
+    /// The layers of the flap are divided into a lower and an upper half, which are folded in
+    /// opposite directions. For an inside reverse fold, the lower half folds up and the upper half
+    /// folds down, so that the folded part ends up between them. For an outside reverse fold, it
+    /// wraps around both of them instead. The flap needs at least two layers (typically, two
+    /// layers joined along its spine).
+    pub fn reverse_fold(
+        &mut self,
+        crease: &Multivector,
+        moving: Option<&Multivector>,
+        kind: ReverseFold,
+    ) -> Result<(), &'static str> {
+        let crease = crease.normalized();
+        let pieces = self.pieces(&crease, moving);
+        let top = (0..self.facets.len())
+            .rev()
+            .find(|&index| !pieces[index].0.is_empty() && !pieces[index].1.is_empty())
+            .ok_or("The crease does not cross the paper")?;
+        let in_flap = flap(&pieces, top);
+        let layers = (0..self.facets.len())
+            .filter(|&index| in_flap[index])
+            .collect::<Vec<_>>();
+        if layers.len() < 2 {
+            return Err("A reverse fold needs a flap with at least two layers");
+        }
+        let (lower, upper) = layers.split_at(layers.len() / 2);
+
+        // Folding flips each half over, so the folded pieces land in reverse order, either just
+        // above the top of their half or just below its bottom
+        let spacing = 0.2 / layers.len() as f32;
+        let mut replacements = vec![];
+        for &(half, up) in [
+            (lower, kind == ReverseFold::Inside),
+            (upper, kind == ReverseFold::Outside),
+        ]
+        .iter()
+        {
+            for (position, &index) in half.iter().enumerate() {
+                let facet = &self.facets[index];
+                let (fixed, moved) = &pieces[index];
+                let key = if up {
+                    half[half.len() - 1] as f32
+                        + 0.25
+                        + (half.len() - 1 - position) as f32 * spacing
+                } else {
+                    half[0] as f32 - 0.25 - position as f32 * spacing
+                };
+                replacements.push((index as f32, facet.piece(fixed, &[])));
+                replacements.push((key, facet.piece(moved, &[crease])));
+            }
+        }
+        self.rearrange(&layers, replacements)
+    }
+
+    /// Squash folds the top flap, which is hinged along `hinge` and made of two layers joined
+    /// along `spine` (both lines in folded coordinates). The flap is lifted, spread open, and
+    /// pressed flat again symmetrically, so that the spine lands on the hinge line.
+    ///
+    /// The top layer is creased along the bisector of the hinge and the spine (axiom 3), and its
+    /// spine side is folded over onto the rest of it. The layer underneath swings across the
+    /// hinge, and its spine side is folded over along the same bisector.
+    pub fn squash_fold(
+        &mut self,
+        hinge: &Multivector,
+        spine: &Multivector,
+    ) -> Result<(), &'static str> {
+        let hinge = hinge.normalized();
+        let spine = spine.normalized();
+
+        // The top layer of the flap is the topmost facet that one of the bisectors cuts through
+        let bisectors = inverse::evaluate(3, &[], &[hinge, spine]);
+        let (top, bisector) = (0..self.facets.len())
+            .rev()
+            .find_map(|index| {
+                let polygon = self.facets[index].folded();
+                bisectors
+                    .iter()
+                    .find(|bisector| geometry::clip_line(bisector, &polygon).is_some())
+                    .map(|bisector| (index, bisector.normalized()))
+            })
+            .ok_or("The hinge and spine do not meet on the paper")?;
+
+        // The layer underneath it, joined to it along the spine
+        let upper = &self.facets[top];
+        let (under, spine_point) = (0..top)
+            .rev()
+            .find_map(|index| {
+                let edge = shared_edge(&upper.sheet, &self.facets[index].sheet)?;
+                let midpoint = upper.to_folded(&edge.midpoint());
+                let midpoint = midpoint / midpoint.e12();
+                if geometry::dist_point_to_line(&midpoint, &spine).abs() < 0.001 {
+                    Some((index, midpoint))
+                } else {
+                    None
+                }
+            })
+            .ok_or("A squash fold needs a flap with two layers joined along the spine")?;
+        let lower = &self.facets[under];
+
+        let (upper_spine, upper_hinge) = divide(&upper.folded(), &bisector, &spine_point);
+        let (lower_spine, lower_hinge) = divide(&lower.folded(), &bisector, &spine_point);
+
+        // Only the part of the lower layer on the flap's side of the hinge swings across it
+        let (lower_hinge, beyond) = divide(&lower_hinge, &hinge, &spine_point);
+        let replacements = vec![
+            (
+                under as f32,
+                lower.piece(&lower.to_sheet_polygon(&beyond), &[]),
+            ),
+            (
+                under as f32,
+                lower.piece(&lower.to_sheet_polygon(&lower_hinge), &[hinge]),
+            ),
+            (
+                under as f32 + 0.25,
+                lower.piece(&lower.to_sheet_polygon(&lower_spine), &[bisector, hinge]),
+            ),
+            (
+                top as f32,
+                upper.piece(&upper.to_sheet_polygon(&upper_hinge), &[]),
+            ),
+            (
+                top as f32 + 0.25,
+                upper.piece(&upper.to_sheet_polygon(&upper_spine), &[bisector]),
+            ),
+        ];
+        self.rearrange(&[under, top], replacements)
+    }
+
+    /// Petal folds the top flap at its corner `tip`, symmetrically about `axis` (a line through
+    /// the tip, in folded coordinates), as in the step from the preliminary base to the bird
+    /// base.
+    ///
+    /// The top layer is creased along the bisectors of the axis and the two edges that meet at
+    /// the tip (axiom 3), and along the hinge that joins the points where those creases leave the
+    /// layer (axiom 1). The tip is lifted across the hinge, while the sides of the layer fold in
+    /// along the bisectors and tuck in underneath it, pulling in the layers they're joined to.
+    pub fn petal_fold(
+        &mut self,
+        tip: &Multivector,
+        axis: &Multivector,
+    ) -> Result<(), &'static str> {
+        let tip = (*tip) / tip.e12();
+        let axis = axis.normalized();
+        let is_tip = |p: &Multivector| geometry::dist_point_to_point(p, &tip) < 0.001;
+        let top = (0..self.facets.len())
+            .rev()
+            .find(|&index| self.facets[index].folded().iter().any(is_tip))
+            .ok_or("The tip is not a corner of the paper")?;
+        let facet = &self.facets[top];
+        let polygon = facet.folded();
+        let corner = polygon.iter().position(is_tip).unwrap();
+        let neighbours = [
+            polygon[(corner + 1) % polygon.len()],
+            polygon[(corner + polygon.len() - 1) % polygon.len()],
+        ];
+
+        // The bisector of the axis and the edge to each neighbour (the one that runs through the
+        // layer, rather than outside of it), and the point where it leaves the layer
+        let mut kites = vec![];
+        for neighbour in neighbours.iter() {
+            let edge = tip.join(neighbour);
+            let (kite, chord) = inverse::evaluate(3, &[], &[edge, axis])
+                .into_iter()
+                .find_map(|bisector| {
+                    let chord = geometry::clip_line(&bisector, &polygon)?;
+                    Some((bisector.normalized(), chord))
+                })
+                .ok_or("The axis must run through the flap")?;
+            let end = if is_tip(&chord.start) {
+                chord.end
+            } else {
+                chord.start
+            };
+            kites.push((kite, end, *neighbour));
+        }
+        let hinge = axioms::axiom_1(&kites[0].1, &kites[1].1);
+
+        // The sides of the top layer fold in along the kite creases and then up across the hinge
+        // with the rest of the tip
+        let mut replacements = vec![];
+        let mut sides = vec![];
+        let mut rest = polygon.clone();
+        for (kite, _, neighbour) in kites.iter() {
+            let (side, remainder) = divide(&rest, kite, neighbour);
+            let side = facet.to_sheet_polygon(&side);
+            replacements.push((top as f32 + 0.2, facet.piece(&side, &[*kite, hinge])));
+            sides.push((side, *kite, *neighbour));
+            rest = remainder;
+        }
+        let (centre, stays) = divide(&rest, &hinge, &tip);
+        replacements.push((
+            top as f32 + 0.3,
+            facet.piece(&facet.to_sheet_polygon(&centre), &[hinge]),
+        ));
+        replacements.push((
+            top as f32,
+            facet.piece(&facet.to_sheet_polygon(&stays), &[]),
+        ));
+
+        // Any layer that is joined to one of the sides is folded in along the same kite crease
+        let mut replaced = vec![top];
+        for (index, other) in self.facets.iter().enumerate() {
+            if index == top {
+                continue;
+            }
+            if let Some((_, kite, neighbour)) = sides
+                .iter()
+                .find(|(side, _, _)| shared_edge(&other.sheet, side).is_some())
+            {
+                let (tucked, kept) = divide(&other.folded(), kite, neighbour);
+                replacements.push((
+                    index as f32,
+                    other.piece(&other.to_sheet_polygon(&kept), &[]),
+                ));
+                replacements.push((
+                    index as f32 + 0.25,
+                    other.piece(&other.to_sheet_polygon(&tucked), &[*kite]),
+                ));
+                replaced.push(index);
+            }
+        }
+        self.rearrange(&replaced, replacements)
+    }
+
+    /// Returns `true` if every pair of facets that are joined on the sheet are still joined once
+    /// folded, i.e. the paper hasn't been torn apart.
+    pub fn is_consistent(&self) -> bool {
+        self.facets.iter().enumerate().all(|(index, a)| {
+            self.facets[index + 1..]
+                .iter()
+                .all(|b| shared_edge(&a.sheet, &b.sheet).map_or(true, |edge| joined(a, b, &edge)))
+        })
+    }
+
+    /// Splits every facet along `crease` (in folded coordinates) into the part that stays and the
+    /// part that moves (see `fold`), in sheet coordinates.
+    fn pieces(
+        &self,
+        crease: &Multivector,
+        moving: Option<&Multivector>,
+    ) -> Vec<(Vec<Multivector>, Vec<Multivector>)> {
+        let moving_sign = match moving {
+            Some(p) if geometry::dist_point_to_line(p, crease) > 0.0 => 1.0,
+            _ => -1.0,
+        };
+        self.facets
+            .iter()
+            .map(|facet| {
+                let (positive, negative) = facet.split(crease);
+                if moving_sign > 0.0 {
+                    (negative, positive)
+                } else {
+                    (positive, negative)
+                }
+            })
+            .collect()
+    }
+
+    /// Replaces the facets at the indices in `replaced` with new pieces, each of which is given a
+    /// key that places it in the stack: the facets that are left alone are keyed by their current
+    /// index, and ties keep their order. Afterwards, facets that are joined without a fold between
+    /// them are merged, and the creases are found again. If the result tears the paper, the state
+    /// is left unchanged.
+    fn rearrange(
+        &mut self,
+        replaced: &[usize],
+        replacements: Vec<(f32, LayerFacet)>,
+    ) -> Result<(), &'static str> {
+        let mut keyed = self
+            .facets
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !replaced.contains(index))
+            .map(|(index, facet)| (index as f32, facet.clone()))
+            .chain(
+                replacements
+                    .into_iter()
+                    .filter(|(_, facet)| facet.sheet.len() >= 3),
+            )
+            .collect::<Vec<_>>();
+        keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let previous = std::mem::replace(
+            &mut self.facets,
+            keyed.into_iter().map(|(_, facet)| facet).collect(),
+        );
+        if !self.is_consistent() {
+            self.facets = previous;
+            return Err("The paper can not be folded flat this way");
+        }
+        self.merge_flat();
+        self.rebuild_creases();
+        Ok(())
+    }
+
+    /// Merges pairs of facets that share a whole edge without a fold between them (i.e. they
+    /// show the same side and are carried by the same isometry), as long as their union is
+    /// convex. The merged facet takes the higher of the two places in the stack.
+    fn merge_flat(&mut self) {
+        'search: loop {
+            for a in 0..self.facets.len() {
+                for b in a + 1..self.facets.len() {
+                    if self.facets[a].flipped != self.facets[b].flipped {
+                        continue;
+                    }
+                    let (first, second) = (&self.facets[a], &self.facets[b]);
+                    let merged = match shared_edge(&first.sheet, &second.sheet) {
+                        Some(edge) if joined(first, second, &edge) => {
+                            union(&first.sheet, &second.sheet)
+                        }
+                        _ => None,
+                    };
+                    if let Some(merged) = merged {
+                        self.facets[b].sheet = merged;
+                        self.facets.remove(a);
+                        continue 'search;
+                    }
+                }
+            }
+            break;
+        }
+    }
+
+    /// Returns where the point `p` (in sheet coordinates) lies on the folded paper, or `None` if
+    /// it isn't on the sheet. Points on a crease belong to more than one facet, but every one of
+    /// them carries the point to the same place.
+    pub fn folded_point(&self, p: &Multivector) -> Option<Multivector> {
+        let facet = self
+            .facets
+            .iter()
+            .find(|facet| geometry::polygon_contains(&facet.sheet, p))?;
+        let folded = facet.to_folded(p);
+        Some(folded / folded.e12())
+    }
+
+    /// Returns the line that the edge of the sheet along `l` (in sheet coordinates) lies on once
+    /// folded, or `None` if its pieces no longer line up (for example, after a fold that isn't
+    /// parallel or perpendicular to it) or it isn't an edge of the sheet.
+    pub fn folded_edge(&self, l: &Multivector) -> Option<Multivector> {
+        // The pieces of the edge are the sides of facets with both vertices on the line
+        let on_edge = |p: &Multivector| geometry::dist_point_to_line(p, l).abs() < 0.001;
+        let pieces = self
+            .facets
+            .iter()
+            .filter(|facet| facet.sheet.iter().filter(|p| on_edge(p)).count() >= 2)
+            .collect::<Vec<_>>();
+
+        let folded = pieces.first()?.to_folded(l);
+        let lines_up = pieces.iter().all(|facet| {
+            facet.sheet.iter().filter(|p| on_edge(p)).all(|p| {
+                let p = facet.to_folded(p);
+                geometry::dist_point_to_line(&(p / p.e12()), &folded).abs() < 0.001
+            })
+        });
+        if lines_up {
+            Some(folded.normalized())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the folded paper as rigid panels in 3D (see `folding::FoldedState`), one for each
+    /// facet, all lying flat in the plane `z = 0`.
+    pub fn lift(&self) -> FoldedState {
+        FoldedState {
+            panels: self
+                .facets
+                .iter()
+                .map(|facet| Panel {
+                    vertices: facet.sheet.clone(),
+                    motor: facet.motor(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Unfolds the paper back to the flat sheet, returning its crease pattern.
+    ///
+    /// Each fold is found where two facets that are joined on the sheet lie on the same side of
+    /// the edge between them once folded: the edge that they share is already in sheet
+    /// coordinates. Seen from the front of the sheet, the crease is a valley if the lower of the
+    /// two facets faces up, and a mountain otherwise. Creases that were folded and later opened
+    /// flat again (for example, by a squash fold) are left out.
+    ///
+    /// These are the same creases as `creases`, which are found again after every move.
+    pub fn unfold(&self) -> CreasePattern {
+        CreasePattern::new(&self.paper, &self.creases)
+    }
+
+    /// Finds the creases from scratch (see `unfold`).
+    fn rebuild_creases(&mut self) {
+        self.creases = self.folded_creases();
+    }
+
+    /// Returns the folded creases, in sheet coordinates, joining segments that continue one
+    /// another.
+    fn folded_creases(&self) -> Vec<CreaseSegment> {
+        let mut creases: Vec<CreaseSegment> = vec![];
+        for (index, lower) in self.facets.iter().enumerate() {
+            for upper in self.facets[index + 1..].iter() {
+                let edge = match shared_edge(&lower.sheet, &upper.sheet) {
+                    Some(edge) => edge,
+                    None => continue,
+                };
+
+                // Where the two facets meet in the folded model
+                let (start, end) = (lower.to_folded(&edge.start), lower.to_folded(&edge.end));
+                let folded = Segment::new(&start, &end);
+                let side = |facet: &LayerFacet| {
+                    geometry::dist_point_to_line(&centroid(&facet.folded()), &folded.line())
+                };
+                if side(lower) * side(upper) < 0.0 {
+                    continue;
+                }
+                let assignment = if lower.flipped {
+                    Assignment::Mountain
+                } else {
+                    Assignment::Valley
+                };
+                creases.push(CreaseSegment::new(&edge.start, &edge.end, assignment));
+            }
+        }
+        pattern::merge_collinear(creases, true)
+    }
+}
+
+/// Returns which facets belong to the flap that contains the moving piece of the facet at
+/// `top`: the moving pieces that are connected to it through shared edges (i.e. existing
+/// creases).
+fn flap(pieces: &[(Vec<Multivector>, Vec<Multivector>)], top: usize) -> Vec<bool> {
+    let mut in_flap = vec![false; pieces.len()];
+    let mut stack = vec![top];
+    in_flap[top] = true;
+
+    while let Some(current) = stack.pop() {
+        for next in 0..pieces.len() {
+            if !in_flap[next]
+                && !pieces[next].1.is_empty()
+                && shares_edge(&pieces[current].1, &pieces[next].1)
+            {
+                in_flap[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    in_flap
+}
+
+/// Returns `true` if the two polygons share (part of) an edge.
+fn shares_edge(a: &[Multivector], b: &[Multivector]) -> bool {
+    shared_edge(a, b).is_some()
+}
+
+/// Returns the part of an edge that the two polygons share, if any.
+fn shared_edge(a: &[Multivector], b: &[Multivector]) -> Option<Segment> {
+    let edges = |polygon: &[Multivector]| {
+        (0..polygon.len())
+            .map(|index| Segment::new(&polygon[index], &polygon[(index + 1) % polygon.len()]))
+            .collect::<Vec<_>>()
+    };
+
+    edges(a).iter().find_map(|edge| {
+        edges(b).iter().find_map(|other| {
+            let line = edge.line();
+            let collinear = geometry::dist_point_to_line(&other.start, &line).abs() < 0.001
+                && geometry::dist_point_to_line(&other.end, &line).abs() < 0.001;
+            if !collinear {
+                return None;
+            }
+            let (s, t) = (edge.parameter(&other.start), edge.parameter(&other.end));
+            let (t0, t1) = (s.min(t).max(0.0), s.max(t).min(1.0));
+            if (t1 - t0) * edge.length() > 0.001 {
+                Some(Segment::new(&edge.point_at(t0), &edge.point_at(t1)))
+            } else {
+                None
+            }
+        })
+    })
+}
+
+/// Returns `true` if the facets `a` and `b`, which share `edge` on the sheet, are still joined
+/// along it once folded.
+fn joined(a: &LayerFacet, b: &LayerFacet, edge: &Segment) -> bool {
+    [edge.start, edge.end].iter().all(|p| {
+        let (p_a, p_b) = (a.to_folded(p), b.to_folded(p));
+        geometry::dist_point_to_point(&(p_a / p_a.e12()), &(p_b / p_b.e12())) < 0.001
+    })
+}
+
+/// Splits the convex polygon (in folded coordinates) along `line`, returning the pieces on the
+/// positive and negative sides of the line (see `geometry::split_polygon`). A piece that is
+/// (almost) too thin to have any area is only there because of round-off error, so the whole
+/// polygon is put on the other side instead.
+fn split_polygon(
+    polygon: &[Multivector],
+    line: &Multivector,
+) -> (Vec<Multivector>, Vec<Multivector>) {
+    let (positive, negative) = geometry::split_polygon(polygon, line);
+    let area = geometry::polygon_area(polygon).abs();
+    let sliver = |piece: &[Multivector]| {
+        geometry::polygon_area(piece).abs() <= geometry::SPLIT_TOLERANCE * area
+    };
+    if sliver(&positive) && !negative.is_empty() {
+        (vec![], polygon.to_vec())
+    } else if sliver(&negative) && !positive.is_empty() {
+        (polygon.to_vec(), vec![])
+    } else {
+        (positive, negative)
+    }
+}
+
+/// Splits the convex polygon along `line`, returning the piece on the same side as `towards`
+/// followed by the piece on the other side (either of which may be empty).
+fn divide(
+    polygon: &[Multivector],
+    line: &Multivector,
+    towards: &Multivector,
+) -> (Vec<Multivector>, Vec<Multivector>) {
+    let (positive, negative) = split_polygon(polygon, line);
+    if geometry::dist_point_to_line(towards, line) > 0.0 {
+        (positive, negative)
+    } else {
+        (negative, positive)
+    }
+}
+
+/// Returns the average of the vertices of a polygon.
+fn centroid(polygon: &[Multivector]) -> Multivector {
+    let sum = polygon
+        .iter()
+        .fold(Multivector::zeros(), |sum, p| sum + (*p) / p.e12());
+    sum / polygon.len() as f32
+}
+
+/// Returns the union of two convex polygons with the same winding order that share a whole edge,
+/// if it is also convex.
+fn union(a: &[Multivector], b: &[Multivector]) -> Option<Vec<Multivector>> {
+    let same = |p: &Multivector, q: &Multivector| geometry::dist_point_to_point(p, q) < 0.001;
+    let (i, j) = (0..a.len()).find_map(|i| {
+        let (start, end) = (&a[i], &a[(i + 1) % a.len()]);
+        (0..b.len())
+            .find(|&j| same(&b[j], end) && same(&b[(j + 1) % b.len()], start))
+            .map(|j| (i, j))
+    })?;
+
+    // Walk around `a` from the end of the shared edge back to its start, then around the rest of
+    // `b`, dropping any vertices where the boundary runs straight on
+    let walk = (1..=a.len())
+        .map(|k| a[(i + k) % a.len()])
+        .chain((2..b.len()).map(|k| b[(j + k) % b.len()]))
+        .collect::<Vec<_>>();
+    let turn = |k: usize| {
+        let (p, q, r) = (
+            walk[(k + walk.len() - 1) % walk.len()],
+            walk[k],
+            walk[(k + 1) % walk.len()],
+        );
+        (q.e20() - p.e20()) * (r.e01() - q.e01()) - (q.e01() - p.e01()) * (r.e20() - q.e20())
+    };
+    let turns = (0..walk.len()).map(turn).collect::<Vec<_>>();
+    let polygon = walk
+        .iter()
+        .zip(turns.iter())
+        .filter(|(_, turn)| turn.abs() > 0.000001)
+        .map(|(p, _)| *p)
+        .collect::<Vec<_>>();
+
+    let convex =
+        turns.iter().all(|&turn| turn > -0.000001) || turns.iter().all(|&turn| turn < 0.000001);
+    if convex {
+        Some(polygon)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::unit_square;
+
+    /// Folds the unit square in half, bringing the right half over the left half.
+    fn book_fold() -> LayeredState {
+        let mut state = LayeredState::new(&unit_square());
+        state
+            .fold(
+                &Multivector::line(1.0, 0.0, -0.5),
+                Assignment::Valley,
+                Some(&Multivector::point(1.0, 0.5)),
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+        state
+    }
+
+    /// Folds the square [-1, 1] x [-1, 1] in half along both diagonals, leaving a triangle with
+    /// a two-layer flap on top, hinged along y = x and with its spine along x + y = 0.
+    fn triangle_fold() -> LayeredState {
+        let mut state = LayeredState::new(&[
+            Multivector::point(-1.0, -1.0),
+            Multivector::point(1.0, -1.0),
+            Multivector::point(1.0, 1.0),
+            Multivector::point(-1.0, 1.0),
+        ]);
+        state
+            .fold(
+                &Multivector::line(1.0, 1.0, 0.0),
+                Assignment::Valley,
+                Some(&Multivector::point(1.0, 1.0)),
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+        state
+            .fold(
+                &Multivector::line(1.0, -1.0, 0.0),
+                Assignment::Valley,
+                Some(&Multivector::point(1.0, -1.0)),
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+        state
+    }
+
+    /// Squash folds the flaps on both sides of the triangle, leaving the preliminary base: a
+    /// square with the center of the paper at <0, 0> and its corners at <-1, -1>.
+    fn preliminary_base() -> LayeredState {
+        let hinge = Multivector::line(1.0, -1.0, 0.0);
+        let spine = Multivector::line(1.0, 1.0, 0.0);
+        let mut state = triangle_fold();
+        state.squash_fold(&hinge, &spine).unwrap();
+        state.turn_over(&hinge);
+        state.squash_fold(&hinge, &spine).unwrap();
+        state
+    }
+
+    #[test]
+    fn test_fold_all_layers() {
+        let mut state = book_fold();
+        assert_eq!(state.facets.len(), 2);
+        assert!(state.facets[1].flipped);
+
+        // Should be: both layers now occupy the left half of the square
+        for facet in state.facets.iter() {
+            for p in facet.folded().iter() {
+                assert!(p.e20() < 0.5 + 0.001);
+            }
+        }
+
+        // Fold both layers in half again, bringing the top half down
+        state
+            .fold(
+                &Multivector::line(0.0, 1.0, -0.5),
+                Assignment::Valley,
+                Some(&Multivector::point(0.25, 1.0)),
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+        assert_eq!(state.facets.len(), 4);
+        assert_eq!(state.creases.len(), 3);
+
+        // Should be: the second crease is split into two segments on the sheet, with opposite
+        // assignments (one is seen from the back of the sheet)
+        let second = &state.creases[1..];
+        for crease in second.iter() {
+            println!(
+                "{} -> {}: {:?}",
+                crease.segment.start, crease.segment.end, crease.assignment
+            );
+            assert!((crease.segment.start.e01() - 0.5).abs() < 0.001);
+            assert!((crease.segment.length() - 0.5).abs() < 0.001);
+        }
+        assert_ne!(second[0].assignment, second[1].assignment);
+    }
+
+    #[test]
+    fn test_fold_rotated_sheet() {
+        // The unit square, rotated by 30 degrees: its folded vertices are only approximately
+        // where they should be
+        let R = Multivector::rotor(30.0f32.to_radians(), 0.0, 0.0);
+        let paper = unit_square()
+            .iter()
+            .map(|p| R * (*p) * R.conjugation())
+            .collect::<Vec<_>>();
+        let diagonals = [paper[0].join(&paper[2]), paper[1].join(&paper[3])];
+
+        let mut state = LayeredState::new(&paper);
+        for diagonal in diagonals.iter() {
+            state
+                .fold(diagonal, Assignment::Valley, None, FoldMode::AllLayers)
+                .unwrap();
+        }
+
+        // Should be: four triangles, stacked on top of each other, without any slivers
+        assert_eq!(state.facets.len(), 4);
+        for facet in state.facets.iter() {
+            let folded = facet.folded();
+            println!("{:?}", folded);
+            assert_eq!(folded.len(), 3);
+            assert!(geometry::polygon_area(&folded).abs() > 0.2);
+        }
+
+        // Should be: an error (the first diagonal is already an edge of every layer)
+        assert_eq!(
+            state.fold(&diagonals[0], Assignment::Valley, None, FoldMode::AllLayers),
+            Err("The crease does not cross the paper")
+        );
+        assert_eq!(state.facets.len(), 4);
+    }
+
+    #[test]
+    fn test_fold_top_flap() {
+        // Fold the corner of the top layer only: the bottom layer is untouched
+        let mut state = book_fold();
+        state
+            .fold(
+                &Multivector::line(1.0, 1.0, -0.25),
+                Assignment::Valley,
+                Some(&Multivector::point(0.0, 0.0)),
+                FoldMode::TopFlap,
+            )
+            .unwrap();
+        assert_eq!(state.facets.len(), 3);
+        assert_eq!(state.creases.len(), 2);
+
+        // Should be: the new crease lies in the lower-right corner of the sheet (the top layer
+        // is the right half, reflected)
+        let crease = state.creases[1].segment;
+        assert!(crease.start.e20() > 0.5 - 0.001 && crease.end.e20() > 0.5 - 0.001);
+
+        // Should be: an error (the crease misses the paper)
+        assert!(state
+            .fold(
+                &Multivector::line(1.0, 0.0, -2.0),
+                Assignment::Valley,
+                None,
+                FoldMode::TopFlap,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_reverse_fold() {
+        // Reverse fold the upper-right corner of the folded strip (the spine is along x = 0.5)
+        let crease = Multivector::line(1.0, 1.0, -1.25);
+        let corner = Multivector::point(0.5, 1.0);
+        let mut state = book_fold();
+        state
+            .reverse_fold(&crease, Some(&corner), ReverseFold::Inside)
+            .unwrap();
+        assert_eq!(state.facets.len(), 4);
+        assert!(state.is_consistent());
+
+        // Should be: the spine reverses beyond the crease, and the crease itself is a valley on
+        // both layers
+        for crease in state.creases.iter() {
+            println!(
+                "{} -> {}: {:?}",
+                crease.segment.start, crease.segment.end, crease.assignment
+            );
+        }
+        assert_eq!(state.creases.len(), 4);
+        let spine = |above: bool| {
+            state
+                .creases
+                .iter()
+                .find(|crease| {
+                    let midpoint = crease.segment.midpoint();
+                    (midpoint.e20() - 0.5).abs() < 0.001 && (midpoint.e01() > 0.75) == above
+                })
+                .unwrap()
+                .assignment
+        };
+        assert_eq!(spine(false), Assignment::Valley);
+        assert_eq!(spine(true), Assignment::Mountain);
+
+        // Should be: the folded corner is tucked in between the two layers
+        assert!(!state.facets[1].folded().is_empty() && !state.facets[2].folded().is_empty());
+        for facet in state.facets[1..3].iter() {
+            assert!(facet
+                .folded()
+                .iter()
+                .all(|p| p.e20() + p.e01() < 1.25 + 0.001));
+        }
+
+        // Should be: for an outside reverse fold, the corner wraps around both layers instead
+        let mut outside = book_fold();
+        outside
+            .reverse_fold(&crease, Some(&corner), ReverseFold::Outside)
+            .unwrap();
+        assert!(outside.is_consistent());
+        for &index in [0, 3].iter() {
+            let facet = &outside.facets[index];
+            assert!(facet
+                .folded()
+                .iter()
+                .all(|p| p.e20() + p.e01() < 1.25 + 0.001));
+        }
+
+        // Should be: an error (a single layer can't be reverse folded)
+        assert!(LayeredState::new(&unit_square())
+            .reverse_fold(&crease, Some(&corner), ReverseFold::Inside)
+            .is_err());
+    }
+
+    #[test]
+    fn test_squash_fold() {
+        let mut state = triangle_fold();
+        state
+            .squash_fold(
+                &Multivector::line(1.0, -1.0, 0.0),
+                &Multivector::line(1.0, 1.0, 0.0),
+            )
+            .unwrap();
+        assert!(state.is_consistent());
+
+        // Should be: the top of the squashed flap is the square [-1, 0] x [-1, 0]
+        let top = state.facets.last().unwrap().folded();
+        println!("Top facet: {:?}", top);
+        assert_eq!(top.len(), 4);
+        assert!(top
+            .iter()
+            .all(|p| p.e20() < 0.001 && p.e01() < 0.001 && p.e20() > -1.001 && p.e01() > -1.001));
+
+        // Should be: the preliminary base, which is the same square on both sides, with a valley
+        // along one diagonal and mountains along both of the midlines (the front and back
+        // quarters of the sheet lie flat)
+        let state = preliminary_base();
+        assert!(state.is_consistent());
+        for facet in state.facets.iter() {
+            assert!(facet
+                .folded()
+                .iter()
+                .all(|p| p.e20() < 0.001 && p.e01() < 0.001));
+        }
+        assert_eq!(state.creases.len(), 3);
+        let valleys = state
+            .creases
+            .iter()
+            .filter(|crease| crease.assignment == Assignment::Valley)
+            .count();
+        assert_eq!(valleys, 1);
+
+        // Should be: an error (there is no flap)
+        assert!(LayeredState::new(&unit_square())
+            .squash_fold(
+                &Multivector::line(1.0, -1.0, 0.0),
+                &Multivector::line(1.0, 1.0, -1.0),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_petal_fold() {
+        // Petal fold the front of the preliminary base, lifting its open corner up over the center
+        let mut state = preliminary_base();
+        state
+            .petal_fold(
+                &Multivector::point(-1.0, -1.0),
+                &Multivector::line(1.0, -1.0, 0.0),
+            )
+            .unwrap();
+        assert!(state.is_consistent());
+
+        // Should be: the corner lands at <√2 - 1, √2 - 1>, on the top of the stack (and turned
+        // over, so that the back of the sheet shows)
+        let tip = Multivector::point(2.0f32.sqrt() - 1.0, 2.0f32.sqrt() - 1.0);
+        let top = state.facets.last().unwrap().folded();
+        assert!(top
+            .iter()
+            .any(|p| geometry::dist_point_to_point(p, &tip) < 0.001));
+        assert!(state.facets.last().unwrap().flipped);
+
+        // Should be: an error (the tip isn't a corner of any layer)
+        assert!(state
+            .petal_fold(
+                &Multivector::point(-0.5, -0.9),
+                &Multivector::line(1.0, -1.0, 0.0),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_lift() {
+        // Every vertex of every panel should land where its facet puts it, with the panels that
+        // were turned over facing down
+        let state = preliminary_base();
+        let lifted = state.lift();
+        assert_eq!(lifted.panels.len(), state.facets.len());
+        for (panel, facet) in lifted.panels.iter().zip(state.facets.iter()) {
+            for (position, folded) in panel.positions().iter().zip(facet.folded().iter()) {
+                assert!((position[0] - folded.e20()).abs() < 0.001);
+                assert!((position[1] - folded.e01()).abs() < 0.001);
+                assert!(position[2].abs() < 0.001);
+            }
+
+            let up = Multivector3::point(0.0, 0.0, 1.0)
+                .transformed(&panel.motor)
+                .coordinates();
+            let origin = Multivector3::point(0.0, 0.0, 0.0)
+                .transformed(&panel.motor)
+                .coordinates();
+            assert_eq!(up[2] < origin[2], facet.flipped);
+        }
+    }
+
+    #[test]
+    fn test_unfold() {
+        // Fold the unit square into quarters
+        let mut state = book_fold();
+        state
+            .fold(
+                &Multivector::line(0.0, 1.0, -0.5),
+                Assignment::Valley,
+                Some(&Multivector::point(0.25, 1.0)),
+                FoldMode::AllLayers,
+            )
+            .unwrap();
+
+        // Should be: the whole center line is a valley, and the crossing fold is a valley on the
+        // left half of the sheet and a mountain on the right half
+        let pattern = state.unfold();
+        for crease in pattern.creases.iter() {
+            println!(
+                "{} -> {}: {:?}",
+                crease.segment.start, crease.segment.end, crease.assignment
+            );
+        }
+        assert_eq!(pattern.creases.len(), 3);
+        let find = |x: f32, y: f32| {
+            pattern
+                .creases
+                .iter()
+                .find(|crease| {
+                    let midpoint = crease.segment.midpoint();
+                    geometry::dist_point_to_point(&midpoint, &Multivector::point(x, y)) < 0.001
+                })
+                .unwrap()
+        };
+        assert_eq!(find(0.5, 0.5).assignment, Assignment::Valley);
+        assert!((find(0.5, 0.5).segment.length() - 1.0).abs() < 0.001);
+        assert_eq!(find(0.25, 0.5).assignment, Assignment::Valley);
+        assert_eq!(find(0.75, 0.5).assignment, Assignment::Mountain);
+
+        // Should be: the vertex at the center of a reverse fold satisfies Maekawa's theorem (the
+        // numbers of mountains and valleys differ by two)
+        let mut state = book_fold();
+        state
+            .reverse_fold(
+                &Multivector::line(1.0, 1.0, -1.25),
+                Some(&Multivector::point(0.5, 1.0)),
+                ReverseFold::Inside,
+            )
+            .unwrap();
+        let vertex = Multivector::point(0.5, 0.75);
+        let pattern = state.unfold();
+        let meeting = pattern
+            .creases
+            .iter()
+            .filter(|crease| {
+                geometry::dist_point_to_point(&crease.segment.start, &vertex) < 0.001
+                    || geometry::dist_point_to_point(&crease.segment.end, &vertex) < 0.001
+            })
+            .map(|crease| crease.assignment.sign())
+            .collect::<Vec<_>>();
+        assert_eq!(meeting.len(), 4);
+        assert_eq!(meeting.iter().sum::<f32>().abs(), 2.0);
+
+        // Should be: more creases once the preliminary base is petal folded
+        let base = preliminary_base().unfold();
+        let mut petal = preliminary_base();
+        petal
+            .petal_fold(
+                &Multivector::point(-1.0, -1.0),
+                &Multivector::line(1.0, -1.0, 0.0),
+            )
+            .unwrap();
+        assert!(petal.unfold().creases.len() > base.creases.len());
+    }
+}
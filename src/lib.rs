@@ -1,9 +1,33 @@
 #![allow(warnings)]
 mod axioms;
+mod backend;
+mod diagram;
+mod dual;
+mod export;
+#[cfg(test)]
+mod fixtures;
+mod folding;
 mod geometry;
+mod history;
 mod interop;
+mod interval;
+mod inverse;
+mod kinematics;
+mod layers;
+mod lill;
 mod multivector;
+mod pattern;
+mod pga3d;
+mod predicates;
+mod rational;
+mod recipes;
+mod scalar;
+mod script;
+mod session;
+mod snapping;
+mod tolerance;
 mod utils;
+mod validation;
 
 use wasm_bindgen::prelude::*;
 
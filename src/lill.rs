@@ -0,0 +1,192 @@
+use crate::axioms;
+use crate::geometry;
+use crate::multivector::Multivector;
+use crate::utils;
+
+/// The points and lines that make up Lill's method for the cubic `ax³ + bx² + cx + d = 0`.
+///
+/// Starting at the origin, the "path" consists of four segments of (signed) length `a`, `b`,
+/// `c`, and `d`, turning 90 degrees counter-clockwise after each segment. A negative coefficient
+/// means that the segment is traversed backwards.
+#[derive(Clone, Debug)]
+pub struct LillConfiguration {
+    /// The vertices of the path, from the origin to the terminus (5 points in total).
+    pub path: Vec<Multivector>,
+
+    /// The line that `start` must be folded onto: parallel to the second segment, and as far
+    /// on the other side of it as `start` is.
+    pub start_guide: Multivector,
+
+    /// The line that `end` must be folded onto: parallel to the third segment, and as far on
+    /// the other side of it as `end` is.
+    pub end_guide: Multivector,
+}
+
+impl LillConfiguration {
+    /// Builds the configuration for the cubic `ax³ + bx² + cx + d = 0`. The leading coefficient
+    /// must be non-zero.
+    pub fn new(a: f32, b: f32, c: f32, d: f32) -> Result<Self, &'static str> {
+        if a.abs() < 0.001 {
+            return Err("The leading coefficient of a cubic must be non-zero");
+        }
+
+        // Walk the path: east, north, west, then south
+        let directions = [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)];
+        let mut path = vec![Multivector::point(0.0, 0.0)];
+        let (mut x, mut y) = (0.0, 0.0);
+        for (coefficient, (dx, dy)) in [a, b, c, d].iter().zip(directions.iter()) {
+            x += coefficient * dx;
+            y += coefficient * dy;
+            path.push(Multivector::point(x, y));
+        }
+
+        // The lines that contain the second and third segments (these are constructed as
+        // perpendiculars, since the segments themselves may have zero length)
+        let first = path[0].join(&path[1]);
+        let second = geometry::orthogonal(&path[1], &first);
+        let third = geometry::orthogonal(&path[2], &second);
+
+        // Reflect the start and end points across the second and third segments, respectively,
+        // then construct the lines through those reflections that are parallel to each segment
+        let start_guide = geometry::project(&second, &geometry::reflect(&path[0], &second));
+        let end_guide = geometry::project(&third, &geometry::reflect(&path[4], &third));
+
+        Ok(Self {
+            path,
+            start_guide,
+            end_guide,
+        })
+    }
+
+    /// The origin of the path.
+    pub fn start(&self) -> Multivector {
+        self.path[0]
+    }
+
+    /// The terminus of the path.
+    pub fn end(&self) -> Multivector {
+        self.path[4]
+    }
+}
+
+/// A single real root of a cubic, as found by folding.
+#[derive(Copy, Clone, Debug)]
+pub struct LillRoot {
+    /// The root, read off from the slope of the crease.
+    pub value: f32,
+
+    /// The crease (found via axiom 6) that corresponds to this root.
+    pub crease: Multivector,
+
+    /// The distance from `value` to the nearest root found analytically.
+    pub error: f32,
+}
+
+/// The result of solving a cubic via Lill's method.
+#[derive(Clone, Debug)]
+pub struct LillSolution {
+    pub configuration: LillConfiguration,
+    pub roots: Vec<LillRoot>,
+
+    /// The real roots, as found by Cardano's method.
+    pub analytic_roots: Vec<f32>,
+}
+
+impl LillSolution {
+    /// Returns `true` if every folded root agrees with an analytic root (within `tolerance`)
+    /// and vice-versa.
+    pub fn is_consistent(&self, tolerance: f32) -> bool {
+        let folded_match = self.roots.iter().all(|root| root.error <= tolerance);
+        let analytic_match = self.analytic_roots.iter().all(|analytic| {
+            self.roots
+                .iter()
+                .any(|root| (root.value - analytic).abs() <= tolerance)
+        });
+
+        folded_match && analytic_match
+    }
+}
+
+/// Solves the cubic `ax³ + bx² + cx + d = 0` by folding.
+///
+/// Lill's method states that a ray launched from the start of the path at angle `θ` that
+/// "bounces" at right angles off of the lines containing the second and third segments will
+/// land on the terminus of the path if and only if `x = -tan(θ)` is a root of the cubic. The
+/// segment of the ray between its two bounces is exactly the crease that simultaneously places
+/// the start onto `start_guide` and the end onto `end_guide` (axiom 6), so every crease gives a
+/// root.
+pub fn solve(a: f32, b: f32, c: f32, d: f32) -> Result<LillSolution, &'static str> {
+    let configuration = LillConfiguration::new(a, b, c, d)?;
+
+    let analytic_roots: Vec<f32> = utils::solve_cubic(a as f64, b as f64, c as f64, d as f64)
+        .into_iter()
+        .map(|root| root as f32)
+        .collect();
+
+    let start = configuration.start();
+    let creases = axioms::axiom_6(
+        &start,
+        &configuration.end(),
+        &configuration.start_guide,
+        &configuration.end_guide,
+    );
+
+    let roots = creases
+        .into_iter()
+        .filter_map(|crease| {
+            // The first "bounce" of the ray is the foot of the perpendicular from the start
+            // of the path to the crease
+            let mut bounce = geometry::project(&start, &crease);
+            bounce /= bounce.e12();
+
+            // The first segment runs along the x-axis and the path starts at the origin, so the
+            // slope of the ray is simply the ratio of the bounce point's coordinates
+            if bounce.e20().abs() < 0.001 {
+                return None;
+            }
+            let value = -bounce.e01() / bounce.e20();
+
+            let error = analytic_roots
+                .iter()
+                .map(|analytic| (value - analytic).abs())
+                .fold(std::f32::INFINITY, f32::min);
+
+            Some(LillRoot {
+                value,
+                crease,
+                error,
+            })
+        })
+        .collect();
+
+    Ok(LillSolution {
+        configuration,
+        roots,
+        analytic_roots,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lill() {
+        // Should be: 1, 2, 3
+        let solution = solve(1.0, -6.0, 11.0, -6.0).unwrap();
+        for root in solution.roots.iter() {
+            println!("Root: {} (error: {})", root.value, root.error);
+        }
+        assert!(solution.is_consistent(0.01));
+
+        // Should be: ∛2 (the only real root)
+        let solution = solve(1.0, 0.0, 0.0, -2.0).unwrap();
+        assert!(solution.is_consistent(0.01));
+
+        // Should be: -2, 0.5, 1
+        let solution = solve(2.0, 1.0, -5.0, 2.0).unwrap();
+        assert!(solution.is_consistent(0.01));
+
+        assert!(solve(0.0, 1.0, 2.0, 3.0).is_err());
+    }
+}
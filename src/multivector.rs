@@ -170,8 +170,8 @@ impl Multivector {
     pub fn translator(delta_x: f32, delta_y: f32) -> Self {
         // Use the formula: 1 + (d / 2) * P_inf - note, however, that this constructs
         // a translator that translates objects in a direction orthogonal to P_inf, which
-        // is why we construct T with the negative y-coordinate below
-        let direction = Self::ideal_point(delta_x, -delta_y);
+        // is why we construct T with the direction rotated by 90 degrees below
+        let direction = Self::ideal_point(delta_y, -delta_x);
         let _amount = direction.ideal_norm();
 
         // This simplifies to the final return statement:
@@ -850,6 +850,21 @@ mod tests {
         println!("R * p * ~R = {}", result);
     }
 
+    #[test]
+    fn test_translator_direction() {
+        // Translating by <2, 2> can't tell the x and y offsets apart, so check an asymmetric
+        // offset too (the ideal point has to be the offset rotated by 90 degrees, not mirrored)
+        let p = Multivector::point(1.0, 2.0);
+        for &(dx, dy) in &[(2.0, -1.0), (-0.5, 3.0), (1.0, 0.0), (0.0, 1.0)] {
+            let T = Multivector::translator(dx, dy);
+            let mut result = T * p * T.conjugation();
+            result /= result.e12();
+            println!("Translated by <{}, {}>: {}", dx, dy, result);
+            assert!((result.e20() - (1.0 + dx)).abs() < 0.001);
+            assert!((result.e01() - (2.0 + dy)).abs() < 0.001);
+        }
+    }
+
     #[test]
     fn test_norm() {
         // Should be ~5 (arbitrary)
@@ -0,0 +1,294 @@
+use crate::geometry::{self, Segment};
+use crate::kinematics::{CreaseSegment, Mechanism};
+use crate::multivector::Multivector;
+
+/// A crease pattern: the crease segments (with their mountain / valley assignments) on a flat,
+/// convex sheet of paper.
+#[derive(Clone, Debug)]
+pub struct CreasePattern {
+    pub paper: Vec<Multivector>,
+    pub creases: Vec<CreaseSegment>,
+}
+
+impl CreasePattern {
+    /// Constructs a new crease pattern on the paper with the specified corners. Duplicate
+    /// creases are removed, and overlapping ones are merged (see `add`).
+    pub fn new(paper: &[Multivector], creases: &[CreaseSegment]) -> Self {
+        let mut pattern = Self {
+            paper: paper.iter().map(|p| (*p) / p.e12()).collect(),
+            creases: vec![],
+        };
+        for crease in creases.iter() {
+            pattern.add(crease);
+        }
+        pattern
+    }
+
+    /// Returns `true` if the segment lies along one of the pattern's creases (regardless of its
+    /// assignment), i.e. it wouldn't add anything new.
+    pub fn contains(&self, crease: &Segment) -> bool {
+        self.creases
+            .iter()
+            .any(|existing| covers(&existing.segment, crease))
+    }
+
+    /// Adds a crease to the pattern, trimmed to the paper. Returns `false` (and leaves the
+    /// pattern unchanged) if the crease misses the paper, or lies along an existing crease.
+    ///
+    /// A crease that overlaps an existing crease with the same assignment along the same line is
+    /// merged with it (see `merge_collinear`), so the indices of other creases may change.
+    pub fn add(&mut self, crease: &CreaseSegment) -> bool {
+        let segment = match self.clip(&crease.segment) {
+            Some(segment) => segment,
+            None => return false,
+        };
+        if self.contains(&segment) {
+            return false;
+        }
+        self.creases.push(CreaseSegment {
+            segment,
+            assignment: crease.assignment,
+        });
+        self.creases = merge_collinear(std::mem::take(&mut self.creases), false);
+        true
+    }
+
+    /// Reflects the selected creases (by index) across `axis`, for example, a symmetry line of
+    /// the design or one of the existing creases, and merges the results into the pattern.
+    /// Reflection within the plane of the paper doesn't change which side of the paper faces
+    /// up, so each reflected crease keeps its assignment. Returns the number of creases that
+    /// were added.
+    pub fn reflect(&mut self, selected: &[usize], axis: &Multivector) -> usize {
+        let reflected = self.transformed(selected, |p| geometry::reflect(p, axis));
+        self.merge(&reflected)
+    }
+
+    /// Reflects the selected creases across the (infinite) line through the crease at
+    /// `mirror` (see `reflect`).
+    pub fn reflect_across_crease(&mut self, selected: &[usize], mirror: usize) -> usize {
+        let axis = self.creases[mirror].segment.line();
+        self.reflect(selected, &axis)
+    }
+
+    /// Repeats the selected creases with `n`-fold rotational symmetry about `center` (i.e.
+    /// rotated by every multiple of `2π / n` radians), and merges the results into the pattern.
+    /// Returns the number of creases that were added.
+    pub fn rotate(&mut self, selected: &[usize], center: &Multivector, n: usize) -> usize {
+        let center = (*center) / center.e12();
+        let mut added = 0;
+        for k in 1..n {
+            let angle = 2.0 * std::f32::consts::PI * k as f32 / n as f32;
+            let rotated = self.transformed(selected, |p| {
+                geometry::rotate(p, angle, center.e20(), center.e01())
+            });
+            added += self.merge(&rotated);
+        }
+        added
+    }
+
+    /// Returns the mechanism that describes the rigid motions of the pattern (see
+    /// `kinematics::Mechanism`).
+    pub fn mechanism(&self) -> Mechanism {
+        Mechanism::new(&self.paper, &self.creases)
+    }
+
+    /// Applies `transform` to the endpoints of each of the selected creases.
+    fn transformed<F>(&self, selected: &[usize], transform: F) -> Vec<CreaseSegment>
+    where
+        F: Fn(&Multivector) -> Multivector,
+    {
+        selected
+            .iter()
+            .filter_map(|&index| self.creases.get(index))
+            .map(|crease| {
+                let start = transform(&crease.segment.start);
+                let end = transform(&crease.segment.end);
+                CreaseSegment::new(&start, &end, crease.assignment)
+            })
+            .collect()
+    }
+
+    /// Adds each of the creases, returning the number that were actually added.
+    fn merge(&mut self, creases: &[CreaseSegment]) -> usize {
+        creases.iter().filter(|crease| self.add(crease)).count()
+    }
+
+    /// Returns the portion of the segment that lies on the paper, if any.
+    fn clip(&self, segment: &Segment) -> Option<Segment> {
+        let chord = geometry::clip_line(&segment.line(), &self.paper)?;
+        let (a, b) = (
+            segment.parameter(&chord.start),
+            segment.parameter(&chord.end),
+        );
+        let t0 = a.min(b).max(0.0);
+        let t1 = a.max(b).min(1.0);
+        if t1 - t0 < 0.001 / segment.length().max(0.001) {
+            return None;
+        }
+        Some(Segment::new(&segment.point_at(t0), &segment.point_at(t1)))
+    }
+}
+
+/// Returns `true` if the segment `inner` lies along the segment `outer` (within tolerance).
+fn covers(outer: &Segment, inner: &Segment) -> bool {
+    let line = outer.line();
+    let margin = 0.001 / outer.length().max(0.001);
+    [inner.start, inner.end].iter().all(|p| {
+        let t = outer.parameter(p);
+        geometry::dist_point_to_line(p, &line).abs() < 0.001 && t > -margin && t < 1.0 + margin
+    })
+}
+
+/// Joins crease segments with the same assignment that lie along the same line and overlap
+/// (or, if `touching` is `true`, that merely continue one another), until none are left.
+pub fn merge_collinear(mut creases: Vec<CreaseSegment>, touching: bool) -> Vec<CreaseSegment> {
+    'search: loop {
+        for a in 0..creases.len() {
+            for b in a + 1..creases.len() {
+                let (first, second) = (creases[a].segment, creases[b].segment);
+                if creases[a].assignment != creases[b].assignment {
+                    continue;
+                }
+                let line = first.line();
+                if geometry::dist_point_to_line(&second.start, &line).abs() > 0.001
+                    || geometry::dist_point_to_line(&second.end, &line).abs() > 0.001
+                {
+                    continue;
+                }
+
+                // Compare the two segments as intervals along the first one
+                let (s, t) = (first.parameter(&second.start), first.parameter(&second.end));
+                let (lower, upper) = (s.min(t), s.max(t));
+                let overlap = (upper.min(1.0) - lower.max(0.0)) * first.length();
+                let joined = if touching {
+                    overlap > -0.001
+                } else {
+                    overlap > 0.001
+                };
+                if joined {
+                    creases[a] = CreaseSegment::new(
+                        &first.point_at(lower.min(0.0)),
+                        &first.point_at(upper.max(1.0)),
+                        creases[a].assignment,
+                    );
+                    creases.remove(b);
+                    continue 'search;
+                }
+            }
+        }
+        return creases;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::unit_square;
+    use crate::kinematics::Assignment;
+
+    #[test]
+    fn test_reflect() {
+        // Half of a diagonal, from the lower-left corner to the center
+        let crease = CreaseSegment::new(
+            &Multivector::point(0.0, 0.0),
+            &Multivector::point(0.5, 0.5),
+            Assignment::Valley,
+        );
+        let mut pattern = CreasePattern::new(&unit_square(), &[crease, crease]);
+        assert_eq!(pattern.creases.len(), 1);
+
+        // Mirror it across the vertical center line
+        assert_eq!(pattern.reflect(&[0], &Multivector::line(1.0, 0.0, -0.5)), 1);
+        let reflected = pattern.creases[1];
+        println!(
+            "Reflected: {} -> {}",
+            reflected.segment.start, reflected.segment.end
+        );
+        assert!(
+            geometry::dist_point_to_point(&reflected.segment.start, &Multivector::point(1.0, 0.0))
+                < 0.001
+        );
+        assert_eq!(reflected.assignment, Assignment::Valley);
+
+        // Should be: nothing new (the reflection of the reflection is the original)
+        assert_eq!(
+            pattern.reflect(&[0, 1], &Multivector::line(1.0, 0.0, -0.5)),
+            0
+        );
+
+        // Reflecting across an existing crease
+        assert_eq!(pattern.reflect_across_crease(&[1], 0), 1);
+        assert_eq!(pattern.creases.len(), 3);
+
+        // Should be: trimmed to the paper, from <0.3, 0.5> to <0, 0.5>, which overlaps the
+        // original crease (so the two are merged into one from <0, 0.5> to <0.9, 0.5>)
+        let horizontal = CreaseSegment::new(
+            &Multivector::point(0.1, 0.5),
+            &Multivector::point(0.9, 0.5),
+            Assignment::Mountain,
+        );
+        pattern.add(&horizontal);
+        assert_eq!(pattern.reflect(&[3], &Multivector::line(1.0, 0.0, -0.2)), 1);
+        assert_eq!(pattern.creases.len(), 4);
+        let merged = pattern.creases[3].segment;
+        assert!((merged.length() - 0.9).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_overlapping_creases() {
+        let crease = |x0: f32, x1: f32, assignment: Assignment| {
+            CreaseSegment::new(
+                &Multivector::point(x0, 0.5 * x0 + 0.25),
+                &Multivector::point(x1, 0.5 * x1 + 0.25),
+                assignment,
+            )
+        };
+        let mut pattern =
+            CreasePattern::new(&unit_square(), &[crease(0.2, 0.6, Assignment::Valley)]);
+
+        // Should be: a part of an existing crease adds nothing (whatever its assignment)
+        assert!(pattern.contains(&crease(0.3, 0.5, Assignment::Valley).segment));
+        assert!(!pattern.add(&crease(0.5, 0.3, Assignment::Mountain)));
+        assert!(!pattern.contains(&crease(0.1, 0.5, Assignment::Valley).segment));
+
+        // Should be: an overlapping crease with the same assignment extends the existing one
+        assert!(pattern.add(&crease(0.4, 0.8, Assignment::Valley)));
+        assert_eq!(pattern.creases.len(), 1);
+        assert!((pattern.creases[0].segment.length() - 0.6 * 1.25f32.sqrt()).abs() < 0.001);
+
+        // ...but one with the opposite assignment, or one that only touches it end to end, is
+        // kept separate
+        assert!(pattern.add(&crease(0.0, 0.3, Assignment::Mountain)));
+        assert!(pattern.add(&crease(0.8, 1.0, Assignment::Valley)));
+        assert_eq!(pattern.creases.len(), 3);
+    }
+
+    #[test]
+    fn test_rotate() {
+        // Four-fold rotational symmetry about the center turns half of one diagonal into both
+        // diagonals
+        let crease = CreaseSegment::new(
+            &Multivector::point(0.0, 0.0),
+            &Multivector::point(0.5, 0.5),
+            Assignment::Mountain,
+        );
+        let mut pattern = CreasePattern::new(&unit_square(), &[crease]);
+        assert_eq!(pattern.rotate(&[0], &Multivector::point(0.5, 0.5), 4), 3);
+        assert_eq!(pattern.creases.len(), 4);
+
+        // Should be: nothing new (two-fold symmetry is a subset of four-fold symmetry)
+        assert_eq!(
+            pattern.rotate(&[0, 1, 2, 3], &Multivector::point(0.5, 0.5), 2),
+            0
+        );
+        for crease in pattern.creases.iter() {
+            let ends = [crease.segment.start, crease.segment.end];
+            assert!(ends
+                .iter()
+                .any(|p| geometry::dist_point_to_point(p, &Multivector::point(0.5, 0.5)) < 0.001));
+        }
+
+        // Should be: one interior vertex, at the center
+        assert_eq!(pattern.mechanism().interior_vertices().len(), 1);
+    }
+}
@@ -0,0 +1,397 @@
+use crate::backend;
+
+use std::fmt::Display;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Neg, Not, Sub};
+
+/// The names of the basis blades of 3D PGA, `R(3,0,1)`. As in 2D, the order (and orientation)
+/// of the blades is chosen so that the dual of a multivector is simply its coefficients in
+/// reverse order.
+pub const BASIS_ELEMENTS: &'static [&'static str] = &[
+    "1", "e0", "e1", "e2", "e3", "e01", "e02", "e03", "e12", "e31", "e23", "e021", "e013", "e032",
+    "e123", "e0123",
+];
+
+/// The number of basis blades in 3D PGA.
+pub const BASIS_COUNT: usize = BASIS_ELEMENTS.len();
+
+/// The basis blades above, encoded as bitmasks over the generators `e0`, `e1`, `e2`, and `e3`,
+/// along with the sign that relates each blade to the "canonical" blade with ascending indices
+/// (see `backend::blade_product`).
+const BLADES: [(usize, f32); BASIS_COUNT] = [
+    (0b0000, 1.0),  // 1
+    (0b0001, 1.0),  // e0
+    (0b0010, 1.0),  // e1
+    (0b0100, 1.0),  // e2
+    (0b1000, 1.0),  // e3
+    (0b0011, 1.0),  // e01
+    (0b0101, 1.0),  // e02
+    (0b1001, 1.0),  // e03
+    (0b0110, 1.0),  // e12
+    (0b1010, -1.0), // e31
+    (0b1100, 1.0),  // e23
+    (0b0111, -1.0), // e021
+    (0b1011, 1.0),  // e013
+    (0b1101, -1.0), // e032
+    (0b1110, 1.0),  // e123
+    (0b1111, 1.0),  // e0123
+];
+
+/// Returns the grade of the blade at `index`.
+fn blade_grade(index: usize) -> u32 {
+    BLADES[index].0.count_ones()
+}
+
+/// A multivector in 3D PGA, `R(3,0,1)`, which is used to lift a (flat) fold state into 3D.
+///
+/// The conventions mirror `Multivector`: planes are vectors (grade-1), lines are bivectors
+/// (grade-2), and points are trivectors (grade-3). The outer product `^` is the meet, and the
+/// join `&` is the regressive product. Rigid motions are represented by motors (even-grade
+/// elements), which act on any element `X` via the sandwich product `M * X * ~M`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Multivector3 {
+    /// The coefficients of this multivector.
+    coeff: [f32; BASIS_COUNT],
+}
+
+impl Multivector3 {
+    /// Constructs a new multivector from the specified coefficients.
+    pub fn with_coefficients(coeff: &[f32; BASIS_COUNT]) -> Self {
+        Self { coeff: *coeff }
+    }
+
+    /// Constructs a new multivector with all coefficients set to zero.
+    pub const fn zeros() -> Self {
+        Self {
+            coeff: [0.0; BASIS_COUNT],
+        }
+    }
+
+    /// Constructs a multivector that consists of a single scalar.
+    pub fn scalar_value(value: f32) -> Self {
+        let mut multivector = Self::zeros();
+        multivector[0] = value;
+        multivector
+    }
+
+    /// Returns a multivector that represents the Euclidean point `<x, y, z>`.
+    pub fn point(x: f32, y: f32, z: f32) -> Self {
+        let mut multivector = Self::zeros();
+        multivector[13] = x; // e032
+        multivector[12] = y; // e013
+        multivector[11] = z; // e021
+        multivector[14] = 1.0; // e123
+        multivector
+    }
+
+    /// Returns a multivector that represents the plane `ax + by + cz + d = 0`.
+    pub fn plane(a: f32, b: f32, c: f32, d: f32) -> Self {
+        let mut multivector = Self::zeros();
+        multivector[1] = d; // e0
+        multivector[2] = a; // e1
+        multivector[3] = b; // e2
+        multivector[4] = c; // e3
+        multivector
+    }
+
+    /// Returns a multivector that represents the 2D line `ax + by + c = 0` (see `Multivector`),
+    /// embedded in the plane `z = 0`: that is, the meet of the vertical plane through the line
+    /// and the plane `z = 0`.
+    pub fn line_in_plane(a: f32, b: f32, c: f32) -> Self {
+        Self::plane(a, b, 0.0, c).meet(&Self::plane(0.0, 0.0, 1.0, 0.0))
+    }
+
+    /// Returns a motor that rotates by `angle` radians about the (Euclidean) line `line`. The
+    /// direction of the rotation depends on the orientation of the line: reversing the line
+    /// (negating it) reverses the rotation.
+    pub fn rotor(angle: f32, line: &Self) -> Self {
+        let half_angle = angle * 0.5;
+        line.normalized() * half_angle.sin() + Self::scalar_value(half_angle.cos())
+    }
+
+    /// Returns a motor that translates by `<delta_x, delta_y, delta_z>`.
+    pub fn translator(delta_x: f32, delta_y: f32, delta_z: f32) -> Self {
+        let mut multivector = Self::scalar_value(1.0);
+        multivector[5] = -0.5 * delta_x; // e01
+        multivector[6] = -0.5 * delta_y; // e02
+        multivector[7] = -0.5 * delta_z; // e03
+        multivector
+    }
+
+    /// Returns the scalar part of the multivector.
+    pub fn scalar(&self) -> f32 {
+        self.coeff[0]
+    }
+
+    /// Returns the Euclidean coordinates `[x, y, z]` of the point represented by this
+    /// multivector (which must be a finite point, i.e. have a non-zero `e123` component).
+    pub fn coordinates(&self) -> [f32; 3] {
+        let w = self.coeff[14];
+        [self.coeff[13] / w, self.coeff[12] / w, self.coeff[11] / w]
+    }
+
+    /// Reverses each element of the multivector, which negates the bivector and trivector
+    /// parts (see `Multivector::reversion`).
+    pub fn reversion(&self) -> Self {
+        let mut multivector = *self;
+        for index in 0..BASIS_COUNT {
+            let grade = blade_grade(index);
+            if grade == 2 || grade == 3 {
+                multivector[index] = -self[index];
+            }
+        }
+        multivector
+    }
+
+    /// Computes the join of two multivectors: for example, the line through two points, or the
+    /// plane through a line and a point.
+    pub fn join(&self, rhs: &Self) -> Self {
+        (*self) & (*rhs)
+    }
+
+    /// Computes the meet of two multivectors: for example, the line where two planes intersect,
+    /// or the point where a line crosses a plane.
+    pub fn meet(&self, rhs: &Self) -> Self {
+        (*self) ^ (*rhs)
+    }
+
+    /// Returns the norm of the multivector, `√|⟨A * ~A⟩₀|`.
+    pub fn norm(&self) -> f32 {
+        ((*self) * self.reversion()).scalar().abs().sqrt()
+    }
+
+    /// Returns a normalized version of the multivector.
+    pub fn normalized(&self) -> Self {
+        (*self) / self.norm()
+    }
+
+    /// Applies the motor `motor` to this multivector via the sandwich product `M * X * ~M`.
+    pub fn transformed(&self, motor: &Self) -> Self {
+        (*motor) * (*self) * motor.reversion()
+    }
+
+    /// Computes the sum of the products of each pair of blades for which `keep` returns
+    /// `true`, given the grades of the two blades and of their product (see
+    /// `backend::GenericMultivector`).
+    fn product_filtered(&self, rhs: &Self, keep: fn(u32, u32, u32) -> bool) -> Self {
+        let mut multivector = Self::zeros();
+
+        for i in 0..BASIS_COUNT {
+            if self[i] == 0.0 {
+                continue;
+            }
+            for j in 0..BASIS_COUNT {
+                if rhs[j] == 0.0 {
+                    continue;
+                }
+                if let Some((sign, k)) = backend::blade_product(&BLADES, i, j) {
+                    if keep(blade_grade(i), blade_grade(j), blade_grade(k)) {
+                        multivector[k] += sign * self[i] * rhs[j];
+                    }
+                }
+            }
+        }
+
+        multivector
+    }
+}
+
+impl Index<usize> for Multivector3 {
+    type Output = f32;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.coeff[index]
+    }
+}
+
+impl IndexMut<usize> for Multivector3 {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.coeff[index]
+    }
+}
+
+/// The geometric product.
+impl Mul for Multivector3 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.product_filtered(&rhs, |_, _, _| true)
+    }
+}
+
+/// The outer (wedge) product, i.e. the meet.
+impl BitXor for Multivector3 {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.product_filtered(&rhs, |r, s, t| t == r + s)
+    }
+}
+
+/// The inner (dot) product.
+impl BitOr for Multivector3 {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.product_filtered(&rhs, |r, s, t| t == (r as i32 - s as i32).abs() as u32)
+    }
+}
+
+/// The regressive (vee) product, i.e. the join: `!(!A ^ !B)`.
+impl BitAnd for Multivector3 {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        !(!self ^ !rhs)
+    }
+}
+
+/// The (Poincaré) dual, which simply reverses the order of the coefficients.
+impl Not for Multivector3 {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        let mut multivector = self;
+        multivector.coeff.reverse();
+        multivector
+    }
+}
+
+impl Add for Multivector3 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut multivector = self;
+        for index in 0..BASIS_COUNT {
+            multivector[index] += rhs[index];
+        }
+        multivector
+    }
+}
+
+impl Sub for Multivector3 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + (-rhs)
+    }
+}
+
+impl Neg for Multivector3 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self * -1.0
+    }
+}
+
+impl Mul<f32> for Multivector3 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        let mut multivector = self;
+        for index in 0..BASIS_COUNT {
+            multivector[index] *= rhs;
+        }
+        multivector
+    }
+}
+
+impl Div<f32> for Multivector3 {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        self * (1.0 / rhs)
+    }
+}
+
+impl Display for Multivector3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let terms = self
+            .coeff
+            .iter()
+            .enumerate()
+            .filter(|(_, coeff)| coeff.abs() > 0.00001)
+            .map(|(index, coeff)| {
+                format!(
+                    "{}{}",
+                    coeff,
+                    if index > 0 { BASIS_ELEMENTS[index] } else { "" }
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if terms.is_empty() {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", terms.join(" + "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        for index in 0..3 {
+            assert!((a[index] - b[index]).abs() < 0.0001, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_incidence() {
+        let p = Multivector3::point(1.0, 2.0, 3.0);
+        let q = Multivector3::point(4.0, 0.0, -1.0);
+        let r = Multivector3::point(0.0, 1.0, 0.0);
+
+        // The plane through three points contains all of them (its meet with each is zero)
+        let plane = p.join(&q).join(&r);
+        for point in [p, q, r].iter() {
+            assert!((plane ^ *point).norm() < 0.0001);
+            assert!(((plane ^ *point)[15]).abs() < 0.0001);
+        }
+
+        // The meet of a line with a plane is the point where they cross: the line `x = y = 0`
+        // crosses the plane `z = 2` at <0, 0, 2>
+        let axis = Multivector3::point(0.0, 0.0, 0.0).join(&Multivector3::point(0.0, 0.0, 1.0));
+        let crossing = axis.meet(&Multivector3::plane(0.0, 0.0, 1.0, -2.0));
+        println!("Crossing: {}", crossing);
+        assert_close(crossing.coordinates(), [0.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_motors() {
+        // A quarter turn about the z-axis takes <1, 0, 0> to <0, ±1, 0>
+        let axis = Multivector3::point(0.0, 0.0, 0.0).join(&Multivector3::point(0.0, 0.0, 1.0));
+        let rotor = Multivector3::rotor(std::f32::consts::FRAC_PI_2, &axis);
+        let rotated = Multivector3::point(1.0, 0.0, 0.0).transformed(&rotor);
+        println!("Rotated: {:?}", rotated.coordinates());
+        let [x, y, z] = rotated.coordinates();
+        assert!(x.abs() < 0.0001 && (y.abs() - 1.0).abs() < 0.0001 && z.abs() < 0.0001);
+
+        // Rotating about a line in the plane `z = 0` (here, `y = 1`) lifts points out of the
+        // plane, but leaves points on the line fixed
+        let line = Multivector3::line_in_plane(0.0, 1.0, -1.0);
+        let rotor = Multivector3::rotor(std::f32::consts::FRAC_PI_2, &line);
+        let fixed = Multivector3::point(5.0, 1.0, 0.0).transformed(&rotor);
+        assert_close(fixed.coordinates(), [5.0, 1.0, 0.0]);
+        let [x, y, z] = Multivector3::point(2.0, 3.0, 0.0)
+            .transformed(&rotor)
+            .coordinates();
+        assert!((x - 2.0).abs() < 0.0001 && (y - 1.0).abs() < 0.0001);
+        assert!((z.abs() - 2.0).abs() < 0.0001);
+
+        // Translators
+        let translator = Multivector3::translator(1.0, -2.0, 0.5);
+        let moved = Multivector3::point(1.0, 1.0, 1.0).transformed(&translator);
+        assert_close(moved.coordinates(), [2.0, -1.0, 1.5]);
+
+        // Motors compose by multiplication (the right-most motor is applied first)
+        let motor = translator * rotor;
+        let composed = Multivector3::point(2.0, 3.0, 0.0).transformed(&motor);
+        let sequential = Multivector3::point(2.0, 3.0, 0.0)
+            .transformed(&rotor)
+            .transformed(&translator);
+        assert_close(composed.coordinates(), sequential.coordinates());
+    }
+}
@@ -0,0 +1,301 @@
+//! Robust geometric predicates, in the style of Shewchuk's "Adaptive Precision Floating-Point
+//! Arithmetic and Fast Robust Geometric Predicates".
+//!
+//! Each predicate first evaluates its determinant in ordinary floating-point arithmetic, along
+//! with a bound on the round-off error. If the result is larger than this bound, its sign is
+//! guaranteed to be correct. Otherwise, the determinant is re-evaluated exactly, using
+//! "expansions": sums of non-overlapping floating-point numbers (in order of increasing
+//! magnitude) that together represent a value with no loss of precision.
+//!
+//! All of the predicates return `1.0`, `-1.0`, or `0.0` (exactly), which makes them drop-in
+//! replacements for `utils::sign_with_tolerance`. Since the sign is exact, the result does not
+//! depend on how the input is scaled.
+//!
+//! Reference: https://people.eecs.berkeley.edu/~jrs/papers/robustr.pdf
+
+use crate::multivector::Multivector;
+
+/// The machine epsilon for `f64`, i.e. half of the distance between 1 and the next representable
+/// number.
+const EPSILON: f64 = f64::EPSILON * 0.5;
+
+/// Used to split an `f64` into two halves with 26 bits of precision each: `2^ceil(53 / 2) + 1`.
+const SPLITTER: f64 = 134_217_729.0;
+
+/// Computes `a + b` exactly, as the rounded sum `x` and the round-off error `y`.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    let b_roundoff = b - b_virtual;
+    let a_roundoff = a - a_virtual;
+    (x, a_roundoff + b_roundoff)
+}
+
+/// Splits `a` into two non-overlapping halves `hi + lo`, each of which fits into 26 bits.
+fn split(a: f64) -> (f64, f64) {
+    let c = SPLITTER * a;
+    let a_big = c - a;
+    let hi = c - a_big;
+    (hi, a - hi)
+}
+
+/// Computes `a * b` exactly, as the rounded product `x` and the round-off error `y`.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    let (a_hi, a_lo) = split(a);
+    let (b_hi, b_lo) = split(b);
+    let error_1 = x - a_hi * b_hi;
+    let error_2 = error_1 - a_lo * b_hi;
+    let error_3 = error_2 - a_hi * b_lo;
+    (x, a_lo * b_lo - error_3)
+}
+
+/// Adds the scalar `b` to the expansion `e`, eliminating any zero components.
+fn grow_expansion(e: &[f64], b: f64) -> Vec<f64> {
+    let mut result = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+
+    for &component in e.iter() {
+        let (sum, error) = two_sum(q, component);
+        if error != 0.0 {
+            result.push(error);
+        }
+        q = sum;
+    }
+
+    if q != 0.0 || result.is_empty() {
+        result.push(q);
+    }
+    result
+}
+
+/// Adds the expansions `e` and `f`.
+fn expansion_sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(e.to_vec(), |sum, &component| {
+        grow_expansion(&sum, component)
+    })
+}
+
+/// Multiplies the expansion `e` by the scalar `b`, eliminating any zero components.
+fn scale_expansion(e: &[f64], b: f64) -> Vec<f64> {
+    let mut result = Vec::with_capacity(e.len() * 2);
+    let mut push = |component: f64| {
+        if component != 0.0 {
+            result.push(component);
+        }
+    };
+
+    let (mut q, error) = two_product(e[0], b);
+    push(error);
+
+    for &component in e.iter().skip(1) {
+        let (product, product_error) = two_product(component, b);
+        let (sum, sum_error) = two_sum(q, product_error);
+        push(sum_error);
+        let (next, next_error) = two_sum(product, sum);
+        push(next_error);
+        q = next;
+    }
+
+    push(q);
+    if result.is_empty() {
+        result.push(0.0);
+    }
+    result
+}
+
+/// Returns the sign of the value represented by the expansion `e`, which is simply the sign of
+/// its largest (i.e. last non-zero) component.
+fn expansion_sign(e: &[f64]) -> f32 {
+    match e.iter().rev().find(|&&component| component != 0.0) {
+        Some(&component) if component > 0.0 => 1.0,
+        Some(_) => -1.0,
+        None => 0.0,
+    }
+}
+
+/// Converts a point to homogeneous coordinates `(x, y, w)`.
+fn point_coordinates(p: &Multivector) -> [f64; 3] {
+    [p.e20() as f64, p.e01() as f64, p.e12() as f64]
+}
+
+/// Returns the sign of `w`, which is factored out of results so that they only depend on the
+/// Euclidean point (and not on the sign of its homogeneous coordinate).
+fn weight_sign(w: f64) -> f32 {
+    if w < 0.0 {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+/// Returns which side of the line `l` the Euclidean point `p` lies on: `1.0` or `-1.0`, or `0.0`
+/// if the point lies exactly on the line. The result doesn't depend on the weight of `p`: for a
+/// point with a positive `e12` coefficient, it matches the sign of
+/// `geometry::dist_point_to_line(p, l)`, but negating `p` (which negates that distance) leaves it
+/// unchanged.
+///
+/// Algebraically, this is the sign of `p ^ l`, i.e. `ax + by + cw` for the line `ax + by + c = 0`
+/// and the homogeneous point `(x, y, w)`, times the sign of `w`. Each product of two `f32` coefficients is exact in
+/// `f64`, so only the sum needs to be computed adaptively.
+pub fn side_of_line(p: &Multivector, l: &Multivector) -> f32 {
+    let [x, y, w] = point_coordinates(p);
+    let terms = [l.e1() as f64 * x, l.e2() as f64 * y, l.e0() as f64 * w];
+
+    let approximate = terms[0] + terms[1] + terms[2];
+    let magnitude = terms[0].abs() + terms[1].abs() + terms[2].abs();
+    let error_bound = 3.0 * EPSILON * magnitude;
+
+    let sign = if approximate > error_bound {
+        1.0
+    } else if approximate < -error_bound {
+        -1.0
+    } else {
+        let exact = terms
+            .iter()
+            .fold(vec![0.0], |sum, &term| grow_expansion(&sum, term));
+        expansion_sign(&exact)
+    };
+
+    sign * weight_sign(w)
+}
+
+/// Returns the sign of the `e12` coefficient of the meet `l0 ^ l1` of two lines, which is `0.0`
+/// if (and only if) the lines are exactly parallel.
+///
+/// Algebraically, this is the 2x2 determinant `a0 * b1 - b0 * a1` of the lines' normals. Each
+/// product is exact in `f64`, and the sign of the difference of two `f64` values is always
+/// correct, so no adaptive evaluation is needed.
+pub fn meet_weight_sign(l0: &Multivector, l1: &Multivector) -> f32 {
+    let first = l0.e1() as f64 * l1.e2() as f64;
+    let second = l0.e2() as f64 * l1.e1() as f64;
+
+    if first > second {
+        1.0
+    } else if first < second {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+/// Returns the orientation of the triangle formed by the points `p`, `q`, and `r`: `1.0` if they
+/// wind counter-clockwise, `-1.0` if they wind clockwise, or `0.0` if they are exactly collinear.
+///
+/// Algebraically, this is the sign of the 3x3 determinant of the points' homogeneous coordinates
+/// (with the sign of each point's `e12` coefficient factored out).
+pub fn orient(p: &Multivector, q: &Multivector, r: &Multivector) -> f32 {
+    let [px, py, pw] = point_coordinates(p);
+    let [qx, qy, qw] = point_coordinates(q);
+    let [rx, ry, rw] = point_coordinates(r);
+
+    // Expand along the first row (the point `p`): each minor is a 2x2 determinant
+    let minors = [
+        (px, qy * rw - ry * qw, (qy * rw).abs() + (ry * qw).abs()),
+        (-py, qx * rw - rx * qw, (qx * rw).abs() + (rx * qw).abs()),
+        (pw, qx * ry - rx * qy, (qx * ry).abs() + (rx * qy).abs()),
+    ];
+
+    let approximate: f64 = minors.iter().map(|(a, minor, _)| a * minor).sum();
+    let permanent: f64 = minors.iter().map(|(a, _, bound)| a.abs() * bound).sum();
+    let error_bound = 8.0 * EPSILON * permanent;
+
+    let sign = if approximate > error_bound {
+        1.0
+    } else if approximate < -error_bound {
+        -1.0
+    } else {
+        let minor = |a: f64, b: f64, c: f64, d: f64| {
+            let (ad, ad_error) = two_product(a, d);
+            let (bc, bc_error) = two_product(b, c);
+            expansion_sum(&[ad_error, ad], &[-bc_error, -bc])
+        };
+        let terms = [
+            scale_expansion(&minor(qy, ry, qw, rw), px),
+            scale_expansion(&minor(qx, rx, qw, rw), -py),
+            scale_expansion(&minor(qx, rx, qy, ry), pw),
+        ];
+        let exact = expansion_sum(&expansion_sum(&terms[0], &terms[1]), &terms[2]);
+        expansion_sign(&exact)
+    };
+
+    sign * weight_sign(pw) * weight_sign(qw) * weight_sign(rw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_of_line() {
+        // The line `x - y = 0`
+        let l = Multivector::line(1.0, -1.0, 0.0);
+        assert_eq!(side_of_line(&Multivector::point(1.0, 0.0), &l), 1.0);
+        assert_eq!(side_of_line(&Multivector::point(0.0, 1.0), &l), -1.0);
+        assert_eq!(side_of_line(&Multivector::point(0.5, 0.5), &l), 0.0);
+
+        // A point with a negative weight is the same Euclidean point, so it lies on the same side
+        // (unlike `geometry::dist_point_to_line`, which changes sign)
+        let p = -Multivector::point(1.0, 0.0);
+        assert!(p.e12() < 0.0);
+        assert_eq!(side_of_line(&p, &l), 1.0);
+        assert!(crate::geometry::dist_point_to_line(&p, &l) < 0.0);
+
+        // A point that is much closer to the line than `sign_with_tolerance` can resolve, at
+        // wildly different scales: the result should always be the same
+        let offset = 1.0e-6;
+        for &scale in &[1.0e-4f32, 1.0, 1.0e4] {
+            let p = Multivector::point(scale * (1.0 + offset), scale);
+            let l = Multivector::line(scale, -scale, 0.0);
+            assert_eq!(side_of_line(&p, &l), 1.0);
+            assert_eq!(side_of_line(&-p, &l), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_orient() {
+        let p = Multivector::point(0.0, 0.0);
+        let q = Multivector::point(1.0, 0.0);
+        let r = Multivector::point(0.0, 1.0);
+        assert_eq!(orient(&p, &q, &r), 1.0);
+        assert_eq!(orient(&p, &r, &q), -1.0);
+
+        // The classic near-degenerate case: points that are almost (but not quite) collinear
+        let q = Multivector::point(12.0, 12.0);
+        let r = Multivector::point(24.0, 24.0);
+        assert_eq!(orient(&p, &q, &r), 0.0);
+        let r = Multivector::point(24.0, 24.000002);
+        assert_eq!(orient(&p, &q, &r), 1.0);
+        assert_eq!(orient(&q, &p, &r), -1.0);
+
+        // Homogeneous coordinates with a negative weight describe the same point
+        let r = Multivector::point(3.0, -2.0);
+        assert_eq!(orient(&p, &q, &r), -1.0);
+        assert_eq!(orient(&-p, &q, &r), -1.0);
+    }
+
+    #[test]
+    fn test_meet_weight_sign() {
+        // Should be: the same sign as the (floating-point) meet, for lines that clearly cross
+        let l0 = Multivector::line(1.0, 2.0, -0.5);
+        let l1 = Multivector::line(-3.0, 1.0, 2.0);
+        assert_eq!(meet_weight_sign(&l0, &l1), l0.meet(&l1).e12().signum());
+        assert_eq!(meet_weight_sign(&l1, &l0), -meet_weight_sign(&l0, &l1));
+
+        // Exactly parallel lines, in any direction
+        let l0 = Multivector::line(1.0, 1.0, 0.0);
+        assert_eq!(
+            meet_weight_sign(&l0, &Multivector::line(2.0, 2.0, 1.0)),
+            0.0
+        );
+        assert_eq!(
+            meet_weight_sign(&l0, &Multivector::line(-0.1, -0.1, 3.0)),
+            0.0
+        );
+
+        // Nearly parallel lines
+        let l1 = Multivector::line(1.0, 1.0000001, 0.0);
+        assert_ne!(meet_weight_sign(&l0, &l1), 0.0);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::backend::GenericMultivector;
+use crate::scalar::Scalar;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// An exact rational number, backed by arbitrary-precision integers.
+///
+/// Origami-constructible numbers are, in general, algebraic (axioms 5 and 6 require square and
+/// cube roots), but the points and lines produced by axioms 1-4 from rational inputs always stay
+/// rational. Building multivectors out of these means that such constructions can be carried
+/// out (and checked) with no round-off error whatsoever.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rational(BigRational);
+
+/// A multivector with exact rational coefficients.
+pub type ExactMultivector = GenericMultivector<Rational>;
+
+impl Rational {
+    /// Constructs the rational number `numerator / denominator`. The denominator must be
+    /// non-zero.
+    pub fn new(numerator: i64, denominator: i64) -> Self {
+        Self(BigRational::new(
+            BigInt::from(numerator),
+            BigInt::from(denominator),
+        ))
+    }
+
+    /// Constructs the rational number `n / 1`.
+    pub fn integer(n: i64) -> Self {
+        Self::new(n, 1)
+    }
+
+    /// Returns the numerator (in lowest terms).
+    pub fn numerator(&self) -> &BigInt {
+        self.0.numer()
+    }
+
+    /// Returns the denominator (in lowest terms, always positive).
+    pub fn denominator(&self) -> &BigInt {
+        self.0.denom()
+    }
+
+    /// Returns the nearest `f64` to this rational number.
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(std::f64::NAN)
+    }
+}
+
+impl Scalar for Rational {
+    fn zero() -> Self {
+        Self(BigRational::zero())
+    }
+
+    fn one() -> Self {
+        Self(BigRational::one())
+    }
+
+    /// Every finite `f32` is a dyadic rational, so this conversion is exact.
+    fn from_f32(value: f32) -> Self {
+        Self(BigRational::from_float(value).expect("Cannot convert a non-finite float"))
+    }
+
+    fn to_f32(&self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// The square root of a rational number (in lowest terms) is rational if and only if both
+    /// its numerator and denominator are perfect squares.
+    fn sqrt(&self) -> Option<Self> {
+        if self.0.is_negative() {
+            return None;
+        }
+
+        let numerator = self.numerator().sqrt();
+        let denominator = self.denominator().sqrt();
+
+        if &(&numerator * &numerator) == self.numerator()
+            && &(&denominator * &denominator) == self.denominator()
+        {
+            Some(Self(BigRational::new(numerator, denominator)))
+        } else {
+            None
+        }
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Self(self.0 / rhs.0)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend;
+
+    /// The unit square, as exact points (in the order: ll, lr, ur, ul).
+    fn corners() -> [ExactMultivector; 4] {
+        let point = |x, y| ExactMultivector::point(Rational::integer(x), Rational::integer(y));
+        [point(0, 0), point(1, 0), point(1, 1), point(0, 1)]
+    }
+
+    #[test]
+    fn test_rational_scalars() {
+        assert_eq!(Rational::from_f32(0.375), Rational::new(3, 8));
+        assert_eq!(Rational::new(9, 4).sqrt(), Some(Rational::new(3, 2)));
+        assert_eq!(Rational::new(2, 1).sqrt(), None);
+        assert_eq!(Rational::new(-1, 4).sqrt(), None);
+        assert_eq!(format!("{}", Rational::new(2, 6)), "1/3");
+    }
+
+    #[test]
+    fn test_haga_third() {
+        // Should be exactly: <0, 1/3> (see `recipes::haga_third`)
+        let [ll, lr, ur, ul] = corners();
+        let bottom = ll.join(&lr);
+        let left = ul.join(&ll);
+
+        let midpoint = backend::midpoint(&ul, &ur);
+        let crease = backend::axiom_2(&lr, &midpoint);
+        let result = backend::reflect(&bottom, &crease)
+            .meet(&left)
+            .to_euclidean()
+            .unwrap();
+
+        println!("Haga's point: <{}, {}>", result.e20(), result.e01());
+        assert_eq!(result.e20(), Rational::zero());
+        assert_eq!(result.e01(), Rational::new(1, 3));
+
+        // The floating-point version should agree (approximately)
+        let approximate = result.to_multivector();
+        assert!((approximate.e01() - 1.0 / 3.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_exact_axioms() {
+        let [ll, lr, ur, ul] = corners();
+
+        // Should be exactly: the point <1/5, 0> (the "crossing diagonals" method applied four
+        // times, see `recipes::divide_edge`)
+        let diagonal = backend::axiom_1(&ll, &ur);
+        let bottom = backend::axiom_1(&ll, &lr);
+        let mut result = lr.clone();
+        for _ in 1..5 {
+            let crossing = backend::axiom_1(&ul, &result).meet(&diagonal);
+            result = backend::axiom_4(&crossing, &bottom)
+                .meet(&bottom)
+                .to_euclidean()
+                .unwrap();
+        }
+        assert_eq!(result.e20(), Rational::new(1, 5));
+        assert_eq!(result.e01(), Rational::zero());
+
+        // Should be: the line `x - y = 0` (the bottom edge folded onto the left edge), which
+        // only exists exactly because both edges have rational norms
+        let left = backend::axiom_1(&ll, &ul);
+        let crease = backend::axiom_3(&bottom, &left).unwrap();
+        assert!(crease.e0().is_zero());
+        assert_eq!(crease.e1(), -crease.e2());
+
+        // The bisector of the bottom edge and the diagonal involves √2, which isn't rational
+        assert!(backend::axiom_3(&bottom, &diagonal).is_none());
+    }
+}
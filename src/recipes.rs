@@ -0,0 +1,305 @@
+use crate::axioms;
+use crate::geometry;
+use crate::multivector::Multivector;
+
+/// The result of a construction: every crease that was folded along the way (in order),
+/// along with the final point or line that the construction was after.
+///
+/// All of the recipes below operate on a unit square, with the lower-left corner at the
+/// origin and the upper-right corner at `<1, 1>`.
+#[derive(Clone, Debug)]
+pub struct Construction {
+    pub creases: Vec<Multivector>,
+    pub result: Multivector,
+}
+
+/// Returns the four corners of the unit square, in the order: lower-left, lower-right,
+/// upper-right, upper-left.
+fn corners() -> [Multivector; 4] {
+    [
+        Multivector::point(0.0, 0.0),
+        Multivector::point(1.0, 0.0),
+        Multivector::point(1.0, 1.0),
+        Multivector::point(0.0, 1.0),
+    ]
+}
+
+/// Returns the edges of the unit square, in the order: bottom, right, top, left.
+fn edges() -> [Multivector; 4] {
+    let [ll, lr, ur, ul] = corners();
+    [ll.join(&lr), lr.join(&ur), ur.join(&ul), ul.join(&ll)]
+}
+
+/// Returns `true` if the (Euclidean) point `p` lies on the unit square (within tolerance).
+fn on_square(p: &Multivector) -> bool {
+    let p = (*p) / p.e12();
+    let range = -0.001..=1.001;
+    range.contains(&p.e20()) && range.contains(&p.e01())
+}
+
+/// Abe's angle trisection. Given an angle `theta` (in radians, between 0 and 90 degrees)
+/// measured counter-clockwise from the bottom edge at the lower-left corner, this returns
+/// the line through the lower-left corner at angle `theta / 3`.
+///
+/// The construction folds two creases parallel to the bottom edge (at heights `h` and `2h`),
+/// then uses axiom 6 to simultaneously place the corner onto the lower crease and the point
+/// where the upper crease meets the left edge onto the line that forms the angle. Returns
+/// `None` if the angle is too small for the folded corner to stay on the paper.
+pub fn trisect_angle(theta: f32) -> Option<Construction> {
+    let [ll, _, _, ul] = corners();
+    let [_, _, _, left] = edges();
+
+    // The line that (along with the bottom edge) forms the angle to be trisected
+    let angle_line = axioms::axiom_1(&ll, &Multivector::point(theta.cos(), theta.sin()));
+
+    // Two creases parallel to the bottom edge, at heights `h = 1/8` and `2h = 1/4`: fold the
+    // bottom edge up to the top edge, then to each new crease in turn, halving the height each
+    // time (axiom 2 places the lower-left corner onto the mark on the left edge)
+    let mut creases = vec![angle_line];
+    let mut mark = ul;
+    for _ in 0..3 {
+        let crease = axioms::axiom_2(&ll, &mark);
+        mark = crease.meet(&left);
+        mark /= mark.e12();
+        creases.push(crease);
+    }
+    let lower = creases[3];
+    let upper = creases[2];
+    let upper_mark = {
+        let mark = upper.meet(&left);
+        mark / mark.e12()
+    };
+
+    // Place the corner onto the lower crease and the upper mark onto the angle line: only one of
+    // the (up to three) solutions keeps both folded points on the paper
+    let crease = axioms::axiom_6(&ll, &upper_mark, &lower, &angle_line)
+        .into_iter()
+        .find(|crease| {
+            on_square(&geometry::reflect(&ll, crease))
+                && on_square(&geometry::reflect(&upper_mark, crease))
+        })?;
+
+    // The fold produces three congruent triangles that share the corner as a vertex, so the
+    // folded image of the corner lies on the line at `θ / 3` (and the folded image of the lower
+    // mark lies on the line at `2θ / 3`)
+    let corner_image = geometry::reflect(&ll, &crease);
+    let trisector = axioms::axiom_1(&ll, &corner_image);
+
+    creases.extend(vec![crease, trisector]);
+    Some(Construction {
+        creases,
+        result: trisector,
+    })
+}
+
+/// Messer's cube doubling. Returns the point on the left edge that divides it in the ratio
+/// `∛2 : 1` (upper segment to lower segment), from which `∛2` can be read off directly.
+///
+/// The construction divides the square into thirds with two creases parallel to the bottom
+/// edge (using Haga's first theorem for the lower one, and folding the upper-right corner down to
+/// it for the upper one), then uses axiom 6 to simultaneously place the lower-right corner onto
+/// the left edge and the point where the lower crease meets the right edge onto the upper crease.
+pub fn double_cube() -> Option<Construction> {
+    let [_, lr, ur, _] = corners();
+    let [_, right, _, left] = edges();
+
+    // Divide the square into thirds: the crease through `<0, 1/3>` that is perpendicular to the
+    // left edge, and the crease that places the upper-right corner onto where that crease meets
+    // the right edge
+    let third = haga_third();
+    let lower = axioms::axiom_4(&third.result, &left);
+    let mut lower_mark = lower.meet(&right);
+    lower_mark /= lower_mark.e12();
+    let upper = axioms::axiom_2(&ur, &lower_mark);
+
+    let crease = axioms::axiom_6(&lr, &lower_mark, &left, &upper)
+        .into_iter()
+        .find(|crease| on_square(&geometry::reflect(&lr, crease)))?;
+
+    let mut result = geometry::reflect(&lr, &crease);
+    result /= result.e12();
+
+    let mut creases = third.creases;
+    creases.extend(vec![lower, upper, crease]);
+
+    Some(Construction { creases, result })
+}
+
+/// Haga's first theorem. Folding the lower-right corner onto the midpoint of the top edge
+/// causes the bottom edge to cross the left edge at a height of `1/3`, which gives the point
+/// `<0, 1/3>`.
+pub fn haga_third() -> Construction {
+    let [_, lr, ur, ul] = corners();
+    let [bottom, _, _, left] = edges();
+
+    let midpoint_crease = axioms::axiom_2(&ul, &ur);
+    let mut midpoint = midpoint_crease.meet(&ur.join(&ul));
+    midpoint /= midpoint.e12();
+
+    let crease = axioms::axiom_2(&lr, &midpoint);
+
+    // Where the folded bottom edge crosses the left edge
+    let folded_bottom = geometry::reflect(&bottom, &crease);
+    let mut result = folded_bottom.meet(&left);
+    result /= result.e12();
+
+    Construction {
+        creases: vec![midpoint_crease, crease],
+        result,
+    }
+}
+
+/// Divides the bottom edge into `n` equal parts, returning the point `<1/n, 0>` (all other
+/// division points follow by repeated application of axiom 2).
+///
+/// This uses the "crossing diagonals" method: given the point `<1/m, 0>`, the line from the
+/// upper-left corner to that point crosses the diagonal from the lower-left corner at
+/// `x = 1 / (m + 1)`. Starting from `m = 1` (the lower-right corner), each step produces the
+/// next unit fraction exactly.
+pub fn divide_edge(n: usize) -> Construction {
+    let [ll, lr, ur, ul] = corners();
+    let [bottom, _, _, _] = edges();
+
+    let diagonal = axioms::axiom_1(&ll, &ur);
+    let mut creases = vec![diagonal];
+    let mut result = lr;
+
+    for _ in 1..n.max(1) {
+        let line = axioms::axiom_1(&ul, &result);
+        let crossing = line.meet(&diagonal);
+
+        // Drop the crossing point onto the bottom edge
+        let vertical = axioms::axiom_4(&crossing, &bottom);
+        result = vertical.meet(&bottom);
+        result /= result.e12();
+
+        creases.push(line);
+        creases.push(vertical);
+    }
+
+    Construction { creases, result }
+}
+
+/// Fujimoto's iterative approximation of the point `<1/n, 0>`, starting from an arbitrary
+/// `guess` along the bottom edge.
+///
+/// Each step folds the current pinch mark onto either the left or right edge (via axiom 2),
+/// halving the distance to that edge. The sequence of left / right folds is given by the
+/// binary expansion of `1/n`, and each full cycle reduces the error by a factor of `2^k`,
+/// where `k` is the length of that cycle. Even values of `n` are handled by first
+/// approximating the odd part of `n`, then halving towards the left edge.
+pub fn fujimoto_approximation(n: usize, guess: f32, cycles: usize) -> Construction {
+    let [ll, lr, _, _] = corners();
+
+    let mut odd = n.max(1);
+    let mut halvings = 0;
+    while odd % 2 == 0 {
+        odd /= 2;
+        halvings += 1;
+    }
+
+    let mut creases = Vec::new();
+    let mut result = Multivector::point(guess, 0.0);
+
+    // Pinch the current mark halfway towards either the left or right edge
+    let mut pinch = |towards: &Multivector, result: &mut Multivector| {
+        let crease = axioms::axiom_2(result, towards);
+        let mut pinched = geometry::midpoint(result, towards);
+        pinched /= pinched.e12();
+        creases.push(crease);
+        *result = pinched;
+    };
+
+    if odd > 1 {
+        // Track the exact fraction `numerator / odd` that the current mark approximates: halving
+        // modulo `odd` walks backwards around the cycle of `2^k / odd`
+        for _ in 0..cycles {
+            let mut numerator = 1;
+            loop {
+                let towards = if numerator % 2 == 1 { &lr } else { &ll };
+                numerator = (numerator + if numerator % 2 == 1 { odd } else { 0 }) / 2;
+                pinch(towards, &mut result);
+
+                if numerator == 1 {
+                    break;
+                }
+            }
+        }
+    } else {
+        result = lr;
+    }
+
+    for _ in 0..halvings {
+        pinch(&ll, &mut result);
+    }
+
+    Construction { creases, result }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trisect_angle() {
+        for &degrees in &[30.0f32, 45.0, 60.0, 75.0] {
+            let theta = degrees.to_radians();
+            let construction = trisect_angle(theta).unwrap();
+
+            // Should be: the creases at heights 1/2, 1/4 and 1/8 (by repeated halving)
+            let [_, _, _, left] = edges();
+            for (crease, height) in construction.creases[1..4]
+                .iter()
+                .zip([0.5, 0.25, 0.125].iter())
+            {
+                let mark = crease.meet(&left);
+                assert!((mark.e01() / mark.e12() - height).abs() < 0.001);
+            }
+
+            // The trisector passes through the origin, so its angle is given by its normal
+            let trisector = construction.result.normalized();
+            let angle = (-trisector.e1()).atan2(trisector.e2()).abs();
+            let angle = angle.min(std::f32::consts::PI - angle);
+            println!("{} degrees / 3 = {} degrees", degrees, angle.to_degrees());
+            assert!((angle - theta / 3.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_double_cube() {
+        // Should be: ∛2 ≈ 1.259921
+        let construction = double_cube().unwrap();
+
+        // Should be: the thirds are folded, rather than measured (Haga's two creases, then the
+        // creases at heights 1/3 and 2/3)
+        let [_, right, _, _] = edges();
+        for (crease, height) in construction.creases[2..4]
+            .iter()
+            .zip([1.0 / 3.0, 2.0 / 3.0].iter())
+        {
+            let mark = crease.meet(&right);
+            assert!((mark.e01() / mark.e12() - height).abs() < 0.001);
+        }
+
+        let y = construction.result.e01();
+        let ratio = (1.0 - y) / y;
+        println!("Messer's construction: {}", ratio);
+        assert!((ratio - 2.0f32.cbrt()).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_divide_edge() {
+        // Should be: <0, 1/3>
+        let third = haga_third();
+        assert!((third.result.e01() - 1.0 / 3.0).abs() < 0.001);
+
+        for n in 1..10 {
+            let construction = divide_edge(n);
+            assert!((construction.result.e20() - 1.0 / n as f32).abs() < 0.001);
+
+            let approximation = fujimoto_approximation(n, 0.5, 4);
+            println!("1/{} ≈ {}", n, approximation.result.e20());
+            assert!((approximation.result.e20() - 1.0 / n as f32).abs() < 0.001);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A number type that `backend::GenericMultivector` can be built from. Besides the usual field
+/// operations, every scalar must be convertible to and from `f32` (for interop with the rest of
+/// the crate and for display purposes).
+pub trait Scalar:
+    Clone
+    + Debug
+    + PartialEq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// The additive identity.
+    fn zero() -> Self;
+
+    /// The multiplicative identity.
+    fn one() -> Self;
+
+    /// Converts an `f32` to this scalar type (exactly, if possible).
+    fn from_f32(value: f32) -> Self;
+
+    /// Converts this scalar to the nearest `f32`.
+    fn to_f32(&self) -> f32;
+
+    /// Returns `true` if this scalar is (certainly) zero.
+    fn is_zero(&self) -> bool;
+
+    /// Returns the square root of this scalar, or `None` if it cannot be represented (for
+    /// example, the square root of a negative number, or of a rational number that isn't a
+    /// perfect square).
+    fn sqrt(&self) -> Option<Self>;
+}
+
+impl Scalar for f32 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn one() -> Self {
+        1.0
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn to_f32(&self) -> f32 {
+        *self
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0.0
+    }
+
+    fn sqrt(&self) -> Option<Self> {
+        if *self >= 0.0 {
+            Some(f32::sqrt(*self))
+        } else {
+            None
+        }
+    }
+}
+
+impl Scalar for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn one() -> Self {
+        1.0
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+
+    fn to_f32(&self) -> f32 {
+        *self as f32
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0.0
+    }
+
+    fn sqrt(&self) -> Option<Self> {
+        if *self >= 0.0 {
+            Some(f64::sqrt(*self))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,644 @@
+use crate::folding::FoldedState;
+use crate::geometry;
+use crate::interop::{self, Paper};
+use crate::inverse;
+use crate::kinematics::Assignment;
+use crate::layers::{FoldMode, LayeredState};
+use crate::multivector::Multivector;
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// An error in a fold script, along with the (1-based) line where it occurred.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ScriptError {
+    fn new(line: usize, message: &str) -> Self {
+        Self {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Number(f32),
+    Symbol(char),
+
+    /// The end of a statement (a semicolon or a newline).
+    End,
+}
+
+/// An expression: a number, a name (either a variable or a keyword like `ul`), or a call to one
+/// of the built-in functions, optionally followed by a solution index (e.g. `axiom3(a, b)[1]`).
+#[derive(Clone, Debug)]
+enum Expression {
+    Number(f32),
+    Name(String),
+    Call {
+        function: String,
+        arguments: Vec<Expression>,
+        solution: Option<usize>,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum Statement {
+    Assign {
+        name: String,
+        expression: Expression,
+    },
+    Fold {
+        crease: Expression,
+        assignment: Assignment,
+    },
+}
+
+/// The value of an expression.
+#[derive(Copy, Clone, Debug)]
+pub enum Value {
+    Number(f32),
+    Point(Multivector),
+    Line(Multivector),
+}
+
+/// A single fold performed by a script.
+#[derive(Clone, Debug)]
+pub struct Step {
+    /// The line of the script that performed the fold.
+    pub line: usize,
+    pub crease: Multivector,
+    pub assignment: Assignment,
+
+    /// The paper after this fold (and every fold before it) has been folded flat.
+    pub state: FoldedState,
+}
+
+/// The result of running a script: every fold that it performed, along with the final state of
+/// the paper and the values of its variables.
+#[derive(Clone, Debug)]
+pub struct Run {
+    pub steps: Vec<Step>,
+    pub state: FoldedState,
+    pub variables: HashMap<String, Value>,
+}
+
+/// A parsed fold script.
+///
+/// Scripts are sequences of statements, separated by semicolons or newlines (anything after a
+/// `#` is a comment). Each statement either names a value, or folds the paper along a crease:
+///
+/// ```text
+/// p1 = corner(ul)
+/// c1 = axiom2(p1, corner(lr))
+/// fold c1 valley
+/// ```
+///
+/// The built-in functions are:
+/// - `corner(ul | ur | lr | ll)` and `edge(top | right | bottom | left)`, the corners and edges
+///   of the paper
+/// - `point(x, y)` and `line(a, b, c)`, for the line `ax + by + c = 0`
+/// - `midpoint(p, q)`, `intersect(l, m)`, and `reflect(x, l)`
+/// - `axiom1` through `axiom7`, whose arguments are the points that the axiom takes followed by
+///   the lines that it takes (see `interop::axiom_arity`), and which return the crease
+///
+/// Axioms 3, 5, and 6 may produce more than one crease: by default, the first one is used, and
+/// the others can be selected by index (e.g. `axiom3(edge(top), edge(left))[1]`). Folds are
+/// either `valley` or `mountain`, and are always folded flat.
+///
+/// Folds are made one after another, through every layer of the paper as it is folded so far
+/// (see `layers::LayeredState`): each crease is a line on the folded paper, and the side of it
+/// that moves is the negative side (as in `Paper::intersect`).
+///
+/// Every value is a position on the paper as it is folded when the value is evaluated, so a
+/// variable keeps its value when the paper is folded afterwards. `corner(..)` and `edge(..)`
+/// follow the corners and edges of the sheet to wherever the folds so far have moved them. An
+/// edge that has been folded onto more than one line (by a fold across it) can't be used.
+#[derive(Clone, Debug)]
+pub struct Script {
+    statements: Vec<(usize, Statement)>,
+}
+
+impl Script {
+    /// Parses the specified script.
+    pub fn parse(source: &str) -> Result<Self, ScriptError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+
+        let mut statements = vec![];
+        while let Some((line, _)) = parser.peek() {
+            if parser.accept(&Token::End) {
+                continue;
+            }
+            statements.push((line, parser.statement()?));
+        }
+        Ok(Self { statements })
+    }
+
+    /// Runs the script on the specified paper. Fails if a crease misses the (folded) paper.
+    pub fn run(&self, paper: &Paper) -> Result<Run, ScriptError> {
+        let mut layers = LayeredState::new(&paper.vertices());
+        let mut variables = HashMap::new();
+        let mut steps = vec![];
+
+        for (line, statement) in self.statements.iter() {
+            match statement {
+                Statement::Assign { name, expression } => {
+                    let value = evaluate(*line, expression, paper, &layers, &variables)?;
+                    variables.insert(name.clone(), value);
+                }
+                Statement::Fold { crease, assignment } => {
+                    let crease = match evaluate(*line, crease, paper, &layers, &variables)? {
+                        Value::Line(crease) => crease,
+                        _ => return Err(ScriptError::new(*line, "Only lines can be folded")),
+                    };
+                    layers
+                        .fold(&crease, *assignment, None, FoldMode::AllLayers)
+                        .map_err(|message| ScriptError::new(*line, message))?;
+                    steps.push(Step {
+                        line: *line,
+                        crease,
+                        assignment: *assignment,
+                        state: layers.lift(),
+                    });
+                }
+            }
+        }
+
+        Ok(Run {
+            steps,
+            state: layers.lift(),
+            variables,
+        })
+    }
+}
+
+/// Splits the source into tokens, each tagged with the line that it appears on.
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ScriptError> {
+    let mut tokens = vec![];
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let text = text.split('#').next().unwrap_or("");
+        let mut chars = text.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+            } else if c.is_alphabetic() || c == '_' {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    name.push(c);
+                    chars.next();
+                }
+                tokens.push((line, Token::Name(name)));
+            } else if c.is_ascii_digit() || c == '-' || c == '.' {
+                let mut number = String::new();
+                number.push(c);
+                chars.next();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                    number.push(c);
+                    chars.next();
+                }
+                let number = number
+                    .parse::<f32>()
+                    .map_err(|_| ScriptError::new(line, "Invalid number"))?;
+                tokens.push((line, Token::Number(number)));
+            } else if c == ';' {
+                chars.next();
+                tokens.push((line, Token::End));
+            } else if "()[],=".contains(c) {
+                chars.next();
+                tokens.push((line, Token::Symbol(c)));
+            } else {
+                return Err(ScriptError::new(
+                    line,
+                    &format!("Unexpected character '{}'", c),
+                ));
+            }
+        }
+        tokens.push((line, Token::End));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(usize, Token)> {
+        self.tokens.get(self.position).cloned()
+    }
+
+    /// Returns the line of the current token (or the last line, at the end of the script).
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(0, |(line, _)| *line)
+    }
+
+    /// Consumes the current token if it is `token`.
+    fn accept(&mut self, token: &Token) -> bool {
+        match self.tokens.get(self.position) {
+            Some((_, current)) if current == token => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, token: &Token, message: &str) -> Result<(), ScriptError> {
+        if self.accept(token) {
+            Ok(())
+        } else {
+            Err(ScriptError::new(self.line(), message))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, ScriptError> {
+        match self.peek() {
+            Some((_, Token::Name(name))) => {
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(ScriptError::new(self.line(), "Expected a name")),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, ScriptError> {
+        let name = self.name()?;
+
+        let statement = if self.accept(&Token::Symbol('=')) {
+            Statement::Assign {
+                name,
+                expression: self.expression()?,
+            }
+        } else if name == "fold" {
+            let crease = self.expression()?;
+            let assignment = match self.name()?.as_str() {
+                "valley" => Assignment::Valley,
+                "mountain" => Assignment::Mountain,
+                _ => {
+                    return Err(ScriptError::new(
+                        self.line(),
+                        "Folds must be either valley or mountain folds",
+                    ))
+                }
+            };
+            Statement::Fold { crease, assignment }
+        } else {
+            return Err(ScriptError::new(
+                self.line(),
+                "Expected an assignment or a fold",
+            ));
+        };
+
+        self.expect(&Token::End, "Expected the end of the statement")?;
+        Ok(statement)
+    }
+
+    fn expression(&mut self) -> Result<Expression, ScriptError> {
+        if let Some((_, Token::Number(number))) = self.peek() {
+            self.position += 1;
+            return Ok(Expression::Number(number));
+        }
+
+        let name = self.name()?;
+        if !self.accept(&Token::Symbol('(')) {
+            return Ok(Expression::Name(name));
+        }
+
+        let mut arguments = vec![];
+        if !self.accept(&Token::Symbol(')')) {
+            loop {
+                arguments.push(self.expression()?);
+                if self.accept(&Token::Symbol(')')) {
+                    break;
+                }
+                self.expect(&Token::Symbol(','), "Expected ',' or ')'")?;
+            }
+        }
+
+        let solution = if self.accept(&Token::Symbol('[')) {
+            let solution = match self.peek() {
+                Some((_, Token::Number(number))) if number >= 0.0 && number.fract() == 0.0 => {
+                    number as usize
+                }
+                _ => return Err(ScriptError::new(self.line(), "Expected a solution index")),
+            };
+            self.position += 1;
+            self.expect(&Token::Symbol(']'), "Expected ']'")?;
+            Some(solution)
+        } else {
+            None
+        };
+
+        Ok(Expression::Call {
+            function: name,
+            arguments,
+            solution,
+        })
+    }
+}
+
+/// Evaluates an expression, given the paper, how it is folded so far, and the variables that have
+/// been assigned so far.
+fn evaluate(
+    line: usize,
+    expression: &Expression,
+    paper: &Paper,
+    layers: &LayeredState,
+    variables: &HashMap<String, Value>,
+) -> Result<Value, ScriptError> {
+    let (function, arguments, solution) = match expression {
+        Expression::Number(number) => return Ok(Value::Number(*number)),
+        Expression::Name(name) => {
+            return variables
+                .get(name)
+                .cloned()
+                .ok_or_else(|| ScriptError::new(line, &format!("Unknown variable '{}'", name)))
+        }
+        Expression::Call {
+            function,
+            arguments,
+            solution,
+        } => (function.as_str(), arguments, solution),
+    };
+    let error = |message: &str| ScriptError::new(line, &format!("{}: {}", function, message));
+
+    // Corners and edges are selected by keyword, rather than by value, and then followed from
+    // the sheet to where they are on the folded paper
+    if function == "corner" || function == "edge" {
+        let keyword = match arguments.as_slice() {
+            [Expression::Name(keyword)] => keyword.as_str(),
+            _ => return Err(error("Expected a single keyword")),
+        };
+        let vertices = paper.vertices();
+        let lines = paper.edges();
+        let corner = |index: usize| {
+            layers
+                .folded_point(&vertices[index])
+                .map(Value::Point)
+                .ok_or_else(|| error("The corner is not on the paper"))
+        };
+        let edge = |index: usize| {
+            layers
+                .folded_edge(&lines[index].line())
+                .map(Value::Line)
+                .ok_or_else(|| {
+                    error("The edge has been folded onto more than one line, so it can't be used")
+                })
+        };
+        return match (function, keyword) {
+            ("corner", "ul") => corner(0),
+            ("corner", "ur") => corner(1),
+            ("corner", "lr") => corner(2),
+            ("corner", "ll") => corner(3),
+            ("edge", "top") => edge(0),
+            ("edge", "right") => edge(1),
+            ("edge", "bottom") => edge(2),
+            ("edge", "left") => edge(3),
+            _ => Err(error(&format!("Unknown keyword '{}'", keyword))),
+        };
+    }
+
+    let values = arguments
+        .iter()
+        .map(|argument| evaluate(line, argument, paper, layers, variables))
+        .collect::<Result<Vec<_>, _>>()?;
+    if solution.is_some() && !function.starts_with("axiom") {
+        return Err(error("Only axioms have more than one solution"));
+    }
+
+    match (function, values.as_slice()) {
+        ("point", [Value::Number(x), Value::Number(y)]) => {
+            Ok(Value::Point(Multivector::point(*x, *y)))
+        }
+        ("line", [Value::Number(a), Value::Number(b), Value::Number(c)]) => {
+            Ok(Value::Line(Multivector::line(*a, *b, *c)))
+        }
+        ("midpoint", [Value::Point(p), Value::Point(q)]) => {
+            let midpoint = geometry::midpoint(p, q);
+            Ok(Value::Point(midpoint / midpoint.e12()))
+        }
+        ("intersect", [Value::Line(l), Value::Line(m)]) => {
+            let intersection = geometry::intersect_lines(l, m);
+            if intersection.e12().abs() < 0.0001 {
+                return Err(error("The lines are parallel"));
+            }
+            Ok(Value::Point(intersection / intersection.e12()))
+        }
+        ("reflect", [Value::Point(p), Value::Line(l)]) => {
+            let reflected = geometry::reflect(p, l);
+            Ok(Value::Point(reflected / reflected.e12()))
+        }
+        ("reflect", [Value::Line(m), Value::Line(l)]) => Ok(Value::Line(geometry::reflect(m, l))),
+        ("point", _) | ("line", _) | ("midpoint", _) | ("intersect", _) | ("reflect", _) => {
+            Err(error("Invalid arguments"))
+        }
+        _ => {
+            let axiom = function
+                .strip_prefix("axiom")
+                .and_then(|number| number.parse::<u8>().ok())
+                .filter(|&axiom| interop::axiom_arity(axiom).is_some())
+                .ok_or_else(|| error("Unknown function"))?;
+            evaluate_axiom(axiom, &values, solution.unwrap_or(0)).map_err(|message| error(message))
+        }
+    }
+}
+
+/// Evaluates an axiom, whose arguments are its points followed by its lines.
+fn evaluate_axiom(axiom: u8, values: &[Value], solution: usize) -> Result<Value, &'static str> {
+    let (point_count, line_count) = interop::axiom_arity(axiom).unwrap();
+    if values.len() != point_count + line_count {
+        return Err("Wrong number of arguments");
+    }
+
+    let mut points = vec![];
+    let mut lines = vec![];
+    for (index, value) in values.iter().enumerate() {
+        match (value, index < point_count) {
+            (Value::Point(p), true) => points.push(*p),
+            (Value::Line(l), false) => lines.push(*l),
+            _ => return Err("Expected the axiom's points, followed by its lines"),
+        }
+    }
+
+    // As in `interop::evaluate_axiom`, lines at infinity are not valid creases
+    let creases = inverse::evaluate(axiom, &points, &lines)
+        .into_iter()
+        .filter(|crease| crease.norm().abs() >= 0.001)
+        .collect::<Vec<_>>();
+    match creases.get(solution) {
+        Some(crease) => Ok(Value::Line(*crease)),
+        None if creases.is_empty() => Err("No crease exists for these inputs"),
+        None => Err("There is no solution with this index"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::paper;
+
+    #[test]
+    fn test_script() {
+        let source = "
+            # Fold the upper-left corner onto the lower-right corner, then fold in half
+            p1 = corner(ul)
+            c1 = axiom2(p1, corner(lr)); fold c1 valley
+            fold axiom3(edge(top), edge(bottom))[0] mountain
+        ";
+        let run = Script::parse(source).unwrap().run(&paper()).unwrap();
+        assert_eq!(run.steps.len(), 2);
+        assert_eq!(run.steps[0].line, 4);
+        assert_eq!(run.steps[1].assignment, Assignment::Mountain);
+
+        // Should be: the diagonal from the lower-left corner to the upper-right corner
+        let expected = Multivector::line(1.0, -1.0, 0.0);
+        println!("First crease: {}", run.steps[0].crease);
+        assert!(inverse::same_line(&run.steps[0].crease, &expected, 0.001));
+        assert_eq!(run.steps[0].state.panels.len(), 2);
+        assert_eq!(run.state.panels.len(), 4);
+
+        // Should be: everything folded flat
+        for positions in run.state.vertex_positions().iter() {
+            for &[_, _, z] in positions.iter() {
+                assert!(z.abs() < 0.001);
+            }
+        }
+
+        // Should be: <0.5, 0.5>
+        let source =
+            "p2 = intersect(axiom1(corner(ul), corner(lr)), axiom1(corner(ll), corner(ur)))";
+        let run = Script::parse(source).unwrap().run(&paper()).unwrap();
+        match run.variables["p2"] {
+            Value::Point(p) => {
+                assert!(geometry::dist_point_to_point(&p, &Multivector::point(0.5, 0.5)) < 0.001)
+            }
+            _ => panic!("Expected a point"),
+        }
+    }
+
+    #[test]
+    fn test_crossing_folds() {
+        // Fold in half, in half again, and then along a diagonal: the creases cross on the sheet,
+        // so the paper would tear if each crease were folded on its own
+        let source = "
+            fold line(1, 0, -0.5) valley
+            fold line(0, 1, -0.5) mountain
+            fold line(1, -1, 0) valley
+        ";
+        let run = Script::parse(source).unwrap().run(&paper()).unwrap();
+        assert_eq!(run.steps.len(), 3);
+
+        // Panels that share a vertex on the sheet should agree on where it lands
+        let panels = &run.state.panels;
+        for (index, a) in panels.iter().enumerate() {
+            for b in panels[index + 1..].iter() {
+                for (p, position) in a.vertices.iter().zip(a.positions().iter()) {
+                    for (q, other) in b.vertices.iter().zip(b.positions().iter()) {
+                        if geometry::dist_point_to_point(p, q) < 0.001 {
+                            for axis in 0..3 {
+                                assert!((position[axis] - other[axis]).abs() < 0.001);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Should be: everything folded into the triangle <0.5, 0.5>, <1, 0.5>, <1, 1> (the
+        // negative side of each crease moves)
+        assert_eq!(panels.len(), 8);
+        for positions in run.state.vertex_positions().iter() {
+            for &[x, y, z] in positions.iter() {
+                assert!(x > 0.499 && y > 0.499 && y < x + 0.001);
+                assert!(z.abs() < 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_folded_references() {
+        // Fold the left half over the right half: should be: the upper-left corner lands on the
+        // upper-right corner, and the left edge on the right edge
+        let source = "
+            fold line(1, 0, -0.5) valley
+            p = corner(ul)
+            e = edge(left)
+        ";
+        let run = Script::parse(source).unwrap().run(&paper()).unwrap();
+        match run.variables["p"] {
+            Value::Point(p) => {
+                println!("Upper-left corner: {}", p);
+                assert!(geometry::dist_point_to_point(&p, &Multivector::point(1.0, 1.0)) < 0.001)
+            }
+            _ => panic!("Expected a point"),
+        }
+        match run.variables["e"] {
+            Value::Line(l) => {
+                println!("Left edge: {}", l);
+                assert!(inverse::same_line(
+                    &l,
+                    &Multivector::line(1.0, 0.0, -1.0),
+                    0.001
+                ));
+            }
+            _ => panic!("Expected a line"),
+        }
+
+        // Fold the upper-left corner down across the top edge, which now lies along two lines.
+        // Should be: an error
+        let source = "
+            fold line(1, -1, 0.5) valley
+            e = edge(top)
+        ";
+        let error = Script::parse(source)
+            .and_then(|script| script.run(&paper()))
+            .unwrap_err();
+        println!("{}", error);
+        assert_eq!(error.line, 3);
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let errors = [
+            ("c = axiom1(corner(ul))", 1),
+            ("\nfold corner(ul) valley", 2),
+            ("c = axiom3(edge(top), edge(bottom))[2]", 1),
+            ("fold x valley", 1),
+            ("c = corner(middle)", 1),
+            ("c = axiom1(corner(ul), corner(lr)); fold c sideways", 1),
+            ("c = point(1, 2", 1),
+            ("c = $", 1),
+        ];
+        for (source, line) in errors.iter() {
+            let error = Script::parse(source)
+                .and_then(|script| script.run(&paper()))
+                .unwrap_err();
+            println!("{}", error);
+            assert_eq!(error.line, *line);
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use crate::history::{Fold, FoldTree};
+use crate::interop::{self, AxiomError, AxiomResult, CreaseTolerance, Line, Paper, Point};
+use crate::inverse::{self, References};
+use crate::multivector::Multivector;
+use crate::snapping::{Features, Snap};
+use crate::tolerance::{self, UncertainPoint};
+
+use wasm_bindgen::prelude::*;
+
+/// The state of an interactive folding session: the paper, the inputs to the current axiom, the
+/// crease that they produce, and the folds that have been applied so far.
+///
+/// The inputs are stored as a flat list of points: first, the points that the axiom takes, and
+/// then the two endpoints of each line that it takes (see `interop::axiom_arity`). The page only
+/// needs to forward changes to these points and render the results.
+///
+/// Applied folds are stored in a tree (see `history::FoldTree`), so undoing a fold and applying a
+/// different one (for example, the other bisector from axiom 3) keeps both branches. Applying a
+/// fold adds its crease to the reference points and lines (see `inverse::References`), along with
+/// the new points where it crosses the existing reference lines on the paper.
+///
+/// The paper itself is never folded: every crease is made on the flat sheet (like the lines of a
+/// crease pattern), so the inputs, references, and creases are all in sheet coordinates, and
+/// `AxiomResult` only shows the current crease folded on its own. Folding the sheet along a
+/// sequence of creases is handled by `layers::LayeredState` instead.
+#[wasm_bindgen]
+pub struct FoldSession {
+    paper: Paper,
+    axiom: u8,
+    inputs: Vec<Multivector>,
+    solution: Option<usize>,
+    current: Result<AxiomResult, AxiomError>,
+    tree: FoldTree,
+    references: References,
+}
+
+#[wasm_bindgen]
+impl FoldSession {
+    /// Starts a new session on the specified paper (with axiom 1 selected, but no inputs).
+    #[wasm_bindgen(constructor)]
+    pub fn new(paper: Paper) -> Self {
+        let mut session = Self {
+            paper,
+            axiom: 1,
+            inputs: vec![],
+            solution: None,
+            current: Err(AxiomError::MissingInputs),
+            tree: FoldTree::new(),
+            references: References::polygon(&paper.vertices()),
+        };
+        session.recompute();
+        session
+    }
+
+    /// Restores a session that was saved with `save` onto the specified paper.
+    pub fn restore(paper: Paper, json: &str) -> Result<FoldSession, JsError> {
+        Self::try_restore(paper, json).map_err(JsError::new)
+    }
+
+    /// Serializes the tree of applied folds (including the current state) to JSON.
+    pub fn save(&self) -> String {
+        self.tree.to_json()
+    }
+
+    /// Selects the specified axiom, with the specified inputs (see `FoldSession`), and returns
+    /// the resulting crease.
+    pub fn set_axiom(&mut self, axiom: u8, inputs: Vec<Point>) -> Result<AxiomResult, JsError> {
+        self.axiom = axiom;
+        self.inputs = inputs.into_iter().map(|p| p.into()).collect();
+        self.solution = None;
+        self.recompute();
+        self.current()
+    }
+
+    /// Moves the input at `index` to `point`, and returns the resulting crease.
+    pub fn move_point(&mut self, index: usize, point: Point) -> Result<AxiomResult, JsError> {
+        if index >= self.inputs.len() {
+            return Err(JsError::new("There is no input with this index"));
+        }
+        self.inputs[index] = point.into();
+        self.recompute();
+        self.current()
+    }
+
+    /// Selects which of the axiom's creases to use, for axioms that produce more than one (see
+    /// `AxiomResult::solution_count`), and returns it. If `solution` is `undefined`, the first
+    /// crease that can actually be folded is used.
+    pub fn set_solution(&mut self, solution: Option<usize>) -> Result<AxiomResult, JsError> {
+        self.solution = solution;
+        self.recompute();
+        self.current()
+    }
+
+    /// Returns the crease produced by the current axiom and inputs.
+    pub fn current(&self) -> Result<AxiomResult, JsError> {
+        Ok(self.current.clone()?)
+    }
+
+    /// Returns how far the current crease may be off if each input is misplaced by up to
+    /// `radius` (see `tolerance::crease_tolerance`), measured at the middle of the crease.
+    pub fn tolerance(&self, radius: f32) -> Result<CreaseTolerance, JsError> {
+        let crease: Multivector = self.current.clone()?.crease().into();
+        let segment = self
+            .paper
+            .clip(&crease)
+            .ok_or_else(|| JsError::new("The current crease misses the paper"))?;
+        let (point_count, _) = interop::axiom_arity(self.axiom).unwrap();
+
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|p| UncertainPoint::new(p, radius))
+            .collect::<Vec<_>>();
+        let report = tolerance::crease_tolerance(
+            &inputs,
+            &segment.midpoint(),
+            inverse::construction(self.axiom, point_count, &crease),
+        )
+        .ok_or_else(|| JsError::new("The current crease can't be differentiated"))?;
+        Ok(report.into())
+    }
+
+    /// Applies the current crease to the paper, starting a new branch if other folds have
+    /// already been applied to the current state. The crease is recorded (and becomes a
+    /// reference line), but the paper stays flat (see `FoldSession`).
+    pub fn apply_fold(&mut self) -> Result<(), JsError> {
+        self.try_apply_fold().map_err(JsError::new)
+    }
+
+    /// Reverts the most recently applied fold, returning `false` if there is nothing to undo.
+    ///
+    /// The axiom, inputs, and solution of the undone fold become the current ones, so that they
+    /// can be adjusted (or a different solution chosen) and applied again as a new branch.
+    pub fn undo(&mut self) -> bool {
+        match self.tree.undo().cloned() {
+            Some(fold) => {
+                self.axiom = fold.axiom;
+                self.inputs = fold.inputs.into_iter().map(|p| p.into()).collect();
+                self.solution = Some(fold.solution);
+                self.recompute();
+                self.rebuild_references();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-applies the most recently undone fold, returning `false` if there is nothing to redo.
+    ///
+    /// The inputs that `undo` restored belong to the fold that is re-applied, so they are
+    /// cleared (keeping the selected axiom), rather than left ready to be applied a second time.
+    pub fn redo(&mut self) -> bool {
+        let redone = self.tree.redo().is_some();
+        if redone {
+            self.inputs.clear();
+            self.solution = None;
+            self.recompute();
+            self.rebuild_references();
+        }
+        redone
+    }
+
+    /// Moves to the state with the specified index (see `state`), which may be on any branch.
+    pub fn checkout(&mut self, state: usize) -> Result<(), JsError> {
+        self.tree.checkout(state).map_err(JsError::new)?;
+        self.rebuild_references();
+        Ok(())
+    }
+
+    /// Returns the corner, edge, applied crease, crease intersection, or midpoint nearest to
+    /// `cursor`, if any lies within `radius` of it (see `snapping::Features::snap`).
+    pub fn snap(&self, cursor: Point, radius: f32) -> Option<Snap> {
+        let creases = self
+            .tree
+            .path()
+            .iter()
+            .map(|fold| fold.crease.into())
+            .collect::<Vec<_>>();
+        Features::new(&self.paper.vertices(), &creases)
+            .snap(&cursor.into(), radius)
+            .map(|(kind, point, distance)| Snap::new(kind, &point, distance))
+    }
+
+    /// The states that have been reached by applying a fold to the specified state.
+    pub fn branches(&self, state: usize) -> Vec<usize> {
+        match self.tree.node(state) {
+            Some(node) => node.children.clone(),
+            None => vec![],
+        }
+    }
+
+    /// The state that the specified state was reached from (or `undefined` for the flat paper).
+    pub fn parent(&self, state: usize) -> Option<usize> {
+        self.tree.node(state).and_then(|node| node.parent)
+    }
+
+    /// The index of the current state: 0 is the flat paper, and each applied fold creates a new
+    /// state.
+    #[wasm_bindgen(getter)]
+    pub fn state(&self) -> usize {
+        self.tree.current()
+    }
+
+    /// The currently selected axiom.
+    #[wasm_bindgen(getter)]
+    pub fn axiom(&self) -> u8 {
+        self.axiom
+    }
+
+    /// The inputs to the currently selected axiom (see `FoldSession`).
+    #[wasm_bindgen(getter)]
+    pub fn inputs(&self) -> Vec<Point> {
+        self.inputs.iter().map(|&p| p.into()).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_undo(&self) -> bool {
+        self.tree.can_undo()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_redo(&self) -> bool {
+        self.tree.can_redo()
+    }
+
+    /// The creases of the folds that lead to the current state, in order.
+    #[wasm_bindgen(getter)]
+    pub fn creases(&self) -> Vec<Line> {
+        self.tree.path().iter().map(|fold| fold.crease).collect()
+    }
+
+    /// The axioms that produced each of the folds that lead to the current state, in order.
+    #[wasm_bindgen(getter)]
+    pub fn axioms(&self) -> Vec<u8> {
+        self.tree.path().iter().map(|fold| fold.axiom).collect()
+    }
+
+    /// The reference points: the corners of the paper, and the points where applied creases
+    /// cross the edges of the paper (and each other).
+    #[wasm_bindgen(getter)]
+    pub fn reference_points(&self) -> Vec<Point> {
+        self.references.points.iter().map(|&p| p.into()).collect()
+    }
+
+    /// The reference lines: the edges of the paper, and the applied creases.
+    #[wasm_bindgen(getter)]
+    pub fn reference_lines(&self) -> Vec<Line> {
+        self.references.lines.iter().map(|&l| l.into()).collect()
+    }
+}
+
+impl FoldSession {
+    /// Restores a session that was saved with `save` onto the specified paper.
+    fn try_restore(paper: Paper, json: &str) -> Result<Self, &'static str> {
+        let mut session = Self::new(paper);
+        session.tree = FoldTree::from_json(json)?;
+        session.rebuild_references();
+        Ok(session)
+    }
+
+    /// Re-evaluates the current axiom with the current inputs.
+    fn recompute(&mut self) {
+        self.current = match interop::axiom_arity(self.axiom) {
+            Some((point_count, line_count))
+                if self.inputs.len() >= point_count + 2 * line_count =>
+            {
+                let points = &self.inputs[..point_count];
+                let lines = (0..line_count)
+                    .map(|index| {
+                        let src = self.inputs[point_count + 2 * index];
+                        let dst = self.inputs[point_count + 2 * index + 1];
+                        src & dst
+                    })
+                    .collect::<Vec<_>>();
+                interop::evaluate_axiom(&self.paper, self.axiom, points, &lines, self.solution)
+            }
+            Some(_) => Err(AxiomError::MissingInputs),
+            None => Err(AxiomError::UnknownAxiom(self.axiom)),
+        };
+    }
+
+    /// Applies the current crease to the paper, if it can actually be folded.
+    fn try_apply_fold(&mut self) -> Result<(), &'static str> {
+        let result = match &self.current {
+            Ok(result) => result,
+            Err(_) => return Err("There is no crease to apply"),
+        };
+        if result.reason().is_some() {
+            return Err("The current crease can't be folded on the paper");
+        }
+
+        self.tree.apply(Fold {
+            axiom: self.axiom,
+            inputs: self.inputs(),
+            solution: result.solution(),
+            crease: result.crease(),
+        });
+        self.rebuild_references();
+        Ok(())
+    }
+
+    /// Rebuilds the reference points and lines from the paper and the folds that lead to the
+    /// current state.
+    fn rebuild_references(&mut self) {
+        self.references = References::polygon(&self.paper.vertices());
+        for fold in self.tree.path() {
+            self.references.add_crease(&fold.crease.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::paper;
+
+    #[test]
+    fn test_fold_session() {
+        let mut session = FoldSession::new(paper());
+        assert!(session.current.is_err());
+        assert!(!session.can_undo());
+
+        // Axiom 2: fold the left edge onto the right edge
+        assert!(session
+            .set_axiom(2, vec![Point::new(0.0, 0.5), Point::new(1.0, 0.5)])
+            .is_ok());
+        session.try_apply_fold().unwrap();
+        assert_eq!(session.creases().len(), 1);
+
+        // The crease (and the two points where it crosses the edges of the paper) should now be
+        // available as references
+        assert_eq!(session.reference_lines().len(), 5);
+        assert_eq!(session.reference_points().len(), 6);
+
+        // Dragging an input recomputes the crease
+        let moved = session.move_point(1, Point::new(0.5, 0.5)).unwrap();
+        let crease: Multivector = moved.crease().into();
+        let expected = Multivector::line(1.0, 0.0, -0.25);
+        assert!(crate::inverse::same_line(&crease, &expected, 0.001));
+        session.try_apply_fold().unwrap();
+        assert_eq!(session.axioms(), vec![2, 2]);
+
+        // Undo and redo
+        assert!(session.undo());
+        assert_eq!(session.creases().len(), 1);
+        assert_eq!(session.reference_lines().len(), 5);
+        assert!(session.can_redo());
+        assert!(session.redo());
+        assert_eq!(session.creases().len(), 2);
+        assert!(!session.redo());
+
+        // Redoing clears the inputs that undoing restored, so the redone crease can't be
+        // applied a second time
+        assert_eq!(
+            session.current.as_ref().err(),
+            Some(&AxiomError::MissingInputs)
+        );
+        assert!(session.try_apply_fold().is_err());
+        assert_eq!(session.creases().len(), 2);
+        assert_eq!(session.tree.len(), 3);
+
+        // Undoing restores the inputs of the undone fold, so applying it again returns to the
+        // same branch
+        session.undo();
+        assert_eq!(session.inputs()[1].x, 0.5);
+        session.try_apply_fold().unwrap();
+        assert!(!session.can_redo());
+        assert_eq!(session.tree.len(), 3);
+
+        // A crease that doesn't exist can't be applied (note that errors are converted to
+        // JavaScript errors by the public methods, so the state is modified directly here)
+        session.axiom = 3;
+        session.inputs.clear();
+        session.recompute();
+        assert_eq!(
+            session.current.as_ref().err(),
+            Some(&AxiomError::MissingInputs)
+        );
+        assert!(session.try_apply_fold().is_err());
+    }
+
+    #[test]
+    fn test_fold_branches() {
+        // Axiom 3: bisect the bottom edge of the paper and the vertical line through its middle
+        // (both bisectors cut across the paper)
+        let mut session = FoldSession::new(paper());
+        let inputs = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.5, 0.0),
+            Point::new(0.5, 1.0),
+        ];
+        let result = session.set_axiom(3, inputs).unwrap();
+        assert_eq!(result.solution_count(), 2);
+        let first = result.solution();
+        session.try_apply_fold().unwrap();
+
+        // Go back and apply the other bisector: both branches are kept
+        assert!(session.undo());
+        assert_eq!(session.solution, Some(first));
+        let other = session.set_solution(Some(1 - first)).unwrap();
+        assert_eq!(other.solution(), 1 - first);
+        session.try_apply_fold().unwrap();
+        assert_eq!(session.branches(0), vec![1, 2]);
+        assert_eq!(session.parent(2), Some(0));
+        assert_eq!(session.state(), 2);
+
+        // Should be: the two creases are perpendicular
+        let a: Multivector = session.creases()[0].into();
+        session.tree.checkout(1).unwrap();
+        session.rebuild_references();
+        let b: Multivector = session.creases()[0].into();
+        println!("Branches: {}, {}", a, b);
+        assert!((a | b).scalar().abs() < 0.001);
+
+        // Saving and restoring keeps both branches (and the current state)
+        let restored = FoldSession::try_restore(paper(), &session.save()).unwrap();
+        assert_eq!(restored.state(), 1);
+        assert_eq!(restored.branches(0), vec![1, 2]);
+        assert_eq!(restored.reference_lines().len(), 5);
+        assert!(FoldSession::try_restore(paper(), "not json").is_err());
+
+        // Should be: the intersection of the applied crease with the bottom edge
+        let snap = restored.snap(Point::new(0.52, 0.01), 0.05).unwrap();
+        assert_eq!(snap.kind, crate::snapping::FeatureKind::Intersection);
+        assert!((snap.point.x - 0.5).abs() < 0.001 && snap.point.y.abs() < 0.001);
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        // Axiom 5 with the circle centered at <0.5, 0.5> through <0.5, 1>, which is exactly
+        // tangent to the bottom edge: should be a single crease, flagged as ambiguous
+        let mut session = FoldSession::new(paper());
+        let bottom = vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)];
+        let inputs = |center: Point| {
+            let mut inputs = vec![Point::new(0.5, 1.0), center];
+            inputs.extend(bottom.iter().cloned());
+            inputs
+        };
+        let result = session.set_axiom(5, inputs(Point::new(0.5, 0.5))).unwrap();
+        assert_eq!(result.solution_count(), 1);
+        assert!(result.ambiguous());
+
+        // Should be: two creases, which certainly exist
+        let result = session.set_axiom(5, inputs(Point::new(0.5, 0.4))).unwrap();
+        assert_eq!(result.solution_count(), 2);
+        assert!(!result.ambiguous());
+    }
+
+    #[test]
+    fn test_tolerance() {
+        // Axiom 2 with the midpoints of the left and right edges: each point moves the crease
+        // by half of its own error. Should be: ±0.01 units at the middle of the crease
+        let mut session = FoldSession::new(paper());
+        session
+            .set_axiom(2, vec![Point::new(0.0, 0.5), Point::new(1.0, 0.5)])
+            .unwrap();
+        let tolerance = session.tolerance(0.01).unwrap();
+        println!("±{} radians, ±{} units", tolerance.angle, tolerance.offset);
+        assert!((tolerance.offset - 0.01).abs() < 0.0001);
+
+        // Axiom 3 with the bottom and left edges: the bisector is the diagonal, through the
+        // corner where the edges meet
+        session
+            .set_axiom(
+                3,
+                vec![
+                    Point::new(0.0, 0.0),
+                    Point::new(1.0, 0.0),
+                    Point::new(0.0, 0.0),
+                    Point::new(0.0, 1.0),
+                ],
+            )
+            .unwrap();
+        let tolerance = session.tolerance(0.01).unwrap();
+        println!("±{} radians, ±{} units", tolerance.angle, tolerance.offset);
+        assert!(tolerance.angle > 0.0 && tolerance.offset.is_finite());
+    }
+}
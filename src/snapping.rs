@@ -0,0 +1,186 @@
+use crate::geometry::{self, Segment};
+use crate::interop::Point;
+use crate::multivector::Multivector;
+
+use wasm_bindgen::prelude::*;
+
+/// The kinds of features that a cursor can snap to.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FeatureKind {
+    /// A corner of the paper.
+    Corner,
+
+    /// An edge of the paper.
+    Edge,
+
+    /// An applied crease (the portion of it that lies on the paper).
+    Crease,
+
+    /// A point where a crease crosses an edge of the paper, or another crease.
+    Intersection,
+
+    /// The midpoint of an edge or a crease.
+    Midpoint,
+}
+
+/// The feature that a cursor snapped to.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug)]
+pub struct Snap {
+    pub kind: FeatureKind,
+
+    /// The point on the feature that is closest to the cursor (for points, the feature itself).
+    pub point: Point,
+
+    /// The distance from the cursor to `point`.
+    pub distance: f32,
+}
+
+/// The point and line features of a sheet of paper that has been creased.
+#[derive(Clone, Debug)]
+pub struct Features {
+    pub points: Vec<(FeatureKind, Multivector)>,
+    pub segments: Vec<(FeatureKind, Segment)>,
+}
+
+impl Features {
+    /// Collects the features of the paper (a convex polygon) with the specified creases. Creases
+    /// that miss the paper are ignored.
+    pub fn new(paper: &[Multivector], creases: &[Multivector]) -> Self {
+        let edges = (0..paper.len())
+            .map(|index| Segment::new(&paper[index], &paper[(index + 1) % paper.len()]))
+            .collect::<Vec<_>>();
+        let creases = creases
+            .iter()
+            .filter_map(|crease| geometry::clip_line(crease, paper))
+            .collect::<Vec<_>>();
+
+        let mut points = paper
+            .iter()
+            .map(|&corner| (FeatureKind::Corner, corner / corner.e12()))
+            .collect::<Vec<_>>();
+        for (index, crease) in creases.iter().enumerate() {
+            // Crossings with the edges of the paper (i.e. the endpoints of the crease), and with
+            // each of the creases after this one
+            points.push((FeatureKind::Intersection, crease.start));
+            points.push((FeatureKind::Intersection, crease.end));
+            for other in creases[index + 1..].iter() {
+                if let Some(intersection) = crease.intersect_segment(other) {
+                    points.push((FeatureKind::Intersection, intersection));
+                }
+            }
+        }
+        for segment in edges.iter().chain(creases.iter()) {
+            points.push((FeatureKind::Midpoint, segment.midpoint()));
+        }
+
+        let segments = edges
+            .into_iter()
+            .map(|edge| (FeatureKind::Edge, edge))
+            .chain(
+                creases
+                    .into_iter()
+                    .map(|crease| (FeatureKind::Crease, crease)),
+            )
+            .collect();
+
+        Self { points, segments }
+    }
+
+    /// Returns the feature nearest to `cursor`, if any lies within `radius` of it. Points (corners,
+    /// intersections, and midpoints) take precedence over edges and creases, so that a cursor
+    /// near a corner snaps to the corner rather than to one of the edges that meet there.
+    pub fn snap(
+        &self,
+        cursor: &Multivector,
+        radius: f32,
+    ) -> Option<(FeatureKind, Multivector, f32)> {
+        let nearest = |candidates: Vec<(FeatureKind, Multivector, f32)>| {
+            candidates
+                .into_iter()
+                .filter(|&(_, _, distance)| distance <= radius)
+                .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+        };
+
+        let points = self
+            .points
+            .iter()
+            .map(|&(kind, point)| (kind, point, geometry::dist_point_to_point(cursor, &point)))
+            .collect();
+
+        // Only the portion of each line that lies between the segment's endpoints counts (beyond
+        // them, the nearest point is an endpoint, which is already a point feature)
+        let segments = self
+            .segments
+            .iter()
+            .filter_map(|&(kind, segment)| {
+                let t = segment.parameter(cursor);
+                if t < 0.0 || t > 1.0 {
+                    return None;
+                }
+                let distance = geometry::dist_point_to_line(cursor, &segment.line()).abs();
+                Some((kind, segment.point_at(t), distance))
+            })
+            .collect();
+
+        nearest(points).or_else(|| nearest(segments))
+    }
+}
+
+impl Snap {
+    pub fn new(kind: FeatureKind, point: &Multivector, distance: f32) -> Self {
+        Self {
+            kind,
+            point: (*point).into(),
+            distance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::unit_square;
+
+    #[test]
+    fn test_snap() {
+        // Both diagonals of the unit square
+        let creases = [
+            Multivector::line(1.0, -1.0, 0.0),
+            Multivector::line(1.0, 1.0, -1.0),
+        ];
+        let features = Features::new(&unit_square(), &creases);
+
+        // Should be: the corner (0, 0)
+        let (kind, point, _) = features.snap(&Multivector::point(0.05, 0.02), 0.1).unwrap();
+        println!("Snapped to {:?} at {}", kind, point);
+        assert_eq!(kind, FeatureKind::Corner);
+        assert!(geometry::dist_point_to_point(&point, &Multivector::point(0.0, 0.0)) < 0.001);
+
+        // Should be: the intersection of the diagonals, at (0.5, 0.5)
+        let (kind, point, _) = features.snap(&Multivector::point(0.48, 0.53), 0.1).unwrap();
+        assert_eq!(kind, FeatureKind::Intersection);
+        assert!(geometry::dist_point_to_point(&point, &Multivector::point(0.5, 0.5)) < 0.001);
+
+        // Should be: the midpoint of the bottom edge
+        let (kind, _, _) = features.snap(&Multivector::point(0.52, 0.03), 0.1).unwrap();
+        assert_eq!(kind, FeatureKind::Midpoint);
+
+        // Should be: the bottom edge, directly below the cursor
+        let (kind, point, distance) = features.snap(&Multivector::point(0.3, 0.04), 0.1).unwrap();
+        assert_eq!(kind, FeatureKind::Edge);
+        assert!((point.e20() - 0.3).abs() < 0.001 && point.e01().abs() < 0.001);
+        assert!((distance - 0.04).abs() < 0.001);
+
+        // Should be: the diagonal through the origin
+        let (kind, point, _) = features.snap(&Multivector::point(0.2, 0.25), 0.1).unwrap();
+        assert_eq!(kind, FeatureKind::Crease);
+        assert!((point.e20() - point.e01()).abs() < 0.001);
+
+        // Should be: nothing (too far from every feature)
+        assert!(features
+            .snap(&Multivector::point(0.3, 0.15), 0.05)
+            .is_none());
+    }
+}
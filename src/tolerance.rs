@@ -0,0 +1,176 @@
+use crate::dual::{self, DualMultivector};
+use crate::multivector::Multivector;
+
+/// A point whose position is only known to within `radius` units (for example, a reference
+/// mark that a folder might misplace by up to 1mm).
+#[derive(Copy, Clone, Debug)]
+pub struct UncertainPoint {
+    pub point: Multivector,
+    pub radius: f32,
+}
+
+impl UncertainPoint {
+    /// Constructs a new uncertain point.
+    pub fn new(point: &Multivector, radius: f32) -> Self {
+        Self {
+            point: *point,
+            radius,
+        }
+    }
+}
+
+/// How much a single input contributes to the uncertainty of a crease.
+#[derive(Copy, Clone, Debug)]
+pub struct Contribution {
+    /// The (worst-case) change in the crease's angle, in radians.
+    pub angle: f32,
+
+    /// The (worst-case) change in the crease's offset, in units.
+    pub offset: f32,
+}
+
+/// The uncertainty band of a crease, as propagated from the uncertainty of its inputs.
+#[derive(Clone, Debug)]
+pub struct ToleranceReport {
+    /// The nominal crease (i.e. the crease constructed from the exact inputs).
+    pub crease: Multivector,
+
+    /// The point at which `offset` is measured.
+    pub reference: Multivector,
+
+    /// The crease's angle may be off by up to this many radians (in either direction).
+    pub angle: f32,
+
+    /// Near `reference`, the crease may be off by up to this many units (in either direction).
+    pub offset: f32,
+
+    /// The contribution of each input (in the same order as the inputs), which can be used to
+    /// figure out which inputs a construction is most sensitive to.
+    pub contributions: Vec<Contribution>,
+}
+
+impl ToleranceReport {
+    /// Returns the index of the input that contributes the most to the crease's offset.
+    pub fn most_sensitive_input(&self) -> Option<usize> {
+        (0..self.contributions.len()).max_by(|&a, &b| {
+            self.contributions[a]
+                .offset
+                .partial_cmp(&self.contributions[b].offset)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+}
+
+/// Propagates the uncertainty of each input through `construction` (see `dual::differentiate`)
+/// by linearization, returning the resulting uncertainty band of the crease. Lines are given
+/// by two (uncertain) points, joined inside of the construction.
+///
+/// Each input is allowed to move anywhere within a disk of radius `radius`. To first order, the
+/// crease's angle and offset (measured at `reference`) change linearly with each input, so the
+/// worst case of each is the sum of the worst cases of the individual inputs: this is the
+/// (conservative) bound that is reported. The bound is only accurate for errors that are small
+/// relative to the size of the construction.
+pub fn crease_tolerance<F>(
+    inputs: &[UncertainPoint],
+    reference: &Multivector,
+    construction: F,
+) -> Option<ToleranceReport>
+where
+    F: Fn(&[DualMultivector]) -> Option<DualMultivector>,
+{
+    let points = inputs.iter().map(|input| input.point).collect::<Vec<_>>();
+    let derivatives = dual::differentiate(&points, construction)?;
+    let crease = derivatives.crease;
+
+    // The angle of the (normalized) crease `ax + by + c = 0` is `atan2(b, a)`: since
+    // `a² + b² = 1`, its derivative is simply `a * db - b * da`
+    let angle_rate =
+        |derivative: &Multivector| crease.e1() * derivative.e2() - crease.e2() * derivative.e1();
+
+    let contributions = inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| {
+            let [dx, dy] = &derivatives.derivatives[index];
+            Contribution {
+                angle: input.radius * angle_rate(dx).hypot(angle_rate(dy)),
+                offset: input.radius * derivatives.sensitivity_at(reference, index),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Some(ToleranceReport {
+        crease,
+        reference: (*reference) / reference.e12(),
+        angle: contributions.iter().map(|c| c.angle).sum(),
+        offset: contributions.iter().map(|c| c.offset).sum(),
+        contributions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axioms;
+    use crate::backend;
+    use crate::geometry;
+
+    #[test]
+    fn test_crease_tolerance() {
+        // Axiom 2 with points that are far apart is robust...
+        let reference = Multivector::point(0.5, 0.5);
+        let inputs = [
+            UncertainPoint::new(&Multivector::point(0.0, 0.5), 0.01),
+            UncertainPoint::new(&Multivector::point(1.0, 0.5), 0.01),
+        ];
+        let far = crease_tolerance(&inputs, &reference, |p| {
+            Some(backend::axiom_2(&p[0], &p[1]))
+        })
+        .unwrap();
+        println!("Far apart: ±{} radians, ±{} units", far.angle, far.offset);
+
+        // Should be: each point moves the crease by half of its own error
+        assert!((far.offset - 0.01).abs() < 0.0001);
+
+        // ...but with points that are close together, the crease's angle is fragile
+        let inputs = [
+            UncertainPoint::new(&Multivector::point(0.45, 0.5), 0.01),
+            UncertainPoint::new(&Multivector::point(0.55, 0.5), 0.01),
+        ];
+        let near = crease_tolerance(&inputs, &reference, |p| {
+            Some(backend::axiom_2(&p[0], &p[1]))
+        })
+        .unwrap();
+        println!(
+            "Close together: ±{} radians, ±{} units",
+            near.angle, near.offset
+        );
+        assert!(near.angle > 5.0 * far.angle);
+
+        // Axiom 7, with each line given by two points: the bound should contain the crease
+        // produced by actually moving an input
+        let inputs = [
+            UncertainPoint::new(&Multivector::point(0.1, 0.5), 0.01),
+            UncertainPoint::new(&Multivector::point(0.0, 0.9), 0.0),
+            UncertainPoint::new(&Multivector::point(1.0, 0.9), 0.0),
+            UncertainPoint::new(&Multivector::point(0.5, 0.0), 0.01),
+            UncertainPoint::new(&Multivector::point(0.5, 1.0), 0.01),
+        ];
+        let report = crease_tolerance(&inputs, &reference, |p| {
+            backend::axiom_7(&p[0], &p[1].join(&p[2]), &p[3].join(&p[4]))
+        })
+        .unwrap();
+        let moved = axioms::axiom_7(
+            &Multivector::point(0.1, 0.5),
+            &Multivector::point(0.0, 0.9).join(&Multivector::point(1.0, 0.9)),
+            &Multivector::point(0.505, 0.0).join(&Multivector::point(0.5, 1.0)),
+        )
+        .unwrap();
+        let moved_by = geometry::dist_point_to_line(&reference, &moved)
+            - geometry::dist_point_to_line(&reference, &report.crease);
+        assert!(moved_by.abs() <= report.offset);
+
+        // The point being folded moves the crease the most
+        assert_eq!(report.most_sensitive_input(), Some(0));
+    }
+}
@@ -21,3 +21,123 @@ pub fn sign_with_tolerance(value: f32) -> f32 {
         0.0
     }
 }
+
+/// Returns the real roots of the quadratic `ax² + bx + c = 0`, in ascending order (repeated
+/// roots are only reported once). If the leading coefficient is (close to) zero, this falls
+/// back to solving the linear equation `bx + c = 0`.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return vec![];
+        }
+        return vec![-c / b];
+    }
+
+    let mut discriminant = b * b - 4.0 * a * c;
+
+    // Treat a discriminant that is within round-off of zero as a repeated root: otherwise,
+    // (for example) a line that is tangent to a conic may be reported as missing it entirely
+    // or crossing it twice
+    if discriminant.abs() <= 1e-6 * (b * b).max((4.0 * a * c).abs()) {
+        discriminant = 0.0;
+    }
+
+    if discriminant < 0.0 {
+        return vec![];
+    } else if discriminant == 0.0 {
+        return vec![-b / (2.0 * a)];
+    }
+
+    // Use the numerically stable form of the quadratic formula, which avoids the
+    // cancellation that happens when `b` and the square root have similar magnitudes
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let mut roots = if q.abs() < 1e-12 {
+        vec![0.0, 0.0]
+    } else {
+        vec![q / a, c / q]
+    };
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    roots.dedup_by(|x, y| (*x - *y).abs() < 1e-9);
+    roots
+}
+
+/// Returns the real roots of the cubic `ax³ + bx² + cx + d = 0`, in ascending order,
+/// using Cardano's method (or the trigonometric method, when there are three real
+/// roots). Repeated roots are only reported once. If the leading coefficient is (close
+/// to) zero, this falls back to solving the quadratic `bx² + cx + d = 0`.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        let mut roots = solve_quadratic(b, c, d);
+        roots.dedup_by(|x, y| (*x - *y).abs() < 1e-9);
+        return roots;
+    }
+
+    // Convert to the depressed cubic `t³ + pt + q = 0` via the substitution `x = t - b / 3a`
+    let b = b / a;
+    let c = c / a;
+    let d = d / a;
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+
+    let discriminant = (q * q) / 4.0 + (p * p * p) / 27.0;
+
+    let mut roots = if discriminant > 1e-12 {
+        // One real root
+        let sqrt_discriminant = discriminant.sqrt();
+        let u = (-q / 2.0 + sqrt_discriminant).cbrt();
+        let v = (-q / 2.0 - sqrt_discriminant).cbrt();
+        vec![u + v - shift]
+    } else if discriminant < -1e-12 {
+        // Three distinct real roots: use the trigonometric form to avoid complex arithmetic
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let phi = (3.0 * q / (p * r)).max(-1.0).min(1.0).acos() / 3.0;
+        (0..3)
+            .map(|k| r * (phi - 2.0 * std::f64::consts::PI * (k as f64) / 3.0).cos() - shift)
+            .collect()
+    } else {
+        // A repeated root (or a triple root, if `p` is also zero)
+        let u = (-q / 2.0).cbrt();
+        vec![2.0 * u - shift, -u - shift]
+    };
+
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    roots.dedup_by(|x, y| (*x - *y).abs() < 1e-9);
+    roots
+}
+
+/// Solves the square linear system `Ax = b` via Gaussian elimination (with partial pivoting),
+/// where `matrix` holds the rows of `A`. Returns `None` if the matrix is (close to) singular.
+pub fn solve_linear(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let n = rhs.len();
+
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&i, &j| {
+            matrix[i][column]
+                .abs()
+                .partial_cmp(&matrix[j][column].abs())
+                .unwrap()
+        })?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        for row in (column + 1)..n {
+            let factor = matrix[row][column] / matrix[column][column];
+            for k in column..n {
+                matrix[row][k] -= factor * matrix[column][k];
+            }
+            rhs[row] -= factor * rhs[column];
+        }
+    }
+
+    // Back substitution
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum = ((row + 1)..n).fold(rhs[row], |sum, k| sum - matrix[row][k] * solution[k]);
+        solution[row] = sum / matrix[row][row];
+    }
+    Some(solution)
+}
@@ -0,0 +1,145 @@
+use crate::geometry::{self, Segment};
+use crate::multivector::Multivector;
+
+use std::fmt::Display;
+
+/// The reasons why a crease (which is mathematically correct) may not be foldable on an actual,
+/// finite sheet of paper.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FoldError {
+    /// The crease does not cross the paper at all.
+    CreaseMissesPaper,
+
+    /// A point that the fold is supposed to move is not on the paper to begin with.
+    PointOffPaper(Multivector),
+
+    /// A point is moved off of the paper by the fold.
+    ImageOffPaper(Multivector),
+
+    /// A point is moved onto its target line, but not onto the portion of that line that lies
+    /// on the paper.
+    ImageOffTarget(Multivector),
+}
+
+impl Display for FoldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FoldError::CreaseMissesPaper => write!(f, "The crease does not cross the paper"),
+            FoldError::PointOffPaper(p) => write!(
+                f,
+                "The point <{}, {}> does not lie on the paper",
+                p.e20(),
+                p.e01()
+            ),
+            FoldError::ImageOffPaper(p) => write!(
+                f,
+                "The fold moves a point off of the paper, to <{}, {}>",
+                p.e20(),
+                p.e01()
+            ),
+            FoldError::ImageOffTarget(p) => write!(
+                f,
+                "The fold moves a point to <{}, {}>, which is not on the paper portion of its target line",
+                p.e20(),
+                p.e01()
+            ),
+        }
+    }
+}
+
+/// A point that is moved by a fold, along with the line that it is supposed to land on (if any).
+#[derive(Copy, Clone, Debug)]
+pub struct Placement {
+    pub point: Multivector,
+    pub target: Option<Multivector>,
+}
+
+impl Placement {
+    /// A point that is moved by the fold but doesn't need to land anywhere in particular (for
+    /// example, `p0` in axiom 2, which is placed onto another point).
+    pub fn free(point: &Multivector) -> Self {
+        Self {
+            point: *point,
+            target: None,
+        }
+    }
+
+    /// A point that is supposed to land on the line `target` (for example, `p0` in axiom 5).
+    pub fn onto(point: &Multivector, target: &Multivector) -> Self {
+        Self {
+            point: *point,
+            target: Some(*target),
+        }
+    }
+}
+
+/// Checks that a crease can actually be folded on the (convex) paper with the specified corners.
+/// The crease must cross the paper, and each of the `placements` must start on the paper and
+/// land on the paper portion of its target line (if it has one).
+///
+/// On success, returns the portion of the crease that lies on the paper.
+pub fn validate_fold(
+    paper: &[Multivector],
+    crease: &Multivector,
+    placements: &[Placement],
+) -> Result<Segment, FoldError> {
+    let segment = geometry::clip_line(crease, paper).ok_or(FoldError::CreaseMissesPaper)?;
+
+    for placement in placements.iter() {
+        let point = placement.point / placement.point.e12();
+
+        if !geometry::polygon_contains(paper, &point) {
+            return Err(FoldError::PointOffPaper(point));
+        }
+
+        let mut image = geometry::reflect(&point, crease);
+        image /= image.e12();
+
+        if let Some(target) = placement.target {
+            // The image lies somewhere along the (infinite) target line by construction, so we
+            // only need to check that it falls within the portion of the line on the paper
+            let on_paper = geometry::clip_line(&target, paper)
+                .map_or(false, |portion| portion.contains(&image));
+
+            if !on_paper {
+                return Err(FoldError::ImageOffTarget(image));
+            }
+        } else if !geometry::polygon_contains(paper, &image) {
+            return Err(FoldError::ImageOffPaper(image));
+        }
+    }
+
+    Ok(segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axioms;
+    use crate::fixtures::unit_square;
+
+    #[test]
+    fn test_validate_fold() {
+        // A crease that misses the paper entirely
+        let crease = Multivector::line(1.0, 0.0, -2.0);
+        assert_eq!(
+            validate_fold(&unit_square(), &crease, &[]).unwrap_err(),
+            FoldError::CreaseMissesPaper
+        );
+
+        // Axiom 7: placing <0.1, 0.5> onto the line `y = 0.9` perpendicular to `x = 0.5` works...
+        let p = Multivector::point(0.1, 0.5);
+        let l0 = Multivector::line(0.0, 1.0, -0.9);
+        let l1 = Multivector::line(1.0, 0.0, -0.5);
+        let crease = axioms::axiom_7(&p, &l0, &l1).unwrap();
+        assert!(validate_fold(&unit_square(), &crease, &[Placement::onto(&p, &l0)]).is_ok());
+
+        // ...but placing it onto `y = 1.5` (which is off of the paper) doesn't
+        let l0 = Multivector::line(0.0, 1.0, -1.5);
+        let crease = axioms::axiom_7(&p, &l0, &l1).unwrap();
+        match validate_fold(&unit_square(), &crease, &[Placement::onto(&p, &l0)]) {
+            Err(FoldError::ImageOffTarget(image)) => println!("Image: {}", image),
+            result => panic!("Expected the image to be off of the paper: {:?}", result),
+        }
+    }
+}